@@ -0,0 +1,26 @@
+// 以独立服务器运行 GitHub fixture（路由定义在 tests/common/mod.rs，与集成测试共用），
+// 在不访问 GitHub 的情况下手动检查 REST 与 GraphQL 两种数据后端。
+//
+//   cargo run --example github_fixture_server        # 默认监听 127.0.0.1:18282（FIXTURE_PORT）
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::net::SocketAddr;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().with_target(false).init();
+
+    let port = std::env::var("FIXTURE_PORT")
+        .ok()
+        .and_then(|v| v.parse::<u16>().ok())
+        .unwrap_or(18282);
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let base = format!("http://{}", addr);
+
+    tracing::info!("GitHub fixture 服务器已启动: {}", base);
+    tracing::info!("  GITHUB_API_URL={} GITHUB_RAW_URL={}/raw", base, base);
+    let listener = tokio::net::TcpListener::bind(addr).await.expect("无法绑定端口");
+    axum::serve(listener, common::fixture_router(base)).await.expect("服务器异常退出");
+}
@@ -12,6 +12,14 @@ fi
 # 服务配置
 SERVICE_NAME="xjz-website"
 SERVICE_FILE="/etc/systemd/system/${SERVICE_NAME}.service"
+SOCKET_FILE="/etc/systemd/system/${SERVICE_NAME}.socket"
+PORT=8181
+
+# --socket：由 systemd 监听端口（套接字激活），重启服务期间新连接在队列中等待而不是被拒绝
+USE_SOCKET=false
+if [ "$1" = "--socket" ]; then
+    USE_SOCKET=true
+fi
 WORK_DIR="/home/xjz/workplace/xjz-website"
 EXECUTABLE="${WORK_DIR}/target/release/xjz_website"
 
@@ -22,6 +30,36 @@ if [ ! -f "$EXECUTABLE" ]; then
     exit 1
 fi
 
+# 服务运行时 public/ 只读，预先生成静态文件的 .br / .gz / .zst
+echo "🗜️  预压缩静态文件..."
+if ! (cd "$WORK_DIR" && sudo -u xjz "$EXECUTABLE" --precompress); then
+    echo "⚠️  预压缩未完成，静态文件将改为动态压缩"
+fi
+
+# 套接字激活时先创建 socket 单元，服务通过 Requires 依赖它
+SOCKET_DEPS=""
+if [ "$USE_SOCKET" = true ]; then
+    echo "📝 创建systemd套接字文件..."
+    cat > "$SOCKET_FILE" << EOF
+[Unit]
+Description=XJZ Personal Website Socket
+
+[Socket]
+ListenStream=$PORT
+NoDelay=true
+
+[Install]
+WantedBy=sockets.target
+EOF
+    echo "✅ 套接字文件已创建: $SOCKET_FILE"
+    SOCKET_DEPS="Requires=${SERVICE_NAME}.socket
+After=${SERVICE_NAME}.socket"
+elif [ -f "$SOCKET_FILE" ]; then
+    # 不再使用套接字激活时移除之前创建的 socket 单元
+    systemctl disable --now ${SERVICE_NAME}.socket 2>/dev/null
+    rm -f "$SOCKET_FILE"
+fi
+
 # 创建systemd服务文件
 echo "📝 创建systemd服务文件..."
 cat > "$SERVICE_FILE" << EOF
@@ -29,26 +67,36 @@ cat > "$SERVICE_FILE" << EOF
 Description=XJZ Personal Website Server
 Documentation=https://github.com/xjz6626/xjz-website
 After=network.target
+$SOCKET_DEPS
 
 [Service]
-Type=simple
+# 服务开始接受请求后通过 sd_notify 发送 READY=1，并定期发送 watchdog 心跳
+Type=notify
+NotifyAccess=main
+WatchdogSec=30
 User=xjz
 Group=xjz
 WorkingDirectory=$WORK_DIR
 ExecStart=$EXECUTABLE
 Restart=always
 RestartSec=5
+# 收到 SIGTERM 后最多用 SHUTDOWN_TIMEOUT_SECS 等待请求完成，再用同样的时间等待后台刷新写完报告
+TimeoutStopSec=75
 StandardOutput=journal
 StandardError=journal
 
 # Environment
 Environment=RUST_LOG=info
+Environment=PORT=$PORT
+Environment=SHUTDOWN_TIMEOUT_SECS=30
 
 # Security settings
 NoNewPrivileges=true
 ProtectSystem=strict
 ProtectHome=false
 ReadWritePaths=$WORK_DIR/data
+# 可选的 Unix 套接字（UNIX_SOCKET）放在 /run/xjz-website/ 中
+RuntimeDirectory=$SERVICE_NAME
 PrivateTmp=true
 
 # Network
@@ -67,10 +115,18 @@ systemctl daemon-reload
 # 启用服务（开机自启）
 echo "⚡ 启用服务（开机自启）..."
 systemctl enable $SERVICE_NAME
+if [ "$USE_SOCKET" = true ]; then
+    systemctl enable ${SERVICE_NAME}.socket
+fi
 
-# 启动服务
+# 启动服务（Type=notify：服务就绪后 start 才返回）
 echo "🚀 启动服务..."
-systemctl start $SERVICE_NAME
+if [ "$USE_SOCKET" = true ]; then
+    # 先停止服务，释放它自己绑定的端口，再由 socket 单元接管
+    systemctl stop $SERVICE_NAME
+    systemctl start ${SERVICE_NAME}.socket
+fi
+systemctl restart $SERVICE_NAME
 
 # 检查服务状态
 echo "📊 检查服务状态..."
@@ -87,4 +143,4 @@ echo "  重启服务: sudo systemctl restart $SERVICE_NAME"
 echo "  停止服务: sudo systemctl stop $SERVICE_NAME"
 echo "  禁用服务: sudo systemctl disable $SERVICE_NAME"
 echo ""
-echo "网站地址: http://localhost:8181"
\ No newline at end of file
+echo "网站地址: http://localhost:$PORT"
\ No newline at end of file
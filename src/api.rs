@@ -1,131 +1,218 @@
+// JSON API 的统一响应格式和错误类型
+//
+// 所有 /api 接口都返回同一种信封：成功时 success 为 true 并携带 data，
+// 失败时返回对应的 HTTP 状态码，并在 code 中给出稳定的错误码，前端按 code 判断而不是解析 message。
+
 use axum::{
-    response::Json,
-    extract::Path,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
-use serde_json::json;
-use crate::github::GitHubClient;
-
-// 获取所有仓库
-pub async fn get_repos() -> Json<serde_json::Value> {
-    match GitHubClient::new() {
-        Ok(client) => {
-            match client.get_featured_repos().await {
-                Ok(repos) => Json(json!({
-                    "success": true,
-                    "data": repos,
-                    "timestamp": chrono::Utc::now()
-                })),
-                Err(e) => Json(json!({
-                    "success": false,
-                    "error": format!("获取仓库失败: {}", e),
-                    "timestamp": chrono::Utc::now()
-                })),
-            }
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub message: String,
+    /// 失败时的错误码，成功时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    pub last_updated: Option<String>,
+    pub degraded: bool, // 数据来自过期缓存（GitHub 不可用或离线模式）
+}
+
+impl<T> ApiResponse<T> {
+    pub fn ok(data: T, message: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            data: Some(data),
+            message: message.into(),
+            code: None,
+            last_updated: None,
+            degraded: false,
         }
-        Err(e) => Json(json!({
-            "success": false,
-            "error": format!("GitHub客户端初始化失败: {}", e),
-            "timestamp": chrono::Utc::now()
-        })),
+    }
+
+    /// 带上 GitHub 数据的更新时间和降级标记
+    pub fn with_data_info(mut self, last_updated: DateTime<Utc>, degraded: bool) -> Self {
+        self.last_updated = Some(last_updated.to_rfc3339());
+        self.degraded = degraded;
+        self
     }
 }
 
-// 获取用户信息
-pub async fn get_user() -> Json<serde_json::Value> {
-    match GitHubClient::new() {
-        Ok(client) => {
-            match client.get_user_info().await {
-                Ok(user) => Json(json!({
-                    "success": true,
-                    "data": user,
-                    "timestamp": chrono::Utc::now()
-                })),
-                Err(e) => Json(json!({
-                    "success": false,
-                    "error": format!("获取用户信息失败: {}", e),
-                    "timestamp": chrono::Utc::now()
-                })),
-            }
+impl ApiResponse<()> {
+    /// 只有提示信息、没有数据的成功响应
+    pub fn message(message: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            data: None,
+            message: message.into(),
+            code: None,
+            last_updated: None,
+            degraded: false,
         }
-        Err(e) => Json(json!({
-            "success": false,
-            "error": format!("GitHub客户端初始化失败: {}", e),
-            "timestamp": chrono::Utc::now()
-        })),
     }
 }
 
-// 获取指定仓库详情
-pub async fn get_repo_details(Path(repo_name): Path<String>) -> Json<serde_json::Value> {
-    match GitHubClient::new() {
-        Ok(client) => {
-            match client.get_repo_details(&repo_name).await {
-                Ok(repo) => Json(json!({
-                    "success": true,
-                    "data": repo,
-                    "timestamp": chrono::Utc::now()
-                })),
-                Err(e) => Json(json!({
-                    "success": false,
-                    "error": format!("获取仓库详情失败: {}", e),
-                    "timestamp": chrono::Utc::now()
-                })),
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+/// 稳定的错误码，序列化为 snake_case，新增可以，已有的不要改名
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    ProjectNotFound,
+    ArticleNotFound,
+    JobNotFound,
+    InvalidRequest,
+    Unauthorized,
+    /// 来源地址不在允许列表中
+    Forbidden,
+    RateLimited,
+    /// 功能未配置，如缺少 Token 或 API 密钥
+    NotConfigured,
+    /// 离线模式下无法访问 GitHub
+    Offline,
+    /// GitHub 数据获取失败且没有可用的缓存
+    DataUnavailable,
+    /// 调用的外部服务返回错误
+    UpstreamError,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::NotFound | ErrorCode::ProjectNotFound | ErrorCode::ArticleNotFound | ErrorCode::JobNotFound => {
+                StatusCode::NOT_FOUND
             }
+            ErrorCode::InvalidRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::NotConfigured | ErrorCode::Offline | ErrorCode::DataUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::UpstreamError => StatusCode::BAD_GATEWAY,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
         }
-        Err(e) => Json(json!({
-            "success": false,
-            "error": format!("GitHub客户端初始化失败: {}", e),
-            "timestamp": chrono::Utc::now()
-        })),
     }
 }
 
-// 获取指定仓库的语言统计
-pub async fn get_repo_languages(Path(repo_name): Path<String>) -> Json<serde_json::Value> {
-    match GitHubClient::new() {
-        Ok(client) => {
-            match client.get_repo_languages(&repo_name).await {
-                Ok(languages) => Json(json!({
-                    "success": true,
-                    "data": languages,
-                    "timestamp": chrono::Utc::now()
-                })),
-                Err(e) => Json(json!({
-                    "success": false,
-                    "error": format!("获取语言统计失败: {}", e),
-                    "timestamp": chrono::Utc::now()
-                })),
-            }
+/// API 错误，转换为带状态码的统一信封
+#[derive(Debug)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// 429/503 时告诉客户端多久后重试（秒）
+    pub retry_after: Option<u64>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retry_after: None,
         }
-        Err(e) => Json(json!({
-            "success": false,
-            "error": format!("GitHub客户端初始化失败: {}", e),
-            "timestamp": chrono::Utc::now()
-        })),
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidRequest, message)
+    }
+
+    pub fn with_retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds.max(1));
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.code.status()
     }
 }
 
-// 获取指定仓库的最新提交
-pub async fn get_repo_commits(Path(repo_name): Path<String>) -> Json<serde_json::Value> {
-    match GitHubClient::new() {
-        Ok(client) => {
-            match client.get_repo_commits(&repo_name, 10).await {
-                Ok(commits) => Json(json!({
-                    "success": true,
-                    "data": commits,
-                    "timestamp": chrono::Utc::now()
-                })),
-                Err(e) => Json(json!({
-                    "success": false,
-                    "error": format!("获取提交记录失败: {}", e),
-                    "timestamp": chrono::Utc::now()
-                })),
-            }
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: self.message,
+            code: Some(self.code),
+            last_updated: None,
+            degraded: false,
+        };
+        let mut response = (self.code.status(), Json(body)).into_response();
+        if let Some(seconds) = self.retry_after {
+            response.headers_mut().insert(header::RETRY_AFTER, seconds.into());
         }
-        Err(e) => Json(json!({
-            "success": false,
-            "error": format!("GitHub客户端初始化失败: {}", e),
-            "timestamp": chrono::Utc::now()
-        })),
+        response
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+    use serde_json::Value;
+
+    async fn body_json(response: Response) -> Value {
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn error_uses_status_code_and_envelope() {
+        let response = ApiError::new(ErrorCode::ProjectNotFound, "项目不存在").into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(!response.headers().contains_key(header::RETRY_AFTER));
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["code"], "project_not_found");
+        assert_eq!(body["message"], "项目不存在");
+        assert!(body["data"].is_null());
+    }
+
+    #[tokio::test]
+    async fn rate_limited_error_has_retry_after() {
+        let response = ApiError::new(ErrorCode::RateLimited, "请求过于频繁").with_retry_after(0).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        assert_eq!(body_json(response).await["code"], "rate_limited");
+    }
+
+    #[tokio::test]
+    async fn success_omits_code() {
+        let response = ApiResponse::ok(vec![1, 2], "ok")
+            .with_data_info(DateTime::<Utc>::UNIX_EPOCH, true)
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"], serde_json::json!([1, 2]));
+        assert_eq!(body["degraded"], true);
+        assert_eq!(body["last_updated"], "1970-01-01T00:00:00+00:00");
+        assert!(body.get("code").is_none());
+    }
+
+    #[test]
+    fn error_codes_map_to_statuses() {
+        let cases = [
+            (ErrorCode::InvalidRequest, StatusCode::BAD_REQUEST),
+            (ErrorCode::Unauthorized, StatusCode::UNAUTHORIZED),
+            (ErrorCode::Forbidden, StatusCode::FORBIDDEN),
+            (ErrorCode::Offline, StatusCode::SERVICE_UNAVAILABLE),
+            (ErrorCode::UpstreamError, StatusCode::BAD_GATEWAY),
+            (ErrorCode::Internal, StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (code, status) in cases {
+            assert_eq!(code.status(), status, "{:?}", code);
+        }
+    }
+}
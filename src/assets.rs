@@ -0,0 +1,214 @@
+// 静态资源：内容哈希 URL、预压缩文件和缓存头
+//
+// 启动时扫描 public/ 计算每个文件的内容哈希，模板通过 assets::url 引用
+// /assets/<哈希>/css/style.css 这样的地址，内容变化后 URL 随之变化，因此可以长期缓存。
+// 可压缩的文件旁边会生成 .br / .gz / .zst，由 ServeDir 按 Accept-Encoding 直接返回；
+// public/ 只读（如 systemd 的 ProtectSystem=strict）时请在部署时运行 `xjz_website --precompress`。
+
+use async_compression::tokio::write::{BrotliEncoder, GzipEncoder, ZstdEncoder};
+use async_compression::Level;
+use axum::{
+    extract::{Path, Request},
+    http::{header, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tokio::io::AsyncWriteExt;
+use tower::ServiceExt;
+use tower_http::services::ServeDir;
+
+pub const PUBLIC_DIR: &str = "public";
+/// 哈希 URL 的缓存时间：一年，且声明为 immutable
+pub const IMMUTABLE_CACHE: &str = "public, max-age=31536000, immutable";
+/// 不带哈希的静态文件缓存时间
+pub const STATIC_CACHE: &str = "public, max-age=3600";
+
+/// 值得预压缩的文本类文件
+const COMPRESSIBLE: &[&str] = &["css", "js", "mjs", "svg", "html", "txt", "json", "xml", "map", "webmanifest"];
+/// 太小的文件压缩后收益不大
+const MIN_COMPRESS_SIZE: u64 = 512;
+const VARIANTS: &[&str] = &["br", "gz", "zst"];
+
+/// 站点路径（如 /css/style.css）到内容哈希的映射
+static MANIFEST: OnceLock<HashMap<String, String>> = OnceLock::new();
+/// 预压缩文件都是最新的才启用，避免返回过期内容
+static PRECOMPRESSED: AtomicBool = AtomicBool::new(false);
+
+/// 一次扫描的结果
+#[derive(Debug, Default)]
+pub struct AssetSummary {
+    pub files: usize,
+    pub generated: usize,
+    /// 缺少预压缩文件（目录只读时），请求时改为动态压缩
+    pub missing: usize,
+    /// 预压缩文件比源文件旧且无法更新
+    pub stale: usize,
+}
+
+/// 扫描 public/，建立哈希清单并补齐预压缩文件；可重复调用，清单只在第一次设置
+pub async fn prepare() -> AssetSummary {
+    let mut summary = AssetSummary::default();
+    let mut manifest = HashMap::new();
+
+    for path in list_files(FsPath::new(PUBLIC_DIR)) {
+        let data = match tokio::fs::read(&path).await {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("读取静态文件 {} 失败: {}", path.display(), e);
+                continue;
+            }
+        };
+        summary.files += 1;
+
+        let Ok(relative) = path.strip_prefix(PUBLIC_DIR) else { continue };
+        let site_path = format!("/{}", relative.to_string_lossy().replace('\\', "/"));
+        manifest.insert(site_path, hex::encode(Sha256::digest(&data))[..10].to_string());
+
+        if is_compressible(&path, data.len() as u64) {
+            precompress(&path, &data, &mut summary).await;
+        }
+    }
+
+    let _ = MANIFEST.set(manifest);
+    PRECOMPRESSED.store(summary.stale == 0, Ordering::Relaxed);
+    summary
+}
+
+fn list_files(dir: &FsPath) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(list_files(&path));
+        } else if !path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| VARIANTS.contains(&e))
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+fn is_compressible(path: &FsPath, size: u64) -> bool {
+    size >= MIN_COMPRESS_SIZE
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| COMPRESSIBLE.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// 生成缺失或过期的 .br / .gz / .zst
+async fn precompress(path: &FsPath, data: &[u8], summary: &mut AssetSummary) {
+    let modified = |p: &FsPath| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    let source_modified = modified(path);
+
+    for variant in VARIANTS {
+        let target = PathBuf::from(format!("{}.{}", path.display(), variant));
+        let existing = modified(&target);
+        if existing.is_some() && existing >= source_modified {
+            continue;
+        }
+
+        let result = match compress(variant, data).await {
+            Ok(compressed) => tokio::fs::write(&target, compressed).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => summary.generated += 1,
+            Err(e) if existing.is_some() => {
+                tracing::warn!("无法更新过期的预压缩文件 {}: {}", target.display(), e);
+                summary.stale += 1;
+            }
+            Err(e) => {
+                tracing::debug!("无法生成预压缩文件 {}: {}", target.display(), e);
+                summary.missing += 1;
+            }
+        }
+    }
+}
+
+async fn compress(variant: &str, data: &[u8]) -> std::io::Result<Vec<u8>> {
+    match variant {
+        "br" => {
+            let mut encoder = BrotliEncoder::with_quality(Vec::new(), Level::Best);
+            encoder.write_all(data).await?;
+            encoder.shutdown().await?;
+            Ok(encoder.into_inner())
+        }
+        "gz" => {
+            let mut encoder = GzipEncoder::with_quality(Vec::new(), Level::Best);
+            encoder.write_all(data).await?;
+            encoder.shutdown().await?;
+            Ok(encoder.into_inner())
+        }
+        _ => {
+            let mut encoder = ZstdEncoder::with_quality(Vec::new(), Level::Best);
+            encoder.write_all(data).await?;
+            encoder.shutdown().await?;
+            Ok(encoder.into_inner())
+        }
+    }
+}
+
+/// 模板中引用静态文件的地址：清单中有的返回带哈希的 URL，否则原样返回
+pub fn url(path: &str) -> String {
+    match MANIFEST.get().and_then(|manifest| manifest.get(path)) {
+        Some(hash) => format!("/assets/{}{}", hash, path),
+        None => path.to_string(),
+    }
+}
+
+/// public/ 的文件服务，预压缩文件都有效时按 Accept-Encoding 返回对应版本
+pub fn static_dir() -> ServeDir {
+    let dir = ServeDir::new(PUBLIC_DIR);
+    if PRECOMPRESSED.load(Ordering::Relaxed) {
+        dir.precompressed_br().precompressed_gzip().precompressed_zstd()
+    } else {
+        dir
+    }
+}
+
+/// 不带哈希的静态文件只缓存一小时，错误响应不缓存
+pub fn static_cache_control<B>(response: &axum::http::Response<B>) -> Option<HeaderValue> {
+    response
+        .status()
+        .is_success()
+        .then(|| HeaderValue::from_static(STATIC_CACHE))
+}
+
+/// 预压缩文件按 Accept-Encoding 选择，缓存（尤其是 CDN）必须区分编码
+pub fn vary() -> HeaderValue {
+    HeaderValue::from_static("accept-encoding")
+}
+
+/// GET /assets/{hash}/{*path}：哈希与当前内容一致时长期缓存；
+/// 不一致（页面引用了部署前的旧地址）时仍返回当前文件，但要求重新验证
+pub async fn hashed_asset(Path((hash, path)): Path<(String, String)>, mut request: Request) -> Response {
+    let current = MANIFEST.get().and_then(|manifest| manifest.get(&format!("/{}", path)));
+    let Ok(uri) = format!("/{}", path).parse::<Uri>() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    *request.uri_mut() = uri;
+
+    let mut response = match static_dir().oneshot(request).await {
+        Ok(response) => response.into_response(),
+        Err(never) => match never {},
+    };
+    if response.status().is_success() {
+        let cache_control = if current == Some(&hash) { IMMUTABLE_CACHE } else { "no-cache" };
+        let headers = response.headers_mut();
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+        headers.entry(header::VARY).or_insert_with(vary);
+    }
+    response
+}
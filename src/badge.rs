@@ -0,0 +1,309 @@
+// 项目徽章：根据缓存的项目数据生成 shields.io 风格的 SVG 徽章
+//
+// 文字宽度按 Verdana 11px 的字宽近似计算，中日韩等全角字符按整个字号计宽，
+// 保证中文标签不会溢出或留下大片空白。颜色只接受预设名称或十六进制值，避免注入 SVG。
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::github::{GitHubData, StoredProject};
+
+const FONT_SIZE: f32 = 11.0;
+const PADDING: f32 = 5.0;
+const DEFAULT_LABEL_COLOR: &str = "#555";
+
+/// 徽章外观参数，字段名与 shields.io 保持一致，便于替换现有链接
+#[derive(Debug, Default, Deserialize)]
+pub struct BadgeQuery {
+    pub label: Option<String>,
+    pub color: Option<String>,
+    #[serde(rename = "labelColor")]
+    pub label_color: Option<String>,
+    pub style: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeStyle {
+    Flat,
+    FlatSquare,
+    Plastic,
+}
+
+impl BadgeStyle {
+    /// 解析 style 参数，未知取值使用 flat
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("flat-square") => BadgeStyle::FlatSquare,
+            Some("plastic") => BadgeStyle::Plastic,
+            _ => BadgeStyle::Flat,
+        }
+    }
+}
+
+/// 一个徽章的内容：左侧标签和右侧数值
+pub struct Badge {
+    pub label: String,
+    pub message: String,
+    pub color: String,
+}
+
+/// 支持的指标，对应 /badges/{repo}/{metric}.svg 中的 metric
+pub fn metric_badge(data: &GitHubData, project: &StoredProject, metric: &str) -> Option<Badge> {
+    let badge = match metric {
+        "stars" => Badge {
+            label: "stars".to_string(),
+            message: format_count(project.stargazers_count),
+            color: "#007ec6".to_string(),
+        },
+        "forks" => Badge {
+            label: "forks".to_string(),
+            message: format_count(project.forks_count),
+            color: "#007ec6".to_string(),
+        },
+        "language" => Badge {
+            label: "language".to_string(),
+            message: project.language.clone().unwrap_or_else(|| "none".to_string()),
+            color: "#007ec6".to_string(),
+        },
+        "last-updated" => {
+            let updated = project.pushed_at.unwrap_or(project.updated_at);
+            Badge {
+                label: "last updated".to_string(),
+                message: updated.format("%Y-%m-%d").to_string(),
+                color: age_color(updated, data.last_updated).to_string(),
+            }
+        }
+        "articles" => {
+            let count = data.articles.iter().filter(|a| a.repo_name == project.name).count();
+            Badge {
+                label: "articles".to_string(),
+                message: format_count(count as u32),
+                color: if count > 0 { "#4c1" } else { "#9f9f9f" }.to_string(),
+            }
+        }
+        _ => return None,
+    };
+    Some(badge)
+}
+
+/// 按最近更新距数据刷新时的天数选择颜色，越新越绿
+fn age_color(updated: DateTime<Utc>, now: DateTime<Utc>) -> &'static str {
+    match (now - updated).num_days() {
+        ..=7 => "#4c1",
+        8..=30 => "#97ca00",
+        31..=180 => "#dfb317",
+        181..=365 => "#fe7d37",
+        _ => "#9f9f9f",
+    }
+}
+
+/// 大数字缩写为 1.2k / 3.4M
+fn format_count(count: u32) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}k", count as f64 / 1_000.0).replace(".0k", "k"),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0).replace(".0M", "M"),
+    }
+}
+
+/// 解析颜色参数：shields.io 的颜色名或 3/6 位十六进制（可省略 #），无效时返回 None
+pub fn parse_color(value: &str) -> Option<String> {
+    let named = match value.to_ascii_lowercase().as_str() {
+        "brightgreen" | "success" => "#4c1",
+        "green" => "#97ca00",
+        "yellowgreen" => "#a4a61d",
+        "yellow" => "#dfb317",
+        "orange" | "important" => "#fe7d37",
+        "red" | "critical" => "#e05d44",
+        "blue" | "informational" => "#007ec6",
+        "lightgrey" | "lightgray" | "inactive" => "#9f9f9f",
+        "grey" | "gray" => "#555",
+        "blueviolet" => "#8a2be2",
+        _ => "",
+    };
+    if !named.is_empty() {
+        return Some(named.to_string());
+    }
+
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    (matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| format!("#{}", hex))
+}
+
+/// 字符在 Verdana 11px 下的近似宽度
+fn char_width(c: char) -> f32 {
+    if is_wide(c) {
+        return FONT_SIZE;
+    }
+    match c {
+        ' ' => 3.9,
+        'i' | 'l' | 'I' | '.' | ',' | ':' | ';' | '|' | '!' | '\'' => 3.3,
+        'f' | 'j' | 'r' | 't' | '(' | ')' | '[' | ']' | '-' => 4.6,
+        'm' | 'w' | 'M' | 'W' => 9.9,
+        '0'..='9' => 7.0,
+        'A'..='Z' => 7.5,
+        _ => 6.5,
+    }
+}
+
+/// 东亚全角字符和常见 emoji
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1FAFF
+        | 0x20000..=0x3FFFD
+    )
+}
+
+pub fn text_width(text: &str) -> f32 {
+    text.chars().map(char_width).sum()
+}
+
+/// 浅色背景上使用深色文字
+fn text_colors(background: &str) -> (&'static str, &'static str) {
+    let hex = background.trim_start_matches('#');
+    let channel = |i: usize| -> f32 {
+        let value = if hex.len() == 3 {
+            u8::from_str_radix(&hex[i..i + 1].repeat(2), 16)
+        } else {
+            u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+        };
+        value.unwrap_or(0) as f32 / 255.0
+    };
+    let luminance = 0.299 * channel(0) + 0.587 * channel(1) + 0.114 * channel(2);
+    if luminance > 0.65 {
+        ("#333", "#ccc")
+    } else {
+        ("#fff", "#010101")
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 渲染徽章，label_color 为 None 时使用默认的深灰色
+pub fn render(badge: &Badge, label_color: Option<&str>, style: BadgeStyle) -> String {
+    let label_color = label_color.unwrap_or(DEFAULT_LABEL_COLOR);
+    let label_width = (text_width(&badge.label) + PADDING * 2.0).round();
+    let message_width = (text_width(&badge.message) + PADDING * 2.0).round();
+    let width = label_width + message_width;
+
+    let (height, radius, text_y, gradient) = match style {
+        BadgeStyle::Flat => (
+            20.0,
+            3,
+            14,
+            r##"<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>"##,
+        ),
+        BadgeStyle::FlatSquare => (20.0, 0, 14, ""),
+        BadgeStyle::Plastic => (
+            18.0,
+            4,
+            13,
+            r##"<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#fff" stop-opacity=".7"/><stop offset=".1" stop-color="#aaa" stop-opacity=".1"/><stop offset=".9" stop-opacity=".3"/><stop offset="1" stop-opacity=".5"/></linearGradient>"##,
+        ),
+    };
+    let overlay = if gradient.is_empty() {
+        String::new()
+    } else {
+        format!(r#"<rect width="{}" height="{}" fill="url(#s)"/>"#, width, height)
+    };
+
+    let label = escape(&badge.label);
+    let message = escape(&badge.message);
+    let text = |content: &str, x: f32, background: &str| {
+        let (fill, shadow) = text_colors(background);
+        let shadow = if style == BadgeStyle::FlatSquare {
+            String::new()
+        } else {
+            format!(
+                r#"<text x="{}" y="{}" fill="{}" fill-opacity=".3">{}</text>"#,
+                x,
+                text_y + 1,
+                shadow,
+                content
+            )
+        };
+        format!(r#"{}<text x="{}" y="{}" fill="{}">{}</text>"#, shadow, x, text_y, fill, content)
+    };
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" role="img" aria-label="{label}: {message}">"#,
+            "<title>{label}: {message}</title>{gradient}",
+            r##"<clipPath id="r"><rect width="{w}" height="{h}" rx="{rx}" fill="#fff"/></clipPath>"##,
+            r#"<g clip-path="url(#r)"><rect width="{lw}" height="{h}" fill="{lc}"/><rect x="{lw}" width="{mw}" height="{h}" fill="{mc}"/>{overlay}</g>"#,
+            r#"<g text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">{label_text}{message_text}</g>"#,
+            "</svg>"
+        ),
+        w = width,
+        h = height,
+        rx = radius,
+        lw = label_width,
+        mw = message_width,
+        lc = label_color,
+        mc = badge.color,
+        label = label,
+        message = message,
+        gradient = gradient,
+        overlay = overlay,
+        label_text = text(&label, label_width / 2.0, label_color),
+        message_text = text(&message, label_width + message_width / 2.0, &badge.color),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_colors() {
+        assert_eq!(parse_color("brightgreen").as_deref(), Some("#4c1"));
+        assert_eq!(parse_color("success").as_deref(), Some("#4c1"));
+        assert_eq!(parse_color("Red").as_deref(), Some("#e05d44"));
+        assert_eq!(parse_color("lightgray").as_deref(), Some("#9f9f9f"));
+        assert_eq!(parse_color("blueviolet").as_deref(), Some("#8a2be2"));
+    }
+
+    #[test]
+    fn hex_colors() {
+        assert_eq!(parse_color("fff").as_deref(), Some("#fff"));
+        assert_eq!(parse_color("#fff").as_deref(), Some("#fff"));
+        assert_eq!(parse_color("1E90FF").as_deref(), Some("#1E90FF"));
+        assert_eq!(parse_color("#a4a61d").as_deref(), Some("#a4a61d"));
+    }
+
+    #[test]
+    fn rejects_invalid_colors() {
+        for value in ["", "#", "##fff", "ff", "ffff", "#fffffff", "ggg", "purple", "red;", "#fff\"><script>", "url(x)"] {
+            assert_eq!(parse_color(value), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn cjk_text_is_wider_than_ascii() {
+        // 全角字符按字号计算宽度
+        assert_eq!(text_width("中文"), 2.0 * FONT_SIZE);
+        assert_eq!(text_width("项目 🚀"), 3.0 * FONT_SIZE + 3.9);
+        assert!(text_width("版本") > text_width("ab"));
+        assert!(text_width("ab") < 2.0 * FONT_SIZE);
+    }
+
+    #[test]
+    fn ascii_width_by_glyph_class() {
+        assert_eq!(text_width(""), 0.0);
+        assert_eq!(text_width("il"), 6.6);
+        assert_eq!(text_width("mW"), 19.8);
+        assert_eq!(text_width("v1.2"), 6.5 + 7.0 + 3.3 + 7.0);
+        assert!(text_width("stars") < text_width("STARS"));
+    }
+}
@@ -0,0 +1,158 @@
+// 动态页面和 JSON 接口的条件请求
+//
+// 对 GET/HEAD 的 200 HTML/JSON 响应按内容计算弱 ETag，客户端带 If-None-Match 且内容未变时返回 304；
+// 带 CSP nonce 的页面（见 security.rs）除外。
+// 默认 Cache-Control: no-cache，浏览器和 Cloudflare 每次都会重新验证，但未变化时不必重新下载正文。
+// 压缩在本中间件之外进行，因此使用弱 ETag，同一内容的不同压缩版本共用一个 ETag。
+// 只缓冲长度已知且不超过 MAX_ETAG_BODY 的正文，过大或流式的响应原样返回，不带 ETag。
+
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+/// 超过该大小的响应不计算 ETag，直接返回
+const MAX_ETAG_BODY: usize = 8 * 1024 * 1024;
+
+pub async fn etag(request: Request, next: Next) -> Response {
+    let cacheable = matches!(*request.method(), Method::GET | Method::HEAD);
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let mut response = next.run(request).await;
+
+    if !cacheable || response.status() != StatusCode::OK || response.headers().contains_key(header::ETAG) {
+        return response;
+    }
+    let dynamic = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("text/html") || ct.starts_with("application/json"));
+    if !dynamic {
+        return response;
+    }
+
+    // 带 CSP nonce 的页面每次内容都不同，304 会让浏览器沿用旧 nonce 的页面，不计算 ETag
+    if crate::security::nonce_used() {
+        response
+            .headers_mut()
+            .entry(header::CACHE_CONTROL)
+            .or_insert(HeaderValue::from_static("no-cache"));
+        return response;
+    }
+
+    // 先看 Content-Length 和正文的长度提示，超过上限时不读取正文，避免 to_bytes 出错后只能返回 500
+    let declared = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let size = declared.or(response.body().size_hint().upper());
+    if size.is_none_or(|size| size > MAX_ETAG_BODY as u64) {
+        tracing::debug!("响应正文过大或长度未知 ({:?} 字节)，不计算 ETag", size);
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ETAG_BODY).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("读取响应正文失败: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let tag = format!("W/\"{}\"", &hex::encode(Sha256::digest(&bytes))[..16]);
+    let Ok(tag_value) = HeaderValue::from_str(&tag) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    parts.headers.insert(header::ETAG, tag_value);
+    parts
+        .headers
+        .entry(header::CACHE_CONTROL)
+        .or_insert(HeaderValue::from_static("no-cache"));
+
+    let matched = if_none_match
+        .as_ref()
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|t| weak_eq(t.trim(), &tag) || t.trim() == "*"));
+    if matched {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::CONTENT_TYPE);
+        return Response::from_parts(parts, Body::empty());
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// If-None-Match 使用弱比较，忽略 W/ 前缀
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app(body: fn() -> Body) -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(move || async move { ([(header::CONTENT_TYPE, "application/json")], body()) }),
+            )
+            .layer(middleware::from_fn(etag))
+    }
+
+    async fn get_with(app: Router, if_none_match: Option<&str>) -> Response {
+        let mut request = Request::get("/");
+        if let Some(tag) = if_none_match {
+            request = request.header(header::IF_NONE_MATCH, tag);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn small_body_gets_etag_and_304() {
+        let response = get_with(app(|| Body::from("{\"ok\":true}")), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let tag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert!(tag.starts_with("W/\""));
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+
+        let response = get_with(app(|| Body::from("{\"ok\":true}")), Some(&tag)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        let strong = tag.trim_start_matches("W/");
+        let response = get_with(app(|| Body::from("{\"ok\":true}")), Some(&format!("\"x\", {}", strong))).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn oversize_body_is_passed_through() {
+        let response = get_with(app(|| Body::from(vec![b' '; MAX_ETAG_BODY + 1])), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(header::ETAG));
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(bytes.len(), MAX_ETAG_BODY + 1);
+    }
+
+    #[tokio::test]
+    async fn streaming_body_is_passed_through() {
+        let response = get_with(
+            app(|| {
+                let chunks = ["{\"a\":", "1}"].map(|c| Ok::<_, std::io::Error>(c.as_bytes()));
+                Body::from_stream(futures_util::stream::iter(chunks))
+            }),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(header::ETAG));
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], b"{\"a\":1}");
+    }
+}
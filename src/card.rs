@@ -0,0 +1,335 @@
+// 社交分享卡片：用 image 和 ab_glyph 绘制 1200×630 的 PNG 预览图
+//
+// 字体按顺序回退：先用 OG_FONT 或系统中找到的中文字体，缺字时使用内置的 DejaVu Sans。
+// 只画卡片本身，缓存和头像下载见 social 模块。
+
+use ab_glyph::{point, Font, FontArc, FontVec, PxScale, ScaleFont};
+use image::{imageops, Rgba, RgbaImage};
+use std::sync::OnceLock;
+
+pub const WIDTH: u32 = 1200;
+pub const HEIGHT: u32 = 630;
+
+const DEJAVU_REGULAR: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+const DEJAVU_BOLD: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+
+const PANEL_MARGIN: u32 = 48;
+const CONTENT_LEFT: f32 = 96.0;
+const CONTENT_WIDTH: f32 = (WIDTH - 2 * 96) as f32;
+
+const GRADIENT_START: [u8; 3] = [0x63, 0x66, 0xf1]; // --primary-color
+const GRADIENT_END: [u8; 3] = [0x8b, 0x5c, 0xf6]; // --primary-light
+const TITLE_COLOR: Rgba<u8> = Rgba([0x1f, 0x29, 0x37, 0xff]);
+const TEXT_COLOR: Rgba<u8> = Rgba([0x6b, 0x72, 0x80, 0xff]);
+const ACCENT_COLOR: Rgba<u8> = Rgba([0x4f, 0x46, 0xe5, 0xff]);
+const TAG_BACKGROUND: Rgba<u8> = Rgba([0xee, 0xf2, 0xff, 0xff]);
+const WHITE: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xff]);
+
+/// 卡片内容
+pub struct Card {
+    /// 左上角的标签，如文章分类或项目语言
+    pub tag: String,
+    pub title: String,
+    /// 标题下方的简介，文章卡片一般为空
+    pub subtitle: Option<String>,
+    /// 阅读时间、仓库等元信息
+    pub meta: String,
+    /// 页脚署名
+    pub author: String,
+    pub site_name: String,
+}
+
+/// 按顺序回退的字体列表
+struct FontSet {
+    /// 加载成功的中文字体路径
+    cjk: Option<String>,
+    regular: Vec<FontArc>,
+    bold: Vec<FontArc>,
+}
+
+fn fonts() -> &'static FontSet {
+    static FONTS: OnceLock<FontSet> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let extra = crate::social::SocialConfig::get_font_path().and_then(|path| {
+            let data = std::fs::read(&path).ok()?;
+            // .ttc 字体集合取第一个字体
+            match FontVec::try_from_vec_and_index(data, 0) {
+                Ok(font) => {
+                    tracing::info!("社交卡片使用字体: {}", path);
+                    Some((path, FontArc::new(font)))
+                }
+                Err(e) => {
+                    tracing::warn!("无法加载字体 {}: {}", path, e);
+                    None
+                }
+            }
+        });
+        if extra.is_none() {
+            tracing::warn!(
+                "未找到中文字体，社交卡片中的中文可能无法显示，请部署 {} 或通过 OG_FONT 指定",
+                crate::social::BUNDLED_CJK_FONT
+            );
+        }
+
+        let builtin = |data: &'static [u8]| FontArc::try_from_slice(data).expect("内置字体无效");
+        let (cjk, extra) = extra.unzip();
+        FontSet {
+            cjk,
+            regular: extra.iter().cloned().chain([builtin(DEJAVU_REGULAR)]).collect(),
+            bold: extra.into_iter().chain([builtin(DEJAVU_BOLD)]).collect(),
+        }
+    })
+}
+
+/// 绘制中文使用的字体文件，没有找到中文字体时为 None
+pub fn cjk_font() -> Option<&'static str> {
+    fonts().cjk.as_deref()
+}
+
+/// 选择第一个包含该字符的字体，都没有时使用最后一个
+fn font_for(fonts: &[FontArc], c: char) -> &FontArc {
+    fonts
+        .iter()
+        .find(|font| font.glyph_id(c).0 != 0)
+        .unwrap_or_else(|| fonts.last().expect("字体列表为空"))
+}
+
+/// 所有字体都没有字形的字符（会画成 .notdef 方框），不含空白
+fn missing_glyphs(fonts: &[FontArc], text: &str) -> Vec<char> {
+    let mut missing: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && fonts.iter().all(|font| font.glyph_id(*c).0 == 0))
+        .collect();
+    missing.dedup();
+    missing
+}
+
+fn text_width(fonts: &[FontArc], text: &str, size: f32) -> f32 {
+    text.chars()
+        .map(|c| {
+            let font = font_for(fonts, c).as_scaled(PxScale::from(size));
+            font.h_advance(font.glyph_id(c))
+        })
+        .sum()
+}
+
+/// 在基线 (x, y) 处绘制单行文字，返回绘制宽度
+fn draw_text(image: &mut RgbaImage, fonts: &[FontArc], text: &str, x: f32, y: f32, size: f32, color: Rgba<u8>) -> f32 {
+    let mut cursor = x;
+    for c in text.chars() {
+        let font = font_for(fonts, c).as_scaled(PxScale::from(size));
+        let glyph = font.scaled_glyph(c);
+        let advance = font.h_advance(glyph.id);
+        if let Some(outline) = font.outline_glyph(ab_glyph::Glyph { position: point(cursor, y), ..glyph }) {
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                blend(image, px, py, color, coverage);
+            });
+        }
+        cursor += advance;
+    }
+    cursor - x
+}
+
+fn blend(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, alpha: f32) {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+        return;
+    }
+    let alpha = alpha.clamp(0.0, 1.0);
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for i in 0..3 {
+        pixel[i] = (pixel[i] as f32 * (1.0 - alpha) + color[i] as f32 * alpha).round() as u8;
+    }
+}
+
+/// 按宽度折行：中日韩字符可在任意位置断开，英文单词尽量在空格处断开；
+/// 超过 max_lines 时在最后一行末尾加省略号
+fn wrap(fonts: &[FontArc], text: &str, size: f32, max_width: f32, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    let mut truncated = false;
+
+    for c in text.split_whitespace().collect::<Vec<_>>().join(" ").chars() {
+        let candidate = format!("{}{}", line, c);
+        if text_width(fonts, &candidate, size) <= max_width {
+            line = candidate;
+            continue;
+        }
+        if lines.len() + 1 == max_lines {
+            truncated = true;
+            break;
+        }
+        // 英文单词尽量整体换到下一行
+        let break_at = line.rfind(' ').filter(|&i| {
+            i > 0 && c.is_ascii_alphanumeric() && line[i + 1..].chars().all(|ch| ch.is_ascii_alphanumeric())
+        });
+        match break_at {
+            Some(i) => {
+                let word = line[i + 1..].to_string();
+                lines.push(line[..i].to_string());
+                line = format!("{}{}", word, c);
+            }
+            None => {
+                lines.push(line.trim_end().to_string());
+                line = if c == ' ' { String::new() } else { c.to_string() };
+            }
+        }
+    }
+
+    if truncated {
+        while !line.is_empty() && text_width(fonts, &format!("{}…", line), size) > max_width {
+            line.pop();
+        }
+        line = format!("{}…", line.trim_end());
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn fill_rounded_rect(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, radius: u32, color: Rgba<u8>) {
+    let r = radius as f32;
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            // 到最近圆角圆心的距离，用于抗锯齿
+            let dx = (r - (px - x) as f32 - 0.5).max((px - x) as f32 + 0.5 - (width as f32 - r)).max(0.0);
+            let dy = (r - (py - y) as f32 - 0.5).max((py - y) as f32 + 0.5 - (height as f32 - r)).max(0.0);
+            let coverage = (r - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                blend(image, px as i32, py as i32, color, coverage);
+            }
+        }
+    }
+}
+
+/// 把头像裁成圆形贴到 (x, y)
+fn draw_avatar(image: &mut RgbaImage, avatar: &RgbaImage, x: u32, y: u32, size: u32) {
+    let avatar = imageops::resize(avatar, size, size, imageops::FilterType::Lanczos3);
+    let r = size as f32 / 2.0;
+    for (ax, ay, pixel) in avatar.enumerate_pixels() {
+        let distance = ((ax as f32 + 0.5 - r).powi(2) + (ay as f32 + 0.5 - r).powi(2)).sqrt();
+        let coverage = (r - distance + 0.5).clamp(0.0, 1.0) * pixel[3] as f32 / 255.0;
+        blend(image, (x + ax) as i32, (y + ay) as i32, Rgba([pixel[0], pixel[1], pixel[2], 0xff]), coverage);
+    }
+}
+
+/// 没有头像时画一个带首字母的圆
+fn draw_initial(image: &mut RgbaImage, initial: &str, x: u32, y: u32, size: u32) {
+    fill_rounded_rect(image, x, y, size, size, size / 2, ACCENT_COLOR);
+    let fonts = &fonts().bold;
+    let font_size = size as f32 * 0.5;
+    let width = text_width(fonts, initial, font_size);
+    draw_text(
+        image,
+        fonts,
+        initial,
+        x as f32 + (size as f32 - width) / 2.0,
+        y as f32 + size as f32 * 0.68,
+        font_size,
+        WHITE,
+    );
+}
+
+pub fn render(card: &Card, avatar: Option<&RgbaImage>) -> RgbaImage {
+    let fonts = fonts();
+    let missing = missing_glyphs(&fonts.bold, &card.title);
+    if !missing.is_empty() {
+        tracing::warn!(
+            "社交卡片标题 \"{}\" 中有 {} 个字符没有可用的字形，将显示为方框: {}",
+            card.title,
+            missing.len(),
+            missing.iter().take(10).collect::<String>()
+        );
+    }
+    let mut image = RgbaImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        let t = (x as f32 / WIDTH as f32 + y as f32 / HEIGHT as f32) / 2.0;
+        let channel = |i: usize| (GRADIENT_START[i] as f32 * (1.0 - t) + GRADIENT_END[i] as f32 * t) as u8;
+        Rgba([channel(0), channel(1), channel(2), 0xff])
+    });
+    fill_rounded_rect(
+        &mut image,
+        PANEL_MARGIN,
+        PANEL_MARGIN,
+        WIDTH - 2 * PANEL_MARGIN,
+        HEIGHT - 2 * PANEL_MARGIN,
+        24,
+        WHITE,
+    );
+
+    // 标签
+    let tag_size = 26.0;
+    let tag_width = text_width(&fonts.bold, &card.tag, tag_size) + 40.0;
+    fill_rounded_rect(&mut image, CONTENT_LEFT as u32, 96, tag_width as u32, 48, 24, TAG_BACKGROUND);
+    draw_text(&mut image, &fonts.bold, &card.tag, CONTENT_LEFT + 20.0, 130.0, tag_size, ACCENT_COLOR);
+
+    // 标题和简介
+    let title_size = 60.0;
+    let title_lines = if card.subtitle.is_some() { 2 } else { 3 };
+    let mut baseline = 232.0;
+    for line in wrap(&fonts.bold, &card.title, title_size, CONTENT_WIDTH, title_lines) {
+        draw_text(&mut image, &fonts.bold, &line, CONTENT_LEFT, baseline, title_size, TITLE_COLOR);
+        baseline += 78.0;
+    }
+    if let Some(subtitle) = &card.subtitle {
+        baseline += 4.0;
+        for line in wrap(&fonts.regular, subtitle, 30.0, CONTENT_WIDTH, 2) {
+            draw_text(&mut image, &fonts.regular, &line, CONTENT_LEFT, baseline, 30.0, TEXT_COLOR);
+            baseline += 42.0;
+        }
+    }
+
+    // 元信息
+    draw_text(&mut image, &fonts.regular, &card.meta, CONTENT_LEFT, 452.0, 28.0, TEXT_COLOR);
+
+    // 页脚：头像、署名和站点名
+    let avatar_size = 64;
+    let footer_top = 486;
+    match avatar {
+        Some(avatar) => draw_avatar(&mut image, avatar, CONTENT_LEFT as u32, footer_top, avatar_size),
+        None => {
+            let initial: String = card.author.chars().take(1).collect::<String>().to_uppercase();
+            draw_initial(&mut image, &initial, CONTENT_LEFT as u32, footer_top, avatar_size);
+        }
+    }
+    draw_text(&mut image, &fonts.bold, &card.author, CONTENT_LEFT + 84.0, 529.0, 30.0, TITLE_COLOR);
+    let site_width = text_width(&fonts.regular, &card.site_name, 26.0);
+    draw_text(
+        &mut image,
+        &fonts.regular,
+        &card.site_name,
+        WIDTH as f32 - CONTENT_LEFT - site_width,
+        528.0,
+        26.0,
+        ACCENT_COLOR,
+    );
+
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builtin() -> Vec<FontArc> {
+        vec![FontArc::try_from_slice(DEJAVU_BOLD).unwrap()]
+    }
+
+    #[test]
+    fn builtin_font_covers_latin_only() {
+        let fonts = builtin();
+        assert!(missing_glyphs(&fonts, "Rust async: tokio & futures — 2026").is_empty());
+        assert_eq!(missing_glyphs(&fonts, "Rust 异步 入门"), ['异', '步', '入', '门']);
+    }
+
+    #[test]
+    fn glyphs_fall_back_in_order() {
+        let regular = FontArc::try_from_slice(DEJAVU_REGULAR).unwrap();
+        let fonts = vec![FontArc::try_from_slice(DEJAVU_BOLD).unwrap(), regular];
+        // 第一个字体有的字符用第一个，都没有时用最后一个
+        assert!(std::ptr::eq(font_for(&fonts, 'R'), &fonts[0]));
+        assert!(std::ptr::eq(font_for(&fonts, '部'), &fonts[1]));
+    }
+}
@@ -0,0 +1,356 @@
+// 客户端真实 IP
+//
+// 只有直接连接的对端在可信代理列表（TRUSTED_PROXIES）中时，才读取 CF-Connecting-IP、Forwarded（RFC 7239）、
+// X-Forwarded-For 和 X-Real-IP；否则这些请求头可以被任何人伪造，直接使用连接地址。
+// CF-Connecting-IP 只在对端属于 Cloudflare 的地址段时读取：经本机反向代理转发时，这个请求头由客户端任意填写。
+// 代理链从右向左查找，跳过可信代理，第一个不可信的地址就是客户端。
+// 处理函数用 ClientIp 提取器，中间件用 client_ip::of(&request)，限流和日志都以此为准。
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, Request},
+    http::{request::Parts, HeaderMap},
+};
+use ipnet::IpNet;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::OnceLock;
+
+use crate::api::ApiError;
+
+/// Cloudflare 的回源地址段，见 https://www.cloudflare.com/ips/
+const CLOUDFLARE_RANGES: &[&str] = &[
+    "173.245.48.0/20",
+    "103.21.244.0/22",
+    "103.22.200.0/22",
+    "103.31.4.0/22",
+    "141.101.64.0/18",
+    "108.162.192.0/18",
+    "190.93.240.0/20",
+    "188.114.96.0/20",
+    "197.234.240.0/22",
+    "198.41.128.0/17",
+    "162.158.0.0/15",
+    "104.16.0.0/13",
+    "104.24.0.0/14",
+    "172.64.0.0/13",
+    "131.0.72.0/22",
+    "2400:cb00::/32",
+    "2606:4700::/32",
+    "2803:f800::/32",
+    "2405:b500::/32",
+    "2405:8100::/32",
+    "2a06:98c0::/29",
+    "2c0f:f248::/32",
+];
+const LOOPBACK_RANGES: &[&str] = &["127.0.0.0/8", "::1/128"];
+const PRIVATE_RANGES: &[&str] = &["10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16", "fc00::/7"];
+
+pub struct ClientIpConfig;
+
+impl ClientIpConfig {
+    /// 可信代理（TRUSTED_PROXIES），逗号分隔的 CIDR 或单个 IP，另外支持 cloudflare、loopback、private 三个别名；
+    /// 默认 loopback,cloudflare（Cloudflare 直连或经本机 cloudflared / 反向代理转发），设为 none 时不信任任何代理
+    pub fn get_trusted_proxies() -> Vec<IpNet> {
+        let value = std::env::var("TRUSTED_PROXIES").unwrap_or_else(|_| "loopback,cloudflare".to_string());
+        parse_networks("TRUSTED_PROXIES", &value)
+    }
+}
+
+/// 解析逗号分隔的 CIDR / IP 列表，支持 cloudflare、loopback、private 别名，none 表示空列表；
+/// name 是配置项名称，用于提示无效的条目
+pub fn parse_networks(name: &str, value: &str) -> Vec<IpNet> {
+    let mut networks = Vec::new();
+    for entry in value.split([',', ' ']).map(str::trim).filter(|e| !e.is_empty()) {
+        let ranges = match entry.to_lowercase().as_str() {
+            "none" => continue,
+            "cloudflare" => CLOUDFLARE_RANGES,
+            "loopback" => LOOPBACK_RANGES,
+            "private" => PRIVATE_RANGES,
+            _ => {
+                match entry.parse::<IpNet>().or_else(|_| entry.parse::<IpAddr>().map(IpNet::from)) {
+                    Ok(network) => networks.push(network),
+                    Err(_) => tracing::warn!("{} 中的 {} 不是合法的 IP 或 CIDR，已忽略", name, entry),
+                }
+                continue;
+            }
+        };
+        networks.extend(ranges.iter().filter_map(|range| range.parse::<IpNet>().ok()));
+    }
+    networks
+}
+
+/// 当前信任的代理地址段，第一次使用时读取配置
+pub fn trusted_proxies() -> &'static [IpNet] {
+    static TRUSTED: OnceLock<Vec<IpNet>> = OnceLock::new();
+    TRUSTED.get_or_init(ClientIpConfig::get_trusted_proxies)
+}
+
+/// Cloudflare 的回源地址段，与 TRUSTED_PROXIES 无关
+fn cloudflare_ranges() -> &'static [IpNet] {
+    static RANGES: OnceLock<Vec<IpNet>> = OnceLock::new();
+    RANGES.get_or_init(|| parse_networks("cloudflare", "cloudflare"))
+}
+
+fn contains(networks: &[IpNet], ip: IpAddr) -> bool {
+    networks.iter().any(|network| network.contains(&ip))
+}
+
+/// 客户端 IP 取自哪里
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpSource {
+    /// 直接连接的对端
+    Peer,
+    CfConnectingIp,
+    Forwarded,
+    XForwardedFor,
+    XRealIp,
+}
+
+impl IpSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IpSource::Peer => "peer",
+            IpSource::CfConnectingIp => "CF-Connecting-IP",
+            IpSource::Forwarded => "Forwarded",
+            IpSource::XForwardedFor => "X-Forwarded-For",
+            IpSource::XRealIp => "X-Real-IP",
+        }
+    }
+}
+
+/// 根据连接地址和转发请求头确定客户端 IP
+pub fn resolve(headers: &HeaderMap, peer: IpAddr) -> (IpAddr, IpSource) {
+    resolve_with(headers, peer, trusted_proxies())
+}
+
+fn resolve_with(headers: &HeaderMap, peer: IpAddr, trusted: &[IpNet]) -> (IpAddr, IpSource) {
+    let peer = peer.to_canonical();
+    if !contains(trusted, peer) {
+        return (peer, IpSource::Peer);
+    }
+
+    let single = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_node)
+    };
+    if contains(cloudflare_ranges(), peer)
+        && let Some(ip) = single("cf-connecting-ip")
+    {
+        return (ip, IpSource::CfConnectingIp);
+    }
+    if let Some(ip) = pick_client(&forwarded_chain(headers), trusted) {
+        return (ip, IpSource::Forwarded);
+    }
+    if let Some(ip) = pick_client(&x_forwarded_for_chain(headers), trusted) {
+        return (ip, IpSource::XForwardedFor);
+    }
+    if let Some(ip) = single("x-real-ip") {
+        return (ip, IpSource::XRealIp);
+    }
+    (peer, IpSource::Peer)
+}
+
+/// 中间件中使用：没有连接信息时（如直接调用 Router）返回 None
+pub fn of(request: &Request) -> Option<(IpAddr, IpSource)> {
+    let ConnectInfo(addr) = request.extensions().get::<ConnectInfo<SocketAddr>>()?;
+    Some(resolve(request.headers(), addr.ip()))
+}
+
+/// 从右向左跳过可信代理；遇到 unknown 或无法解析的节点时放弃整个请求头，全部可信时取最左边的地址
+fn pick_client(chain: &[Option<IpAddr>], trusted: &[IpNet]) -> Option<IpAddr> {
+    for hop in chain.iter().rev() {
+        let ip = (*hop)?;
+        if !contains(trusted, ip) {
+            return Some(ip);
+        }
+    }
+    chain.first().copied().flatten()
+}
+
+/// Forwarded: for=192.0.2.60;proto=https, for="[2001:db8::1]:4711"
+fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    headers
+        .get_all("forwarded")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|element| {
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                .and_then(|(_, node)| parse_node(node))
+        })
+        .collect()
+}
+
+/// X-Forwarded-For: client, proxy1, proxy2
+fn x_forwarded_for_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter(|node| !node.trim().is_empty())
+        .map(parse_node)
+        .collect()
+}
+
+/// 解析单个节点，支持带引号、带端口和 [IPv6]:端口 的写法；unknown 和混淆的标识（_hidden）返回 None
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    let ip: IpAddr = if let Some(rest) = node.strip_prefix('[') {
+        rest.split_once(']')?.0.parse().ok()?
+    } else if let Some((host, port)) = node.split_once(':')
+        && !port.contains(':')
+    {
+        host.parse().ok()?
+    } else {
+        node.parse().ok()?
+    };
+    Some(ip.to_canonical())
+}
+
+/// 客户端真实 IP 的提取器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(ConnectInfo(addr)) = parts.extensions.get::<ConnectInfo<SocketAddr>>() else {
+            tracing::error!("请求缺少连接信息，无法确定客户端 IP");
+            return Err(ApiError::internal("无法确定客户端地址"));
+        };
+        Ok(ClientIp(resolve(&parts.headers, addr.ip()).0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const CF_PEER: &str = "172.68.1.10";
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn trusted() -> Vec<IpNet> {
+        parse_networks("TRUSTED_PROXIES", "loopback,cloudflare")
+    }
+
+    fn header_map(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn parse_node_forms() {
+        assert_eq!(parse_node("203.0.113.7"), Some(ip("203.0.113.7")));
+        assert_eq!(parse_node(" 203.0.113.7:4711 "), Some(ip("203.0.113.7")));
+        assert_eq!(parse_node("\"203.0.113.7\""), Some(ip("203.0.113.7")));
+        assert_eq!(parse_node("2001:db8::1"), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("[2001:db8::1]"), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("\"[2001:db8::1]:4711\""), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("::ffff:203.0.113.7"), Some(ip("203.0.113.7")));
+    }
+
+    #[test]
+    fn parse_node_rejects_unknown_and_obfuscated() {
+        for node in ["unknown", "_hidden", "\"_gazonk\"", "", "[2001:db8::1", "example.com:80", "300.1.1.1"] {
+            assert_eq!(parse_node(node), None, "{}", node);
+        }
+    }
+
+    #[test]
+    fn pick_client_skips_trusted_hops_from_the_right() {
+        let trusted = trusted();
+        // 最左边的地址由客户端填写，可以伪造，不能使用
+        let chain = [Some(ip("1.1.1.1")), Some(ip("198.51.100.9")), Some(ip(CF_PEER)), Some(ip("127.0.0.1"))];
+        assert_eq!(pick_client(&chain, &trusted), Some(ip("198.51.100.9")));
+
+        let chain = [Some(ip("198.51.100.9")), None, Some(ip("127.0.0.1"))];
+        assert_eq!(pick_client(&chain, &trusted), None);
+
+        // 全部可信时取最左边的地址
+        let chain = [Some(ip(CF_PEER)), Some(ip("127.0.0.1"))];
+        assert_eq!(pick_client(&chain, &trusted), Some(ip(CF_PEER)));
+        assert_eq!(pick_client(&[], &trusted), None);
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_headers() {
+        let headers = header_map(&[("cf-connecting-ip", "1.1.1.1"), ("x-forwarded-for", "1.1.1.1")]);
+        assert_eq!(
+            resolve_with(&headers, ip("203.0.113.7"), &trusted()),
+            (ip("203.0.113.7"), IpSource::Peer)
+        );
+        assert_eq!(resolve_with(&headers, ip(CF_PEER), &[]), (ip(CF_PEER), IpSource::Peer));
+    }
+
+    #[test]
+    fn cf_connecting_ip_only_from_cloudflare_peers() {
+        let headers = header_map(&[("cf-connecting-ip", "2001:db8::1"), ("x-forwarded-for", "1.1.1.1, 198.51.100.9")]);
+        assert_eq!(
+            resolve_with(&headers, ip(CF_PEER), &trusted()),
+            (ip("2001:db8::1"), IpSource::CfConnectingIp)
+        );
+        assert_eq!(
+            resolve_with(&headers, ip("2606:4700::1"), &trusted()),
+            (ip("2001:db8::1"), IpSource::CfConnectingIp)
+        );
+        // 经本机反向代理转发时 CF-Connecting-IP 可能是客户端自己填写的
+        assert_eq!(
+            resolve_with(&headers, ip("127.0.0.1"), &trusted()),
+            (ip("198.51.100.9"), IpSource::XForwardedFor)
+        );
+    }
+
+    #[test]
+    fn forwarded_before_x_forwarded_for() {
+        let headers = header_map(&[
+            ("forwarded", "for=1.1.1.1, for=\"[2001:db8::1]:4711\";proto=https"),
+            ("forwarded", "for=127.0.0.1"),
+            ("x-forwarded-for", "198.51.100.9"),
+        ]);
+        assert_eq!(
+            resolve_with(&headers, ip("::1"), &trusted()),
+            (ip("2001:db8::1"), IpSource::Forwarded)
+        );
+
+        // 链中有 unknown 时放弃 Forwarded，改用 X-Forwarded-For
+        let headers = header_map(&[("forwarded", "for=unknown"), ("x-forwarded-for", "198.51.100.9")]);
+        assert_eq!(
+            resolve_with(&headers, ip("127.0.0.1"), &trusted()),
+            (ip("198.51.100.9"), IpSource::XForwardedFor)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_x_real_ip_then_peer() {
+        let trusted = trusted();
+        let headers = header_map(&[("x-forwarded-for", "_hidden"), ("x-real-ip", "198.51.100.9")]);
+        assert_eq!(
+            resolve_with(&headers, ip("127.0.0.1"), &trusted),
+            (ip("198.51.100.9"), IpSource::XRealIp)
+        );
+        assert_eq!(
+            resolve_with(&HeaderMap::new(), ip("::ffff:127.0.0.1"), &trusted),
+            (ip("127.0.0.1"), IpSource::Peer)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+// GitHub 数据后端：GitHubDataManager 通过该 trait 获取数据，
+// 可在 REST（GitHubClient）和 GraphQL（GraphQlClient）之间切换，见 GITHUB_API_BACKEND
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use super::client::GitHubClient;
+use super::config::GitHubConfig;
+use super::graphql::{self, GraphQlClient};
+use super::models::*;
+use super::rules::ProjectRules;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[async_trait]
+pub trait GitHubBackend: Send + Sync {
+    /// 后端名称，记录在刷新报告中
+    fn name(&self) -> &'static str;
+
+    /// 按规则过滤后的用户仓库
+    async fn get_featured_repos(&self, rules: &ProjectRules) -> Result<Vec<Repository>>;
+    /// 用户在 GitHub 个人主页置顶的仓库名，后端不支持时返回空列表
+    async fn get_pinned_repos(&self) -> Result<Vec<String>>;
+    /// 单个仓库，不存在或不可见时返回 None
+    async fn get_repo(&self, repo_name: &str) -> Result<Option<Repository>>;
+    async fn get_user_info(&self) -> Result<User>;
+    /// 过去一年的贡献日历（只有 GraphQL API 提供，两种后端都通过 GraphQL 获取）
+    async fn get_contribution_calendar(&self) -> Result<ContributionCalendar>;
+    /// 最近的公开事件（只有 REST API 提供）
+    async fn get_public_events(&self, per_page: u32) -> Result<Vec<Event>>;
+    /// 用户的公开 Gist（两种后端都通过 REST 获取）
+    async fn get_user_gists(&self, per_page: u32) -> Result<Vec<Gist>>;
+
+    async fn get_repo_readme(&self, repo_name: &str) -> Result<Option<String>>;
+    async fn get_repo_languages(&self, repo_name: &str) -> Result<HashMap<String, u64>>;
+    async fn get_repo_commits(&self, repo_name: &str, per_page: u32) -> Result<Vec<Commit>>;
+    async fn get_repo_releases(&self, repo_name: &str, per_page: u32) -> Result<Vec<Release>>;
+    async fn get_repo_contents(&self, repo_name: &str, path: &str) -> Result<Vec<RepoContent>>;
+    async fn get_file_content(&self, download_url: &str) -> Result<String>;
+
+    /// 已使用的 API 请求数
+    fn api_calls(&self) -> u32;
+    /// 剩余的 API 预算
+    fn remaining_budget(&self) -> u32;
+    /// GitHub 返回的剩余速率限制
+    fn rate_limit_remaining(&self) -> Option<u32>;
+}
+
+/// 按 GITHUB_API_BACKEND 创建数据后端，未知取值时回退到 REST
+pub fn create_backend() -> Result<Box<dyn GitHubBackend>> {
+    let client = GitHubClient::new()?;
+    match GitHubConfig::get_api_backend().as_str() {
+        "graphql" => Ok(Box::new(GraphQlClient::new(client))),
+        "rest" => Ok(Box::new(client)),
+        other => {
+            tracing::warn!("未知的 GITHUB_API_BACKEND: {}，使用 REST", other);
+            Ok(Box::new(client))
+        }
+    }
+}
+
+#[async_trait]
+impl GitHubBackend for GitHubClient {
+    fn name(&self) -> &'static str {
+        "rest"
+    }
+
+    async fn get_featured_repos(&self, rules: &ProjectRules) -> Result<Vec<Repository>> {
+        GitHubClient::get_featured_repos(self, rules).await
+    }
+
+    async fn get_pinned_repos(&self) -> Result<Vec<String>> {
+        // REST API 不提供个人主页置顶信息
+        Ok(Vec::new())
+    }
+
+    async fn get_repo(&self, repo_name: &str) -> Result<Option<Repository>> {
+        GitHubClient::get_repo(self, repo_name).await
+    }
+
+    async fn get_user_info(&self) -> Result<User> {
+        GitHubClient::get_user_info(self).await
+    }
+
+    async fn get_contribution_calendar(&self) -> Result<ContributionCalendar> {
+        graphql::fetch_contribution_calendar(self, &GitHubConfig::get_graphql_url()).await
+    }
+
+    async fn get_public_events(&self, per_page: u32) -> Result<Vec<Event>> {
+        GitHubClient::get_public_events(self, per_page).await
+    }
+
+    async fn get_user_gists(&self, per_page: u32) -> Result<Vec<Gist>> {
+        GitHubClient::get_user_gists(self, per_page).await
+    }
+
+    async fn get_repo_readme(&self, repo_name: &str) -> Result<Option<String>> {
+        GitHubClient::get_repo_readme(self, repo_name).await
+    }
+
+    async fn get_repo_languages(&self, repo_name: &str) -> Result<HashMap<String, u64>> {
+        GitHubClient::get_repo_languages(self, repo_name).await
+    }
+
+    async fn get_repo_commits(&self, repo_name: &str, per_page: u32) -> Result<Vec<Commit>> {
+        GitHubClient::get_repo_commits(self, repo_name, per_page).await
+    }
+
+    async fn get_repo_releases(&self, repo_name: &str, per_page: u32) -> Result<Vec<Release>> {
+        GitHubClient::get_repo_releases(self, repo_name, per_page).await
+    }
+
+    async fn get_repo_contents(&self, repo_name: &str, path: &str) -> Result<Vec<RepoContent>> {
+        GitHubClient::get_repo_contents(self, repo_name, path).await
+    }
+
+    async fn get_file_content(&self, download_url: &str) -> Result<String> {
+        GitHubClient::get_file_content(self, download_url).await
+    }
+
+    fn api_calls(&self) -> u32 {
+        GitHubClient::api_calls(self)
+    }
+
+    fn remaining_budget(&self) -> u32 {
+        GitHubClient::remaining_budget(self)
+    }
+
+    fn rate_limit_remaining(&self) -> Option<u32> {
+        GitHubClient::rate_limit_remaining(self)
+    }
+}
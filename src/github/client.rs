@@ -1,19 +1,42 @@
-use reqwest::Client;
-use std::env;
+use reqwest::{Client, RequestBuilder, Response};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::sync::Semaphore;
 use super::models::*;
 use super::config::GitHubConfig;
+use super::rules::ProjectRules;
+
+/// 本次刷新的 API 预算已用完，调用方据此把仓库标记为跳过而不是失败
+#[derive(Debug)]
+pub struct BudgetExhausted(pub u32);
+
+impl std::fmt::Display for BudgetExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "本次刷新的 GitHub API 预算 ({} 次) 已用完", self.0)
+    }
+}
+
+impl std::error::Error for BudgetExhausted {}
 
 pub struct GitHubClient {
     client: Client,
     base_url: String,
     token: Option<String>,
     username: String,
+    /// 限制同时进行的请求数
+    limiter: Semaphore,
+    /// 本客户端已发出的 API 请求数（不含 raw 文件下载）
+    api_calls: AtomicU32,
+    /// 单次刷新允许的 API 请求上限
+    api_budget: u32,
+    /// 响应头中最小的 x-ratelimit-remaining，u32::MAX 表示尚未收到
+    rate_limit_remaining: AtomicU32,
 }
 
 impl GitHubClient {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let client = Client::new();
-        let base_url = "https://api.github.com".to_string();
+        let base_url = GitHubConfig::get_api_url();
         
         // 使用配置模块获取token和用户名
         let token = GitHubConfig::get_token();
@@ -27,15 +50,112 @@ impl GitHubClient {
             base_url,
             token,
             username,
+            limiter: Semaphore::new(GitHubConfig::get_fetch_concurrency()),
+            api_calls: AtomicU32::new(0),
+            api_budget: GitHubConfig::get_api_budget(),
+            rate_limit_remaining: AtomicU32::new(u32::MAX),
         })
     }
 
+    /// 创建带 User-Agent 和认证头的 GET 请求
+    pub(super) fn get(&self, url: &str) -> RequestBuilder {
+        self.authorize(self.client.get(url))
+    }
+
+    /// 创建带 User-Agent 和认证头的 POST 请求（GraphQL 使用）
+    pub(super) fn post(&self, url: &str) -> RequestBuilder {
+        self.authorize(self.client.post(url))
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = request.header("User-Agent", "xjz-website/1.0");
+        match self.token {
+            Some(ref token) => request.header("Authorization", format!("token {}", token)),
+            None => request,
+        }
+    }
+
+    pub(super) fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// 发送 GitHub API 请求：计入 API 预算并受并发限制
+    pub(super) async fn send(&self, request: RequestBuilder) -> Result<Response, Box<dyn std::error::Error>> {
+        let used = self.api_calls.fetch_add(1, Ordering::Relaxed);
+        if used >= self.api_budget {
+            self.api_calls.fetch_sub(1, Ordering::Relaxed);
+            return Err(Box::new(BudgetExhausted(self.api_budget)));
+        }
+        crate::metrics::metrics().github_api_calls.inc();
+        let response = self.send_unmetered(request).await?;
+
+        // 并发请求的响应可能乱序到达，取最小值作为剩余额度
+        if let Some(remaining) = response
+            .headers()
+            .get("x-ratelimit-remaining")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u32>().ok())
+        {
+            self.rate_limit_remaining.fetch_min(remaining, Ordering::Relaxed);
+            crate::metrics::metrics().github_rate_limit_remaining.set(i64::from(remaining));
+        }
+        Ok(response)
+    }
+
+    /// 发送不计入 API 预算的请求（raw 文件下载），仍受并发限制
+    pub(super) async fn send_unmetered(&self, request: RequestBuilder) -> Result<Response, Box<dyn std::error::Error>> {
+        if GitHubConfig::is_offline() {
+            return Err("离线模式下不访问 GitHub".into());
+        }
+        let _permit = self.limiter.acquire().await?;
+        Ok(request.send().await?)
+    }
+
+    /// 已使用的 API 请求数
+    pub fn api_calls(&self) -> u32 {
+        self.api_calls.load(Ordering::Relaxed)
+    }
+
+    /// GitHub 返回的剩余速率限制，尚未发出请求时为 None
+    pub fn rate_limit_remaining(&self) -> Option<u32> {
+        match self.rate_limit_remaining.load(Ordering::Relaxed) {
+            u32::MAX => None,
+            remaining => Some(remaining),
+        }
+    }
+
+    /// 剩余的 API 预算
+    pub fn remaining_budget(&self) -> u32 {
+        self.api_budget.saturating_sub(self.api_calls())
+    }
+
+    /// 检查响应状态，速率限制耗尽时给出更明确的错误信息
+    pub(super) fn check_response(response: &Response) -> Result<(), Box<dyn std::error::Error>> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        // 检查是否是速率限制问题
+        let headers = response.headers();
+        if status.as_u16() == 403
+            && headers.get("x-ratelimit-remaining").is_some_and(|v| v == "0")
+            && let Some(reset_time) = headers.get("x-ratelimit-reset")
+        {
+            return Err(format!(
+                "GitHub API速率限制已达上限。重置时间: {}。建议设置GITHUB_TOKEN环境变量以提高限制到5000次/小时。",
+                reset_time.to_str().unwrap_or("unknown")
+            ).into());
+        }
+
+        Err(format!("GitHub API error: {} {}", status.as_u16(), status.canonical_reason().unwrap_or("Unknown")).into())
+    }
+
     // 获取用户的所有公开仓库
     pub async fn get_user_repos(&self) -> Result<Vec<Repository>, Box<dyn std::error::Error>> {
         let url = format!("{}/users/{}/repos", self.base_url, self.username);
         
-        let mut request = self.client.get(&url)
-            .header("User-Agent", "xjz-website/1.0")
+        let request = self.get(&url)
             .query(&[
                 ("sort", "updated"),
                 ("direction", "desc"),
@@ -43,91 +163,48 @@ impl GitHubClient {
                 ("per_page", "100")
             ]);
 
-        // 如果有token，添加认证头
-        if let Some(ref token) = self.token {
-            request = request.header("Authorization", format!("token {}", token));
-        }
-
-        let response = request.send().await?;
+        let response = self.send(request).await?;
         
-        if !response.status().is_success() {
-            let status = response.status();
-            let headers = response.headers().clone();
-            
-            // 检查是否是速率限制问题
-            if status.as_u16() == 403 {
-                if let Some(remaining) = headers.get("x-ratelimit-remaining") {
-                    if remaining == "0" {
-                        if let Some(reset_time) = headers.get("x-ratelimit-reset") {
-                            return Err(format!(
-                                "GitHub API速率限制已达上限。重置时间: {}。建议设置GITHUB_TOKEN环境变量以提高限制到5000次/小时。", 
-                                reset_time.to_str().unwrap_or("unknown")
-                            ).into());
-                        }
-                    }
-                }
-            }
-            
-            return Err(format!("GitHub API error: {} {}", status.as_u16(), status.canonical_reason().unwrap_or("Unknown")).into());
-        }
+        Self::check_response(&response)?;
 
         let repos: Vec<Repository> = response.json().await?;
         Ok(repos)
     }
 
-    // 获取精选仓库（过滤和排序）
-    pub async fn get_featured_repos(&self) -> Result<Vec<Repository>, Box<dyn std::error::Error>> {
+    // 获取精选仓库（按配置规则过滤）
+    pub async fn get_featured_repos(&self, rules: &ProjectRules) -> Result<Vec<Repository>, Box<dyn std::error::Error>> {
         let all_repos = self.get_user_repos().await?;
-        
-        // 过滤和排序逻辑
+        let now = chrono::Utc::now();
+
         let featured_repos = all_repos
             .into_iter()
-            .filter(|repo| {
-                // 排除fork的仓库
-                !repo.fork &&
-                // 排除archived的仓库
-                !repo.archived &&
-                // 可以添加更多过滤条件，比如star数量、最近活跃度等
-                true
-            })
+            .filter(|repo| rules.matches(repo, now))
             .collect::<Vec<_>>();
-        
+
         Ok(featured_repos)
     }
 
+    // 获取单个仓库信息，仓库不存在或不可见时返回 None
+    pub async fn get_repo(&self, repo_name: &str) -> Result<Option<Repository>, Box<dyn std::error::Error>> {
+        let url = format!("{}/repos/{}/{}", self.base_url, self.username, repo_name);
+        let response = self.send(self.get(&url)).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Self::check_response(&response)?;
+
+        let repo: Repository = response.json().await?;
+        Ok(Some(repo))
+    }
+
     // 获取用户信息
     pub async fn get_user_info(&self) -> Result<User, Box<dyn std::error::Error>> {
         let url = format!("{}/users/{}", self.base_url, self.username);
         
-        let mut request = self.client.get(&url)
-            .header("User-Agent", "xjz-website/1.0");
-
-        if let Some(ref token) = self.token {
-            request = request.header("Authorization", format!("token {}", token));
-        }
-
-        let response = request.send().await?;
+        let response = self.send(self.get(&url)).await?;
         
-        if !response.status().is_success() {
-            let status = response.status();
-            let headers = response.headers().clone();
-            
-            // 检查是否是速率限制问题
-            if status.as_u16() == 403 {
-                if let Some(remaining) = headers.get("x-ratelimit-remaining") {
-                    if remaining == "0" {
-                        if let Some(reset_time) = headers.get("x-ratelimit-reset") {
-                            return Err(format!(
-                                "GitHub API速率限制已达上限。重置时间: {}。建议设置GITHUB_TOKEN环境变量以提高限制到5000次/小时。", 
-                                reset_time.to_str().unwrap_or("unknown")
-                            ).into());
-                        }
-                    }
-                }
-            }
-            
-            return Err(format!("GitHub API error: {} {}", status.as_u16(), status.canonical_reason().unwrap_or("Unknown")).into());
-        }
+        Self::check_response(&response)?;
 
         let user: User = response.json().await?;
         Ok(user)
@@ -137,19 +214,9 @@ impl GitHubClient {
     pub async fn get_repo_contents(&self, repo_name: &str, path: &str) -> Result<Vec<RepoContent>, Box<dyn std::error::Error>> {
         let url = format!("{}/repos/{}/{}/contents/{}", self.base_url, self.username, repo_name, path);
         
-        let mut request = self.client.get(&url)
-            .header("User-Agent", "xjz-website/1.0");
-
-        if let Some(ref token) = self.token {
-            request = request.header("Authorization", format!("token {}", token));
-        }
-
-        let response = request.send().await?;
+        let response = self.send(self.get(&url)).await?;
         
-        if !response.status().is_success() {
-            let status = response.status();
-            return Err(format!("GitHub API error: {} {}", status.as_u16(), status.canonical_reason().unwrap_or("Unknown")).into());
-        }
+        Self::check_response(&response)?;
 
         let contents: Vec<RepoContent> = response.json().await?;
         Ok(contents)
@@ -157,21 +224,93 @@ impl GitHubClient {
 
     // 获取文件内容
     pub async fn get_file_content(&self, download_url: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let mut request = self.client.get(download_url)
-            .header("User-Agent", "xjz-website/1.0");
+        let response = self.send_unmetered(self.get(download_url)).await?;
+        
+        Self::check_response(&response)?;
+
+        let content = response.text().await?;
+        Ok(content)
+    }
+
+    // 获取仓库 README 原始内容，仓库没有 README 时返回 None
+    pub async fn get_repo_readme(&self, repo_name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let url = format!("{}/repos/{}/{}/readme", self.base_url, self.username, repo_name);
+        let request = self.get(&url)
+            .header("Accept", "application/vnd.github.raw");
+        let response = self.send(request).await?;
 
-        if let Some(ref token) = self.token {
-            request = request.header("Authorization", format!("token {}", token));
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
         }
+        Self::check_response(&response)?;
 
-        let response = request.send().await?;
-        
-        if !response.status().is_success() {
-            let status = response.status();
-            return Err(format!("GitHub API error: {} {}", status.as_u16(), status.canonical_reason().unwrap_or("Unknown")).into());
+        Ok(Some(response.text().await?))
+    }
+
+    // 获取仓库的语言统计（语言名 -> 字节数）
+    pub async fn get_repo_languages(&self, repo_name: &str) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>> {
+        let url = format!("{}/repos/{}/{}/languages", self.base_url, self.username, repo_name);
+        let response = self.send(self.get(&url)).await?;
+
+        Self::check_response(&response)?;
+
+        let languages: HashMap<String, u64> = response.json().await?;
+        Ok(languages)
+    }
+
+    // 获取仓库的最新提交
+    pub async fn get_repo_commits(&self, repo_name: &str, per_page: u32) -> Result<Vec<Commit>, Box<dyn std::error::Error>> {
+        let url = format!("{}/repos/{}/{}/commits", self.base_url, self.username, repo_name);
+        let request = self.get(&url)
+            .query(&[("per_page", per_page.to_string())]);
+        let response = self.send(request).await?;
+
+        // 空仓库会返回 409
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return Ok(Vec::new());
         }
+        Self::check_response(&response)?;
 
-        let content = response.text().await?;
-        Ok(content)
+        let commits: Vec<Commit> = response.json().await?;
+        Ok(commits)
     }
-}
\ No newline at end of file
+
+    // 获取用户最近的公开事件
+    pub async fn get_public_events(&self, per_page: u32) -> Result<Vec<Event>, Box<dyn std::error::Error>> {
+        let url = format!("{}/users/{}/events/public", self.base_url, self.username);
+        let request = self.get(&url)
+            .query(&[("per_page", per_page.to_string())]);
+        let response = self.send(request).await?;
+
+        Self::check_response(&response)?;
+
+        let events: Vec<Event> = response.json().await?;
+        Ok(events)
+    }
+
+    // 获取用户的公开 Gist（按更新时间倒序）
+    pub async fn get_user_gists(&self, per_page: u32) -> Result<Vec<Gist>, Box<dyn std::error::Error>> {
+        let url = format!("{}/users/{}/gists", self.base_url, self.username);
+        let request = self.get(&url)
+            .query(&[("per_page", per_page.to_string())]);
+        let response = self.send(request).await?;
+
+        Self::check_response(&response)?;
+
+        let gists: Vec<Gist> = response.json().await?;
+        Ok(gists)
+    }
+
+    // 获取仓库的 Release 列表（按发布时间倒序）
+    pub async fn get_repo_releases(&self, repo_name: &str, per_page: u32) -> Result<Vec<Release>, Box<dyn std::error::Error>> {
+        let url = format!("{}/repos/{}/{}/releases", self.base_url, self.username, repo_name);
+        let request = self.get(&url)
+            .query(&[("per_page", per_page.to_string())]);
+        let response = self.send(request).await?;
+
+        Self::check_response(&response)?;
+
+        let releases: Vec<Release> = response.json().await?;
+        Ok(releases)
+    }
+}
@@ -1,5 +1,16 @@
 // GitHub API 配置
 
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use super::rules::ProjectRules;
+
+/// 项目展示规则文件的默认路径
+const PROJECT_RULES_FILE: &str = "config/projects.json";
+
+/// 启动参数 --offline 设置的离线标记
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
 pub struct GitHubConfig;
 
 impl GitHubConfig {
@@ -22,12 +33,129 @@ impl GitHubConfig {
         "xjz6626".to_string()
     }
     
+    /// 数据后端（GITHUB_API_BACKEND）：rest（默认）或 graphql
+    pub fn get_api_backend() -> String {
+        std::env::var("GITHUB_API_BACKEND")
+            .map(|v| v.trim().to_lowercase())
+            .unwrap_or_else(|_| "rest".to_string())
+    }
+
+    /// REST API 地址（GITHUB_API_URL），可指向本地 fixture 服务器
+    pub fn get_api_url() -> String {
+        std::env::var("GITHUB_API_URL")
+            .map(|v| v.trim_end_matches('/').to_string())
+            .unwrap_or_else(|_| "https://api.github.com".to_string())
+    }
+
+    /// GraphQL API 地址（GITHUB_GRAPHQL_URL），默认为 REST 地址下的 /graphql
+    pub fn get_graphql_url() -> String {
+        std::env::var("GITHUB_GRAPHQL_URL")
+            .unwrap_or_else(|_| format!("{}/graphql", Self::get_api_url()))
+    }
+
+    /// 原始文件下载地址（GITHUB_RAW_URL），GraphQL 后端据此拼接文章的下载链接
+    pub fn get_raw_url() -> String {
+        std::env::var("GITHUB_RAW_URL")
+            .map(|v| v.trim_end_matches('/').to_string())
+            .unwrap_or_else(|_| "https://raw.githubusercontent.com".to_string())
+    }
+
+    /// 获取 GitHub Webhook 签名密钥（GITHUB_WEBHOOK_SECRET），未配置时禁用 Webhook
+    pub fn get_webhook_secret() -> Option<String> {
+        std::env::var("GITHUB_WEBHOOK_SECRET")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+    }
+
+    /// 刷新时同时进行的 GitHub 请求数（GITHUB_FETCH_CONCURRENCY，默认 4）
+    pub fn get_fetch_concurrency() -> usize {
+        std::env::var("GITHUB_FETCH_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(4)
+    }
+
+    /// 单次刷新允许的 GitHub API 请求数（GITHUB_API_BUDGET，默认 300）
+    pub fn get_api_budget() -> u32 {
+        std::env::var("GITHUB_API_BUDGET")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(300)
+    }
+
+    /// 每个仓库单次刷新最多重新下载的文章数（GITHUB_ARTICLES_PER_REPO，默认 10）
+    /// 超出部分保留旧版本或留到下次刷新，不影响其他仓库
+    pub fn get_articles_per_repo() -> usize {
+        std::env::var("GITHUB_ARTICLES_PER_REPO")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(10)
+    }
+
+    /// 作为笔记收录的 Gist 标签（GITHUB_GIST_TAGS，逗号分隔，如 "note,til"）
+    /// 只收录描述中带有其中任一 #标签 的 Markdown Gist；未配置时收录全部 Markdown Gist
+    pub fn get_gist_tags() -> Vec<String> {
+        std::env::var("GITHUB_GIST_TAGS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+
+    /// 保留的刷新报告条数（REFRESH_HISTORY_LIMIT，默认 20）
+    pub fn get_refresh_history_limit() -> usize {
+        std::env::var("REFRESH_HISTORY_LIMIT")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(20)
+    }
+
+    /// 设置离线模式（启动参数 --offline）
+    pub fn set_offline(offline: bool) {
+        OFFLINE.store(offline, Ordering::Relaxed);
+    }
+
+    /// 是否处于离线模式：启动参数 --offline 或 GITHUB_OFFLINE=1
+    /// 离线模式下不访问 GitHub，只使用本地缓存数据
+    pub fn is_offline() -> bool {
+        OFFLINE.load(Ordering::Relaxed)
+            || std::env::var("GITHUB_OFFLINE").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+    }
+
     /// 检查配置状态
     pub fn check_config() {
-        println!("=== GitHub配置状态 ===");
-        println!("用户名: {}", Self::get_username());
-        println!("Token: 已配置 ✅");
-        println!("API限制: 5000次/小时");
-        println!("=====================");
+        tracing::debug!(
+            username = %Self::get_username(),
+            backend = %Self::get_api_backend(),
+            offline = Self::is_offline(),
+            "GitHub 配置: Token 已配置 ✅，API 限制 5000 次/小时"
+        );
+    }
+
+    /// 加载项目过滤/置顶规则
+    /// 路径可通过 PROJECT_RULES_FILE 环境变量覆盖，文件不存在时使用默认规则
+    pub fn load_project_rules() -> ProjectRules {
+        let path = std::env::var("PROJECT_RULES_FILE")
+            .unwrap_or_else(|_| PROJECT_RULES_FILE.to_string());
+
+        if !Path::new(&path).exists() {
+            return ProjectRules::default();
+        }
+
+        match fs::read_to_string(&path).map(|content| serde_json::from_str::<ProjectRules>(&content)) {
+            Ok(Ok(rules)) => rules,
+            Ok(Err(e)) => {
+                tracing::warn!("项目规则文件 {} 解析失败，使用默认规则: {}", path, e);
+                ProjectRules::default()
+            }
+            Err(e) => {
+                tracing::warn!("无法读取项目规则文件 {}，使用默认规则: {}", path, e);
+                ProjectRules::default()
+            }
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,844 @@
+// GitHub GraphQL v4 后端
+//
+// 一次分页查询取回仓库的元数据、语言、topic、Release、最近提交、README 和根目录文件列表，
+// 结果缓存在客户端中，项目详情和文章列表直接命中缓存，不再逐个仓库请求 REST API。
+// 请求的发送、并发限制和 API 预算复用 GitHubClient；缓存未命中时回退到 REST。
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::backend::GitHubBackend;
+use super::client::GitHubClient;
+use super::config::GitHubConfig;
+use super::models::*;
+use super::rules::ProjectRules;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// 每页仓库数，嵌套的 Release/附件数量决定了单次查询的节点数，不宜过大
+const REPOS_PER_PAGE: u32 = 50;
+
+const REPO_FIELDS: &str = r#"
+fragment RepoFields on Repository {
+  databaseId name nameWithOwner description url homepageUrl
+  primaryLanguage { name }
+  stargazerCount forkCount
+  watchers { totalCount }
+  issues(states: OPEN) { totalCount }
+  createdAt updatedAt pushedAt
+  repositoryTopics(first: 20) { nodes { topic { name } } }
+  visibility diskUsage isArchived isDisabled isFork
+  licenseInfo { key name spdxId url }
+  defaultBranchRef {
+    name
+    target { ... on Commit { history(first: 5) { nodes { oid message url author { name email date } } } } }
+  }
+  languages(first: 20, orderBy: { field: SIZE, direction: DESC }) { edges { size node { name } } }
+  releases(first: 20, orderBy: { field: CREATED_AT, direction: DESC }) {
+    nodes {
+      databaseId tagName name url publishedAt createdAt description isPrerelease isDraft
+      releaseAssets(first: 20) { nodes { name size downloadCount downloadUrl } }
+    }
+  }
+  readme: object(expression: "HEAD:README.md") { ... on Blob { text } }
+  readmeLower: object(expression: "HEAD:readme.md") { ... on Blob { text } }
+  tree: object(expression: "HEAD:") { ... on Tree { entries { name path type oid object { ... on Blob { byteSize } } } } }
+}
+"#;
+
+const USER_REPOS_QUERY: &str = r#"
+query UserRepos($login: String!, $first: Int!, $after: String) {
+  user(login: $login) {
+    repositories(first: $first, after: $after, ownerAffiliations: OWNER, privacy: PUBLIC,
+                 orderBy: { field: UPDATED_AT, direction: DESC }) {
+      pageInfo { hasNextPage endCursor }
+      nodes { ...RepoFields }
+    }
+  }
+}
+"#;
+
+const REPO_QUERY: &str = r#"
+query Repo($owner: String!, $name: String!) {
+  repository(owner: $owner, name: $name) { ...RepoFields }
+}
+"#;
+
+const PINNED_REPOS_QUERY: &str = r#"
+query PinnedRepos($login: String!) {
+  user(login: $login) {
+    pinnedItems(first: 6, types: REPOSITORY) { nodes { ... on Repository { name } } }
+  }
+}
+"#;
+
+const CONTRIBUTIONS_QUERY: &str = r#"
+query ContributionCalendar($login: String!) {
+  user(login: $login) {
+    contributionsCollection {
+      contributionCalendar {
+        totalContributions
+        weeks { contributionDays { date contributionCount contributionLevel } }
+      }
+    }
+  }
+}
+"#;
+
+const USER_INFO_QUERY: &str = r#"
+query UserInfo($login: String!) {
+  user(login: $login) {
+    login databaseId avatarUrl url name bio location email websiteUrl company
+    repositories(privacy: PUBLIC, ownerAffiliations: OWNER) { totalCount }
+    gists(privacy: PUBLIC) { totalCount }
+    followers { totalCount }
+    following { totalCount }
+    createdAt updatedAt
+  }
+}
+"#;
+
+// === 响应结构 ===
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+    #[serde(rename = "type")]
+    error_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Nodes<T> {
+    #[serde(default = "Vec::new")]
+    nodes: Vec<T>,
+}
+
+impl<T> Default for Nodes<T> {
+    fn default() -> Self {
+        Self { nodes: Vec::new() }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Count {
+    total_count: i32,
+}
+
+#[derive(Deserialize)]
+struct Named {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UserReposData {
+    user: Option<UserRepos>,
+}
+
+#[derive(Deserialize)]
+struct UserRepos {
+    repositories: RepoConnection,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepoConnection {
+    page_info: PageInfo,
+    nodes: Vec<RepoNode>,
+}
+
+#[derive(Deserialize)]
+struct RepoData {
+    repository: Option<RepoNode>,
+}
+
+#[derive(Deserialize)]
+struct PinnedData {
+    user: Option<PinnedUser>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PinnedUser {
+    pinned_items: Nodes<Named>,
+}
+
+#[derive(Deserialize)]
+struct UserInfoData {
+    user: Option<UserNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserNode {
+    login: String,
+    database_id: i64,
+    avatar_url: String,
+    url: String,
+    name: Option<String>,
+    bio: Option<String>,
+    location: Option<String>,
+    email: Option<String>,
+    website_url: Option<String>,
+    company: Option<String>,
+    repositories: Count,
+    gists: Count,
+    followers: Count,
+    following: Count,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepoNode {
+    database_id: i64,
+    name: String,
+    name_with_owner: String,
+    description: Option<String>,
+    url: String,
+    homepage_url: Option<String>,
+    primary_language: Option<Named>,
+    stargazer_count: i32,
+    fork_count: i32,
+    watchers: Count,
+    issues: Count,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    pushed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    repository_topics: Nodes<TopicNode>,
+    visibility: String,
+    disk_usage: Option<i32>,
+    is_archived: bool,
+    is_disabled: bool,
+    is_fork: bool,
+    license_info: Option<LicenseNode>,
+    default_branch_ref: Option<BranchRef>,
+    languages: Option<LanguageConnection>,
+    #[serde(default)]
+    releases: Nodes<ReleaseNode>,
+    readme: Option<BlobText>,
+    readme_lower: Option<BlobText>,
+    tree: Option<TreeNode>,
+}
+
+#[derive(Deserialize)]
+struct TopicNode {
+    topic: Named,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LicenseNode {
+    key: String,
+    name: String,
+    spdx_id: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BranchRef {
+    name: String,
+    target: Option<CommitTarget>,
+}
+
+#[derive(Deserialize)]
+struct CommitTarget {
+    #[serde(default)]
+    history: Nodes<CommitNode>,
+}
+
+#[derive(Deserialize)]
+struct CommitNode {
+    oid: String,
+    message: String,
+    url: String,
+    author: Option<GitActor>,
+}
+
+#[derive(Deserialize)]
+struct GitActor {
+    name: Option<String>,
+    email: Option<String>,
+    date: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct LanguageConnection {
+    #[serde(default)]
+    edges: Vec<LanguageEdge>,
+}
+
+#[derive(Deserialize)]
+struct LanguageEdge {
+    size: u64,
+    node: Named,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseNode {
+    database_id: Option<i64>,
+    tag_name: String,
+    name: Option<String>,
+    url: String,
+    published_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    description: Option<String>,
+    is_prerelease: bool,
+    is_draft: bool,
+    #[serde(default)]
+    release_assets: Nodes<AssetNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetNode {
+    name: String,
+    size: u64,
+    download_count: u64,
+    download_url: String,
+}
+
+#[derive(Deserialize)]
+struct BlobText {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TreeNode {
+    #[serde(default)]
+    entries: Vec<TreeEntry>,
+}
+
+#[derive(Deserialize)]
+struct TreeEntry {
+    name: String,
+    path: Option<String>,
+    #[serde(rename = "type")]
+    entry_type: String,
+    oid: String,
+    object: Option<BlobSize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlobSize {
+    #[serde(default)]
+    byte_size: u32,
+}
+
+#[derive(Deserialize)]
+struct ContributionsData {
+    user: Option<ContributionsUser>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionsUser {
+    contributions_collection: ContributionsCollection,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionsCollection {
+    contribution_calendar: CalendarNode,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarNode {
+    total_contributions: u32,
+    weeks: Vec<WeekNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WeekNode {
+    contribution_days: Vec<DayNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DayNode {
+    date: chrono::NaiveDate,
+    contribution_count: u32,
+    contribution_level: String,
+}
+
+/// URL 路径段中需要编码的字符，与 REST API 返回的 download_url / html_url 一致
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// 逐段编码仓库内的路径（保留 /），文件名中的空格、#、? 和中文不能直接拼进 URL
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 一个仓库在查询中附带取回的详情
+struct RepoBundle {
+    readme: Option<String>,
+    /// 根目录有 README 但文件名不是 README.md / readme.md，需要通过 REST 获取
+    readme_elsewhere: bool,
+    languages: HashMap<String, u64>,
+    commits: Vec<Commit>,
+    releases: Vec<Release>,
+    contents: Vec<RepoContent>,
+}
+
+impl RepoNode {
+    /// 拆分为仓库元数据和附带的详情
+    fn into_parts(self, raw_url: &str) -> (Repository, RepoBundle) {
+        let default_branch = self
+            .default_branch_ref
+            .as_ref()
+            .map(|b| b.name.clone())
+            .unwrap_or_else(|| "main".to_string());
+
+        let commits = self
+            .default_branch_ref
+            .and_then(|b| b.target)
+            .map(|t| t.history.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| {
+                let author = c.author.unwrap_or(GitActor { name: None, email: None, date: None });
+                Commit {
+                    sha: c.oid,
+                    html_url: c.url,
+                    commit: CommitDetails {
+                        message: c.message,
+                        author: CommitAuthor {
+                            name: author.name.unwrap_or_default(),
+                            email: author.email.unwrap_or_default(),
+                            date: author.date.unwrap_or(self.updated_at),
+                        },
+                    },
+                }
+            })
+            .collect();
+
+        let releases = self
+            .releases
+            .nodes
+            .into_iter()
+            .map(|r| Release {
+                id: r.database_id.unwrap_or_default(),
+                tag_name: r.tag_name,
+                name: r.name,
+                html_url: r.url,
+                published_at: r.published_at,
+                created_at: r.created_at,
+                body: r.description,
+                prerelease: r.is_prerelease,
+                draft: r.is_draft,
+                assets: r.release_assets.nodes
+                    .into_iter()
+                    .map(|a| ReleaseAsset {
+                        name: a.name,
+                        size: a.size,
+                        download_count: a.download_count,
+                        browser_download_url: a.download_url,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let languages = self
+            .languages
+            .map(|l| l.edges)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|e| e.node.name.map(|name| (name, e.size)))
+            .collect();
+
+        let entries = self.tree.map(|t| t.entries).unwrap_or_default();
+        let readme = self
+            .readme
+            .and_then(|b| b.text)
+            .or_else(|| self.readme_lower.and_then(|b| b.text));
+        let readme_elsewhere = readme.is_none()
+            && entries.iter().any(|e| e.entry_type == "blob" && e.name.to_lowercase().starts_with("readme"));
+
+        let contents = entries
+            .into_iter()
+            .map(|e| {
+                let path = e.path.unwrap_or_else(|| e.name.clone());
+                let item_type = match e.entry_type.as_str() {
+                    "blob" => "file",
+                    "tree" => "dir",
+                    _ => "submodule",
+                };
+                let url_path = format!("{}/{}", encode_path(&default_branch), encode_path(&path));
+                RepoContent {
+                    download_url: (item_type == "file")
+                        .then(|| format!("{}/{}/{}", raw_url, self.name_with_owner, url_path)),
+                    html_url: format!("{}/blob/{}", self.url, url_path),
+                    name: e.name,
+                    path,
+                    item_type: item_type.to_string(),
+                    size: e.object.map(|o| o.byte_size).unwrap_or_default(),
+                    sha: e.oid,
+                }
+            })
+            .collect();
+
+        let repo = Repository {
+            id: self.database_id,
+            name: self.name,
+            full_name: self.name_with_owner,
+            description: self.description,
+            clone_url: format!("{}.git", self.url),
+            html_url: self.url,
+            language: self.primary_language.and_then(|l| l.name),
+            stargazers_count: self.stargazer_count,
+            watchers_count: self.watchers.total_count,
+            forks_count: self.fork_count,
+            open_issues_count: self.issues.total_count,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            pushed_at: self.pushed_at,
+            homepage: self.homepage_url.filter(|h| !h.is_empty()),
+            topics: self.repository_topics.nodes.into_iter().filter_map(|t| t.topic.name).collect(),
+            visibility: self.visibility.to_lowercase(),
+            default_branch,
+            license: self.license_info.map(|l| License {
+                key: l.key,
+                name: l.name,
+                spdx_id: l.spdx_id,
+                url: l.url,
+            }),
+            size: self.disk_usage.unwrap_or_default(),
+            archived: self.is_archived,
+            disabled: self.is_disabled,
+            fork: self.is_fork,
+        };
+
+        let bundle = RepoBundle { readme, readme_elsewhere, languages, commits, releases, contents };
+        (repo, bundle)
+    }
+}
+
+pub struct GraphQlClient {
+    rest: GitHubClient,
+    endpoint: String,
+    raw_url: String,
+    /// 仓库名 -> 查询时附带取回的详情
+    cache: Mutex<HashMap<String, RepoBundle>>,
+}
+
+impl GraphQlClient {
+    pub fn new(rest: GitHubClient) -> Self {
+        Self {
+            rest,
+            endpoint: GitHubConfig::get_graphql_url(),
+            raw_url: GitHubConfig::get_raw_url(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn query<T: DeserializeOwned>(&self, operation: &str, query: String, variables: Value) -> Result<T> {
+        run_query(&self.rest, &self.endpoint, operation, query, variables).await
+    }
+
+    /// 缓存仓库详情并返回仓库元数据
+    fn cache_repo(&self, node: RepoNode) -> Repository {
+        let (repo, bundle) = node.into_parts(&self.raw_url);
+        self.cache.lock().unwrap().insert(repo.name.clone(), bundle);
+        repo
+    }
+
+    fn cached<R>(&self, repo_name: &str, read: impl FnOnce(&RepoBundle) -> R) -> Option<R> {
+        self.cache.lock().unwrap().get(repo_name).map(read)
+    }
+}
+
+/// 发送 GraphQL 查询，NOT_FOUND 错误交给调用方按空数据处理
+async fn run_query<T: DeserializeOwned>(
+    rest: &GitHubClient,
+    endpoint: &str,
+    operation: &str,
+    query: String,
+    variables: Value,
+) -> Result<T> {
+    let request = rest.post(endpoint).json(&json!({
+        "query": query,
+        "operationName": operation,
+        "variables": variables,
+    }));
+    let response = rest.send(request).await?;
+    GitHubClient::check_response(&response)?;
+
+    let body: GraphQlResponse<T> = response.json().await?;
+    let errors: Vec<&str> = body
+        .errors
+        .iter()
+        .filter(|e| e.error_type.as_deref() != Some("NOT_FOUND"))
+        .map(|e| e.message.as_str())
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("GitHub GraphQL 错误: {}", errors.join("; ")).into());
+    }
+    body.data.ok_or_else(|| "GitHub GraphQL 响应缺少 data".into())
+}
+
+/// 查询用户过去一年的贡献日历
+/// 贡献数据只有 GraphQL API 提供，REST 后端也通过这里获取
+pub(super) async fn fetch_contribution_calendar(rest: &GitHubClient, endpoint: &str) -> Result<ContributionCalendar> {
+    let data: ContributionsData = run_query(
+        rest,
+        endpoint,
+        "ContributionCalendar",
+        CONTRIBUTIONS_QUERY.to_string(),
+        json!({ "login": rest.username() }),
+    )
+    .await?;
+    let calendar = data
+        .user
+        .ok_or_else(|| format!("GitHub 用户 {} 不存在", rest.username()))?
+        .contributions_collection
+        .contribution_calendar;
+
+    Ok(ContributionCalendar {
+        total: calendar.total_contributions,
+        weeks: calendar
+            .weeks
+            .into_iter()
+            .map(|week| {
+                week.contribution_days
+                    .into_iter()
+                    .map(|day| ContributionDay {
+                        date: day.date,
+                        count: day.contribution_count,
+                        level: contribution_level(&day.contribution_level),
+                    })
+                    .collect()
+            })
+            .collect(),
+    })
+}
+
+/// GitHub 的 contributionLevel 转换为 0-4 的颜色等级，NONE 和未知值为 0
+fn contribution_level(level: &str) -> u8 {
+    match level {
+        "FIRST_QUARTILE" => 1,
+        "SECOND_QUARTILE" => 2,
+        "THIRD_QUARTILE" => 3,
+        "FOURTH_QUARTILE" => 4,
+        _ => 0,
+    }
+}
+
+#[async_trait]
+impl GitHubBackend for GraphQlClient {
+    fn name(&self) -> &'static str {
+        "graphql"
+    }
+
+    async fn get_featured_repos(&self, rules: &ProjectRules) -> Result<Vec<Repository>> {
+        let now = Utc::now();
+        let mut repos = Vec::new();
+        let mut after: Option<String> = None;
+
+        loop {
+            let data: UserReposData = self
+                .query(
+                    "UserRepos",
+                    format!("{}{}", USER_REPOS_QUERY, REPO_FIELDS),
+                    json!({ "login": self.rest.username(), "first": REPOS_PER_PAGE, "after": after }),
+                )
+                .await?;
+            let Some(user) = data.user else {
+                return Err(format!("GitHub 用户 {} 不存在", self.rest.username()).into());
+            };
+
+            let connection = user.repositories;
+            repos.extend(connection.nodes.into_iter().map(|node| self.cache_repo(node)));
+            if !connection.page_info.has_next_page {
+                break;
+            }
+            after = connection.page_info.end_cursor;
+        }
+
+        Ok(repos.into_iter().filter(|repo| rules.matches(repo, now)).collect())
+    }
+
+    async fn get_pinned_repos(&self) -> Result<Vec<String>> {
+        let data: PinnedData = self
+            .query("PinnedRepos", PINNED_REPOS_QUERY.to_string(), json!({ "login": self.rest.username() }))
+            .await?;
+        Ok(data
+            .user
+            .map(|u| u.pinned_items.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|n| n.name)
+            .collect())
+    }
+
+    async fn get_repo(&self, repo_name: &str) -> Result<Option<Repository>> {
+        let data: RepoData = self
+            .query(
+                "Repo",
+                format!("{}{}", REPO_QUERY, REPO_FIELDS),
+                json!({ "owner": self.rest.username(), "name": repo_name }),
+            )
+            .await?;
+        Ok(data.repository.map(|node| self.cache_repo(node)))
+    }
+
+    async fn get_user_info(&self) -> Result<User> {
+        let data: UserInfoData = self
+            .query("UserInfo", USER_INFO_QUERY.to_string(), json!({ "login": self.rest.username() }))
+            .await?;
+        let user = data
+            .user
+            .ok_or_else(|| format!("GitHub 用户 {} 不存在", self.rest.username()))?;
+
+        Ok(User {
+            login: user.login,
+            id: user.database_id,
+            avatar_url: user.avatar_url,
+            html_url: user.url,
+            name: user.name,
+            bio: user.bio,
+            location: user.location,
+            email: user.email.filter(|e| !e.is_empty()),
+            blog: user.website_url,
+            company: user.company,
+            public_repos: user.repositories.total_count,
+            public_gists: user.gists.total_count,
+            followers: user.followers.total_count,
+            following: user.following.total_count,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        })
+    }
+
+    async fn get_contribution_calendar(&self) -> Result<ContributionCalendar> {
+        fetch_contribution_calendar(&self.rest, &self.endpoint).await
+    }
+
+    async fn get_public_events(&self, per_page: u32) -> Result<Vec<Event>> {
+        // GraphQL API 没有事件流，使用 REST
+        self.rest.get_public_events(per_page).await
+    }
+
+    async fn get_user_gists(&self, per_page: u32) -> Result<Vec<Gist>> {
+        // 文件内容仍需逐个下载，列表用 REST 一次取回即可
+        self.rest.get_user_gists(per_page).await
+    }
+
+    async fn get_repo_readme(&self, repo_name: &str) -> Result<Option<String>> {
+        match self.cached(repo_name, |b| (b.readme.clone(), b.readme_elsewhere)) {
+            Some((readme, false)) => Ok(readme),
+            _ => self.rest.get_repo_readme(repo_name).await,
+        }
+    }
+
+    async fn get_repo_languages(&self, repo_name: &str) -> Result<HashMap<String, u64>> {
+        match self.cached(repo_name, |b| b.languages.clone()) {
+            Some(languages) => Ok(languages),
+            None => self.rest.get_repo_languages(repo_name).await,
+        }
+    }
+
+    async fn get_repo_commits(&self, repo_name: &str, per_page: u32) -> Result<Vec<Commit>> {
+        let cached = self.cached(repo_name, |b| b.commits.iter().take(per_page as usize).cloned().collect());
+        match cached {
+            Some(commits) => Ok(commits),
+            None => self.rest.get_repo_commits(repo_name, per_page).await,
+        }
+    }
+
+    async fn get_repo_releases(&self, repo_name: &str, per_page: u32) -> Result<Vec<Release>> {
+        let cached = self.cached(repo_name, |b| b.releases.iter().take(per_page as usize).cloned().collect());
+        match cached {
+            Some(releases) => Ok(releases),
+            None => self.rest.get_repo_releases(repo_name, per_page).await,
+        }
+    }
+
+    async fn get_repo_contents(&self, repo_name: &str, path: &str) -> Result<Vec<RepoContent>> {
+        // 查询只取回根目录，子目录仍走 REST
+        let cached = if path.is_empty() {
+            self.cached(repo_name, |b| b.contents.clone())
+        } else {
+            None
+        };
+        match cached {
+            Some(contents) => Ok(contents),
+            None => self.rest.get_repo_contents(repo_name, path).await,
+        }
+    }
+
+    async fn get_file_content(&self, download_url: &str) -> Result<String> {
+        self.rest.get_file_content(download_url).await
+    }
+
+    fn api_calls(&self) -> u32 {
+        self.rest.api_calls()
+    }
+
+    fn remaining_budget(&self) -> u32 {
+        self.rest.remaining_budget()
+    }
+
+    fn rate_limit_remaining(&self) -> Option<u32> {
+        self.rest.rate_limit_remaining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_segments_are_encoded() {
+        assert_eq!(encode_path("docs/rust-async.md"), "docs/rust-async.md");
+        assert_eq!(encode_path("部署 笔记 #1.md"), "%E9%83%A8%E7%BD%B2%20%E7%AC%94%E8%AE%B0%20%231.md");
+        assert_eq!(encode_path("a?b/100%.md"), "a%3Fb/100%25.md");
+    }
+
+    #[test]
+    fn contribution_levels_are_bucketed() {
+        let levels: Vec<u8> = ["NONE", "FIRST_QUARTILE", "SECOND_QUARTILE", "THIRD_QUARTILE", "FOURTH_QUARTILE", "OTHER"]
+            .into_iter()
+            .map(contribution_level)
+            .collect();
+        assert_eq!(levels, [0, 1, 2, 3, 4, 0]);
+    }
+}
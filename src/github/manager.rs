@@ -1,34 +1,91 @@
-use crate::github::client::GitHubClient;
+use crate::github::backend::{self, GitHubBackend};
+use crate::github::client::BudgetExhausted;
+use crate::github::config::GitHubConfig;
 use crate::github::storage::*;
+use crate::github::models::{Event, Gist, Language, Repository};
+use crate::github::report::{self, RefreshReport, RefreshTrigger, RepoReport, RepoStatus};
+use crate::github::relations;
+use crate::github::rules::ProjectRules;
+use crate::lifecycle;
 use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use tokio::time::{sleep, Duration};
 use serde_json;
+use tokio::sync::Mutex;
+use tracing::Instrument;
+
+/// 串行化所有写入数据文件的刷新操作（全量更新与单仓库增量刷新）
+static REFRESH_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// 刷新失败后的首次重试间隔，之后每次失败翻倍
+const RETRY_BASE_SECS: i64 = 60;
+/// 重试间隔上限
+const RETRY_MAX_SECS: i64 = 3600;
+
+/// 刷新失败后的重试退避状态（进程内共享）
+struct RetryBackoff {
+    failures: u32,
+    next_attempt: Option<DateTime<Utc>>,
+}
+
+static RETRY_BACKOFF: std::sync::Mutex<RetryBackoff> = std::sync::Mutex::new(RetryBackoff {
+    failures: 0,
+    next_attempt: None,
+});
+
+/// 单仓库增量刷新的范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoRefresh {
+    /// 仅仓库元数据（star、描述、topic 等）
+    Metadata,
+    /// 元数据 + README、语言、提交、Release
+    Details,
+    /// 以上全部 + 仓库中的文章
+    Full,
+}
 
 pub struct GitHubDataManager {
-    client: GitHubClient,
+    client: Box<dyn GitHubBackend>,
     data_file: String,
-    username: String,
+    history_file: String,
+    /// 当前（或最近一次）刷新的报告，并发任务通过 record_* 写入
+    report: std::sync::Mutex<Option<RefreshReport>>,
 }
 
 impl GitHubDataManager {
     pub fn new(username: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = GitHubClient::new()?;
-        let data_file = format!("data/github_{}.json", username);
-        
-        Ok(Self {
+        let client = backend::create_backend()?;
+        Ok(Self::with_backend(client, Path::new("data"), &username))
+    }
+
+    /// 使用指定的数据后端和数据目录（集成测试用 fixture 服务器和临时目录）
+    pub fn with_backend(client: Box<dyn GitHubBackend>, data_dir: &Path, username: &str) -> Self {
+        let data_file = data_dir.join(format!("github_{}.json", username));
+        let history_file = data_dir.join(format!("refresh_history_{}.json", username));
+
+        Self {
             client,
-            data_file,
-            username,
-        })
+            data_file: data_file.to_string_lossy().into_owned(),
+            history_file: history_file.to_string_lossy().into_owned(),
+            report: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// 数据文件路径
+    pub fn data_file(&self) -> &str {
+        &self.data_file
+    }
+
+    /// 数据目录，刷新结果和刷新报告都保存在这里
+    pub fn data_dir(&self) -> &Path {
+        Path::new(&self.data_file).parent().unwrap_or(Path::new("."))
     }
 
     /// 确保数据目录存在
     fn ensure_data_dir(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(parent) = Path::new(&self.data_file).parent() {
-            fs::create_dir_all(parent)?;
-        }
+        fs::create_dir_all(self.data_dir())?;
         Ok(())
     }
 
@@ -47,11 +104,93 @@ impl GitHubDataManager {
     fn save_data(&self, data: &GitHubData) -> Result<(), Box<dyn std::error::Error>> {
         self.ensure_data_dir()?;
         let content = serde_json::to_string_pretty(data)?;
-        fs::write(&self.data_file, content)?;
-        println!("GitHub数据已保存到: {}", self.data_file);
+        write_atomic(Path::new(&self.data_file), content.as_bytes())?;
+        tracing::debug!("GitHub数据已保存到: {}", self.data_file);
         Ok(())
     }
 
+    /// 最近的刷新报告（最新在前）
+    pub fn load_history(&self) -> Vec<RefreshReport> {
+        report::load_history(&self.history_file)
+    }
+
+    /// 本地缓存数据的更新时间，没有缓存文件时返回 None
+    pub fn cached_last_updated(&self) -> Option<DateTime<Utc>> {
+        if !Path::new(&self.data_file).exists() {
+            return None;
+        }
+        self.load_data().ok().map(|data| data.last_updated)
+    }
+
+    /// 本管理器最近一次刷新的报告
+    pub fn last_report(&self) -> Option<RefreshReport> {
+        self.report.lock().unwrap().clone()
+    }
+
+    fn begin_report(&self, trigger: RefreshTrigger, target: Option<&str>) {
+        let mut report = RefreshReport::new(trigger, target, self.client.name());
+        // 客户端可能已被之前的刷新使用过，记录起点以便只统计本次调用
+        report.api_calls = self.client.api_calls();
+        *self.report.lock().unwrap() = Some(report);
+    }
+
+    fn record_repo(&self, repo_name: &str, update: impl FnOnce(&mut RepoReport)) {
+        if let Some(report) = self.report.lock().unwrap().as_mut() {
+            update(report.repo_mut(repo_name));
+        }
+    }
+
+    fn record_failure(&self, repo_name: &str, (status, reason): (RepoStatus, String)) {
+        self.record_repo(repo_name, |repo| repo.fail(status, reason));
+    }
+
+    /// 预算用完的错误记为跳过，其余记为失败
+    fn classify_error(e: Box<dyn std::error::Error>) -> (RepoStatus, String) {
+        let status = if e.is::<BudgetExhausted>() { RepoStatus::Skipped } else { RepoStatus::Error };
+        (status, e.to_string())
+    }
+
+    /// 补全报告并写入刷新历史
+    fn finish_report(&self, result: Result<&GitHubData, String>) {
+        let mut guard = self.report.lock().unwrap();
+        let Some(report) = guard.as_mut() else {
+            return;
+        };
+
+        let now = Utc::now();
+        report.finished_at = Some(now);
+        report.duration_ms = (now - report.started_at).num_milliseconds();
+        report.api_calls = self.client.api_calls().saturating_sub(report.api_calls);
+        report.rate_limit_remaining = self.client.rate_limit_remaining();
+        match result {
+            Ok(data) => {
+                report.success = true;
+                report.projects = data.projects.len();
+                report.articles = data.articles.len();
+                report.notes = data.notes.len();
+            }
+            Err(e) => report.error = Some(e),
+        }
+
+        tracing::info!("刷新报告: {}", report.summary());
+        let metrics = crate::metrics::metrics();
+        let trigger = report.trigger.as_str();
+        metrics
+            .refreshes
+            .with_label_values(&[trigger, if report.success { "success" } else { "error" }])
+            .inc();
+        metrics
+            .refresh_duration
+            .with_label_values(&[trigger])
+            .observe(report.duration_ms as f64 / 1000.0);
+        if report.success {
+            metrics.last_refresh_success.set(now.timestamp() as f64);
+        }
+        if let Err(e) = report::append_history(&self.history_file, report, GitHubConfig::get_refresh_history_limit()) {
+            tracing::warn!("保存刷新报告失败: {}", e);
+        }
+    }
+
     /// 检查是否需要更新数据
     pub fn needs_update(&self) -> bool {
         match self.load_data() {
@@ -60,38 +199,67 @@ impl GitHubDataManager {
         }
     }
 
+    /// 加载展示规则，并把 GitHub 个人主页置顶的仓库追加到配置的置顶列表之后
+    async fn load_rules(&self) -> ProjectRules {
+        let mut rules = GitHubConfig::load_project_rules();
+        match self.client.get_pinned_repos().await {
+            Ok(pinned) => {
+                for name in pinned {
+                    if !rules.is_pinned(&name) {
+                        rules.pinned.push(name);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("获取 GitHub 置顶仓库失败: {}", e),
+        }
+        rules
+    }
+
     /// 获取用户的仓库并转换为StoredProject
     async fn fetch_projects(&self) -> Result<Vec<StoredProject>, Box<dyn std::error::Error>> {
-        let repos = self.client.get_featured_repos().await?;
+        let rules = self.load_rules().await;
+        let repos = self.client.get_featured_repos(&rules).await?;
         
-        let mut projects = Vec::new();
-        for repo in repos {
-            let project = StoredProject {
-                id: repo.id as u64,
-                name: repo.name,
-                full_name: repo.full_name,
-                description: repo.description,
-                html_url: repo.html_url,
-                language: repo.language,
-                stargazers_count: repo.stargazers_count as u32,
-                forks_count: repo.forks_count as u32,
-                updated_at: repo.updated_at,
-                created_at: repo.created_at,
-                topics: repo.topics,
-                open_issues_count: repo.open_issues_count as u32,
-                size: repo.size as u32,
-                default_branch: repo.default_branch,
-                archived: repo.archived,
-                fork: repo.fork,
-            };
-            projects.push(project);
-        }
+        let mut projects: Vec<StoredProject> = repos
+            .into_iter()
+            .map(|repo| Self::to_stored_project(repo, &rules))
+            .collect();
 
-        // 按更新时间排序
-        projects.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        // 置顶项目在前，其余按更新时间排序
+        rules.sort(&mut projects);
         Ok(projects)
     }
 
+    /// 将 GitHub 仓库转换为 StoredProject 并应用展示覆盖
+    fn to_stored_project(repo: Repository, rules: &ProjectRules) -> StoredProject {
+        let mut project = StoredProject {
+            id: repo.id as u64,
+            name: repo.name,
+            full_name: repo.full_name,
+            description: repo.description,
+            html_url: repo.html_url,
+            language: repo.language,
+            stargazers_count: repo.stargazers_count as u32,
+            forks_count: repo.forks_count as u32,
+            updated_at: repo.updated_at,
+            created_at: repo.created_at,
+            topics: repo.topics,
+            open_issues_count: repo.open_issues_count as u32,
+            size: repo.size as u32,
+            default_branch: repo.default_branch,
+            archived: repo.archived,
+            fork: repo.fork,
+            pushed_at: repo.pushed_at,
+            pinned: false,
+            display_name: None,
+            cover_image: None,
+            license: repo.license,
+            latest_version: None,
+        };
+        rules.apply_overrides(&mut project);
+        project
+    }
+
     /// 获取用户统计信息
     async fn fetch_user_stats(&self) -> Result<UserStats, Box<dyn std::error::Error>> {
         let user = self.client.get_user_info().await?;
@@ -116,54 +284,369 @@ impl GitHubDataManager {
         Ok(stats)
     }
 
+    /// 获取贡献日历和最近的公开动态，各部分失败时保留上次的数据
+    async fn fetch_activity(&self, previous: &Activity) -> Activity {
+        let (contributions, events) = tokio::join!(
+            async { self.client.get_contribution_calendar().await.map_err(|e| e.to_string()) },
+            async { self.client.get_public_events(50).await.map_err(|e| e.to_string()) },
+        );
+
+        let contributions = match contributions {
+            Ok(calendar) => Some(calendar),
+            Err(e) => {
+                tracing::warn!("获取贡献日历失败: {}", e);
+                previous.contributions.clone()
+            }
+        };
+        let events = match events {
+            Ok(events) => events.into_iter().filter_map(Self::to_activity_item).take(30).collect(),
+            Err(e) => {
+                tracing::warn!("获取公开动态失败: {}", e);
+                previous.events.clone()
+            }
+        };
+
+        Activity { contributions, events }
+    }
+
+    /// 将公开事件转换为动态条目，只保留推送、PR、Issue 和 Release
+    fn to_activity_item(event: Event) -> Option<ActivityItem> {
+        let payload = &event.payload;
+        let text = |pointer: &str| payload.pointer(pointer).and_then(|v| v.as_str()).map(str::to_string);
+        let repo_url = format!("https://github.com/{}", event.repo.name);
+
+        let (kind, title, url) = match event.event_type.as_str() {
+            "PushEvent" => {
+                let branch = text("/ref").map(|r| r.trim_start_matches("refs/heads/").to_string()).unwrap_or_default();
+                let commits = payload.pointer("/commits").and_then(|c| c.as_array()).map(Vec::len);
+                let title = match (commits, text("/commits/0/message")) {
+                    (Some(n), Some(message)) if n > 0 => format!(
+                        "推送 {} 个提交到 {}: {}",
+                        n, branch, message.lines().next().unwrap_or("")
+                    ),
+                    _ => format!("推送到 {}", branch),
+                };
+                let url = match text("/head") {
+                    Some(head) => format!("{}/commit/{}", repo_url, head),
+                    None => repo_url.clone(),
+                };
+                ("push", title, url)
+            }
+            "PullRequestEvent" => {
+                let action = text("/action").unwrap_or_default();
+                let title = text("/pull_request/title").unwrap_or_default();
+                let url = text("/pull_request/html_url").unwrap_or_else(|| repo_url.clone());
+                ("pull_request", format!("{} PR: {}", action, title), url)
+            }
+            "IssuesEvent" => {
+                let action = text("/action").unwrap_or_default();
+                let title = text("/issue/title").unwrap_or_default();
+                let url = text("/issue/html_url").unwrap_or_else(|| repo_url.clone());
+                ("issue", format!("{} Issue: {}", action, title), url)
+            }
+            "ReleaseEvent" => {
+                let name = text("/release/name")
+                    .filter(|n| !n.is_empty())
+                    .or_else(|| text("/release/tag_name"))
+                    .unwrap_or_default();
+                let url = text("/release/html_url").unwrap_or_else(|| repo_url.clone());
+                ("release", format!("发布 {}", name), url)
+            }
+            _ => return None,
+        };
+
+        Some(ActivityItem {
+            kind: kind.to_string(),
+            repo_name: event.repo.name,
+            title,
+            url,
+            created_at: event.created_at,
+        })
+    }
+
+    /// 获取项目详情页所需的 README、语言、提交和 Release 信息
+    async fn fetch_project_details(&self, projects: &[StoredProject]) -> HashMap<String, ProjectDetails> {
+        let concurrency = GitHubConfig::get_fetch_concurrency();
+
+        // 先收集成 Vec 再交给 stream，避免闭包类型进入 Future 导致 Send 推导失败
+        let tasks: Vec<_> = projects
+            .iter()
+            .map(|project| async move { (project.name.clone(), self.fetch_repo_details(project).await) })
+            .collect();
+
+        stream::iter(tasks)
+            .buffer_unordered(concurrency)
+            .collect()
+            .await
+    }
+
+    /// 获取单个仓库的详情，各部分失败时保留默认值
+    async fn fetch_repo_details(&self, project: &StoredProject) -> ProjectDetails {
+        let mut detail = ProjectDetails::default();
+
+        // 四个请求并行发出，总并发由客户端限制
+        // （错误先转为 (状态, 原因)，Box<dyn Error> 不能跨 await 持有）
+        let (readme, languages, commits, releases) = tokio::join!(
+            async { self.client.get_repo_readme(&project.name).await.map_err(Self::classify_error) },
+            async { self.client.get_repo_languages(&project.name).await.map_err(Self::classify_error) },
+            async { self.client.get_repo_commits(&project.name, 5).await.map_err(Self::classify_error) },
+            async { self.client.get_repo_releases(&project.name, 20).await.map_err(Self::classify_error) },
+        );
+
+        match readme {
+            Ok(readme) => detail.readme = readme,
+            Err((status, e)) => {
+                tracing::warn!("获取 {} 的 README 失败: {}", project.name, e);
+                self.record_failure(&project.name, (status, format!("README: {}", e)));
+            }
+        }
+
+        match languages {
+            Ok(languages) => {
+                let total: u64 = languages.values().sum();
+                let mut list: Vec<Language> = languages
+                    .into_iter()
+                    .map(|(name, bytes)| Language {
+                        name,
+                        bytes: bytes.min(i32::MAX as u64) as i32,
+                        percentage: if total > 0 { bytes as f32 * 100.0 / total as f32 } else { 0.0 },
+                    })
+                    .collect();
+                list.sort_by_key(|l| std::cmp::Reverse(l.bytes));
+                detail.languages = list;
+            }
+            Err((status, e)) => {
+                tracing::warn!("获取 {} 的语言统计失败: {}", project.name, e);
+                self.record_failure(&project.name, (status, format!("语言统计: {}", e)));
+            }
+        }
+
+        match commits {
+            Ok(commits) => {
+                detail.recent_commits = commits
+                    .into_iter()
+                    .map(|c| StoredCommit {
+                        sha: c.sha,
+                        message: c.commit.message.lines().next().unwrap_or("").to_string(),
+                        author: c.commit.author.name,
+                        date: c.commit.author.date,
+                        html_url: c.html_url,
+                    })
+                    .collect();
+            }
+            Err((status, e)) => {
+                tracing::warn!("获取 {} 的提交记录失败: {}", project.name, e);
+                self.record_failure(&project.name, (status, format!("提交记录: {}", e)));
+            }
+        }
+
+        match releases {
+            Ok(releases) => {
+                detail.releases = releases
+                    .into_iter()
+                    .filter(|r| !r.draft)
+                    .map(|r| StoredRelease {
+                        tag_name: r.tag_name,
+                        name: r.name,
+                        html_url: r.html_url,
+                        published_at: r.published_at.or(Some(r.created_at)),
+                        body: r.body,
+                        prerelease: r.prerelease,
+                        assets: r.assets
+                            .into_iter()
+                            .map(|a| StoredReleaseAsset {
+                                name: a.name,
+                                size: a.size,
+                                download_count: a.download_count,
+                                download_url: a.browser_download_url,
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                detail.releases.sort_by_key(|r| std::cmp::Reverse(r.published_at));
+                detail.latest_release = detail.releases.iter().find(|r| !r.prerelease).cloned();
+            }
+            Err((status, e)) => {
+                tracing::warn!("获取 {} 的 Release 列表失败: {}", project.name, e);
+                self.record_failure(&project.name, (status, format!("Release 列表: {}", e)));
+            }
+        }
+
+        detail
+    }
+
     /// 从仓库中获取Markdown文档
-    async fn fetch_articles(&self, projects: &[StoredProject]) -> Result<Vec<StoredArticle>, Box<dyn std::error::Error>> {
+    /// previous 为上次刷新的文章，源文件 SHA 未变化的文章直接复用
+    async fn fetch_articles(&self, projects: &[StoredProject], previous: &[StoredArticle]) -> Result<Vec<StoredArticle>, Box<dyn std::error::Error>> {
+        let concurrency = GitHubConfig::get_fetch_concurrency();
+
+        // 所有仓库并行处理，总请求数受客户端并发限制和 API 预算约束
+        let tasks: Vec<_> = projects
+            .iter()
+            .map(|project| {
+                let repo_previous: Vec<StoredArticle> = previous
+                    .iter()
+                    .filter(|a| a.repo_name == project.name)
+                    .cloned()
+                    .collect();
+                async move {
+                    tracing::debug!("正在处理仓库: {}", project.name);
+                    let result = self
+                        .fetch_articles_from_repo(project, &repo_previous)
+                        .await
+                        .map_err(Self::classify_error);
+                    (project.name.as_str(), repo_previous, result)
+                }
+                .instrument(tracing::info_span!("repo", repo = %project.name))
+            })
+            .collect();
+
+        let results: Vec<_> = stream::iter(tasks)
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+
         let mut articles = Vec::new();
-        
-        // 限制处理的仓库数量，避免API限制
-        for project in projects.iter().take(10) {
-            println!("正在处理仓库: {}", project.name);
-            match self.fetch_articles_from_repo(project).await {
+        for (repo_name, repo_previous, result) in results {
+            match result {
                 Ok(mut repo_articles) => {
-                    println!("从仓库 {} 获取了 {} 篇文章", project.name, repo_articles.len());
+                    self.record_repo(repo_name, |repo| repo.articles = repo_articles.len());
                     articles.append(&mut repo_articles);
-                },
-                Err(e) => {
-                    println!("跳过仓库 {}: {}", project.name, e);
+                }
+                Err((status, e)) => {
+                    // 获取失败时保留该仓库上次的文章，避免被误判为删除
+                    tracing::warn!("跳过仓库 {}: {}（保留 {} 篇已缓存文章）", repo_name, e, repo_previous.len());
+                    let kept = repo_previous.len();
+                    self.record_repo(repo_name, |repo| {
+                        repo.articles = kept;
+                        repo.fail(status, format!("文章: {}（保留 {} 篇已缓存文章）", e, kept));
+                    });
+                    articles.extend(repo_previous);
                 }
             }
         }
 
         // 按更新时间排序
-        articles.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-        println!("总共获取到 {} 篇文章", articles.len());
+        articles.sort_by_key(|a| std::cmp::Reverse(a.updated_at));
+        tracing::info!("总共获取到 {} 篇文章，使用 GitHub API {} 次，剩余预算 {} 次",
+            articles.len(), self.client.api_calls(), self.client.remaining_budget());
         Ok(articles)
     }
 
-    /// 从单个仓库获取Markdown文档
-    async fn fetch_articles_from_repo(&self, project: &StoredProject) -> Result<Vec<StoredArticle>, Box<dyn std::error::Error>> {
+    /// 从单个仓库获取Markdown文档，只下载新增或 SHA 变化的文件
+    /// 每个仓库单次最多下载 GITHUB_ARTICLES_PER_REPO 篇，超出的文件保留旧版本或留到下次刷新
+    async fn fetch_articles_from_repo(&self, project: &StoredProject, previous: &[StoredArticle]) -> Result<Vec<StoredArticle>, Box<dyn std::error::Error>> {
         let contents = self.client.get_repo_contents(&project.name, "").await?;
+        let previous: HashMap<&str, &StoredArticle> = previous
+            .iter()
+            .map(|a| (a.file_path.as_str(), a))
+            .collect();
         let mut articles = Vec::new();
+        let mut to_fetch = Vec::new();
 
         for item in contents {
             if item.item_type == "file" && 
                item.name.ends_with(".md") && 
                !item.name.to_lowercase().starts_with("readme") {
-                
-                println!("找到Markdown文件: {}/{}", project.name, item.name);
-                match self.create_article_from_file(&item, project).await {
-                    Ok(article) => {
-                        articles.push(article);
-                        println!("成功创建文章: {}", item.name);
-                    },
-                    Err(e) => println!("跳过文件 {}/{}: {}", project.name, item.name, e),
+
+                if let Some(old) = previous.get(item.path.as_str())
+                    && !item.sha.is_empty()
+                    && old.sha == item.sha
+                {
+                    articles.push(self.reuse_article(old, project));
+                } else {
+                    to_fetch.push(item);
+                }
+            }
+        }
+
+        let per_repo = GitHubConfig::get_articles_per_repo();
+        if to_fetch.len() > per_repo {
+            tracing::info!("仓库 {} 有 {} 个文件需要更新，本次处理 {} 个", project.name, to_fetch.len(), per_repo);
+            let deferred = to_fetch.len() - per_repo;
+            self.record_repo(&project.name, |repo| {
+                repo.reasons.push(format!("{} 个文件超出单仓库上限，留到下次刷新", deferred));
+            });
+            for item in to_fetch.drain(per_repo..) {
+                if let Some(old) = previous.get(item.path.as_str()) {
+                    articles.push(self.reuse_article(old, project));
+                }
+            }
+        }
+
+        let tasks: Vec<_> = to_fetch
+            .iter()
+            .map(|item| async move {
+                tracing::debug!("找到Markdown文件: {}/{}", project.name, item.name);
+                let result = self.create_article_from_file(item, project)
+                    .await
+                    .map_err(Self::classify_error);
+                (item, result)
+            })
+            .collect();
+
+        let fetched: Vec<_> = stream::iter(tasks)
+            .buffer_unordered(GitHubConfig::get_fetch_concurrency())
+            .collect()
+            .await;
+
+        let mut over_budget = 0;
+        for (item, result) in fetched {
+            match result {
+                Ok(article) => {
+                    tracing::debug!("成功创建文章: {}", article.file_path);
+                    articles.push(article);
+                },
+                // API 预算用完与超出单仓库上限相同：保留旧版本，留到下次刷新，不算失败
+                Err((RepoStatus::Skipped, _)) => {
+                    over_budget += 1;
+                    if let Some(old) = previous.get(item.path.as_str()) {
+                        articles.push(self.reuse_article(old, project));
+                    }
+                }
+                Err((status, e)) => {
+                    // 下载失败时保留旧版本（连同旧 SHA，下次刷新会重试），只有仓库中删除的文件才算删除
+                    let old = previous.get(item.path.as_str());
+                    let reason = match old {
+                        Some(_) => format!("跳过文件 {}/{}: {}（保留旧版本）", project.name, item.name, e),
+                        None => format!("跳过文件 {}/{}: {}", project.name, item.name, e),
+                    };
+                    tracing::warn!("{}", reason);
+                    self.record_failure(&project.name, (status, reason));
+                    if let Some(old) = old {
+                        articles.push(self.reuse_article(old, project));
+                    }
                 }
             }
         }
+        if over_budget > 0 {
+            tracing::info!("仓库 {} 有 {} 个文件因 API 预算用完留到下次刷新", project.name, over_budget);
+            self.record_repo(&project.name, |repo| {
+                repo.reasons.push(format!("{} 个文件因 API 预算用完，留到下次刷新", over_budget));
+            });
+        }
 
         Ok(articles)
     }
 
+    /// 复用未变化的文章，仅同步依赖项目信息的字段
+    fn reuse_article(&self, old: &StoredArticle, project: &StoredProject) -> StoredArticle {
+        let mut article = old.clone();
+        article.file_url = format!("https://github.com/{}/blob/{}/{}",
+            project.full_name, project.default_branch, article.file_path);
+        article.repo_url = project.html_url.clone();
+        article.updated_at = project.updated_at;
+        article.featured = Self::is_featured(project, article.reading_time, article.file_size);
+        article
+    }
+
+    /// 判断是否为精选文章 (基于项目星数、文件大小、阅读时间等)
+    fn is_featured(project: &StoredProject, reading_time: u32, file_size: u32) -> bool {
+        project.stargazers_count > 2 || 
+            reading_time > 5 || 
+            file_size > 5000
+    }
+
     /// 从文件创建文章对象
     async fn create_article_from_file(
         &self, 
@@ -189,22 +672,15 @@ impl GitHubDataManager {
             // 提取标签
             let tags = self.extract_tags(&content, &project.name);
             
-            // 判断是否为精选文章 (基于项目星数、文件大小、阅读时间等)
-            let featured = project.stargazers_count > 2 || 
-                          reading_time > 5 || 
-                          file.size > 5000;
+            let featured = Self::is_featured(project, reading_time, file.size);
 
-            // 从full_name中提取owner (格式: "owner/repo")
-            let owner = project.full_name.split('/').next().unwrap_or("unknown");
-            
             let article = StoredArticle {
                 id: format!("{}-{}", project.name, file.name.replace(".md", "")),
                 title,
                 content,
                 excerpt,
                 file_path: file.path.clone(),
-                file_url: format!("https://github.com/{}/blob/{}/{}", 
-                    project.full_name, project.default_branch, file.path),
+                file_url: file.html_url.clone(),
                 repo_name: project.name.clone(),
                 repo_url: project.html_url.clone(),
                 updated_at: project.updated_at,
@@ -213,6 +689,9 @@ impl GitHubDataManager {
                 category,
                 tags,
                 featured,
+                sha: file.sha.clone(),
+                series: None,
+                related: Vec::new(),
             };
 
             Ok(article)
@@ -223,11 +702,17 @@ impl GitHubDataManager {
 
     /// 提取文档标题
     fn extract_title(&self, content: &str, filename: &str) -> String {
+        // front matter 中的 title 优先
+        let (front_matter, content) = relations::split_front_matter(content);
+        if let Some(title) = front_matter.get("title").filter(|t| !t.is_empty()) {
+            return title.clone();
+        }
+
         // 查找第一个 # 标题
         for line in content.lines().take(10) {
             let line = line.trim();
-            if line.starts_with("# ") {
-                return line[2..].trim().to_string();
+            if let Some(title) = line.strip_prefix("# ") {
+                return title.trim().to_string();
             }
         }
         
@@ -237,6 +722,7 @@ impl GitHubDataManager {
 
     /// 生成文章摘要
     fn generate_excerpt(&self, content: &str) -> String {
+        let (_, content) = relations::split_front_matter(content);
         let lines: Vec<&str> = content.lines()
             .filter(|line| !line.trim().starts_with('#') && !line.trim().is_empty())
             .take(3)
@@ -298,23 +784,227 @@ impl GitHubDataManager {
         tags
     }
 
-    /// 更新所有GitHub数据
-    pub async fn update_data(&self) -> Result<GitHubData, Box<dyn std::error::Error>> {
-        println!("开始更新GitHub数据...");
+    /// 把 Markdown Gist 转换为笔记，只下载新增或更新过的 Gist
+    async fn fetch_notes(&self, previous: &[StoredNote]) -> Vec<StoredNote> {
+        let gists = match self.client.get_user_gists(100).await {
+            Ok(gists) => gists,
+            Err(e) => {
+                tracing::warn!("获取 Gist 列表失败: {}（保留 {} 条已缓存笔记）", e, previous.len());
+                return previous.to_vec();
+            }
+        };
+
+        let wanted_tags = GitHubConfig::get_gist_tags();
+        let previous: HashMap<&str, &StoredNote> = previous.iter().map(|n| (n.id.as_str(), n)).collect();
+        let mut notes = Vec::new();
+        let mut to_fetch = Vec::new();
+
+        for gist in gists {
+            if !gist.files.values().any(Self::is_markdown_file) {
+                continue;
+            }
+            let (_, tags) = Self::split_gist_description(gist.description.as_deref());
+            if !wanted_tags.is_empty() && !tags.iter().any(|t| wanted_tags.contains(&t.to_lowercase())) {
+                continue;
+            }
+            match previous.get(gist.id.as_str()) {
+                Some(old) if old.updated_at == gist.updated_at => notes.push((*old).clone()),
+                _ => to_fetch.push(gist),
+            }
+        }
+
+        let tasks: Vec<_> = to_fetch
+            .into_iter()
+            .map(|gist| async move {
+                let id = gist.id.clone();
+                let result = self.create_note_from_gist(gist).await.map_err(|e| e.to_string());
+                (id, result)
+            })
+            .collect();
+        let fetched: Vec<_> = stream::iter(tasks)
+            .buffer_unordered(GitHubConfig::get_fetch_concurrency())
+            .collect()
+            .await;
+
+        for (id, result) in fetched {
+            match result {
+                Ok(note) => notes.push(note),
+                Err(e) => {
+                    // 下载失败时保留旧版本
+                    tracing::warn!("跳过 Gist {}: {}", id, e);
+                    if let Some(old) = previous.get(id.as_str()) {
+                        notes.push((*old).clone());
+                    }
+                }
+            }
+        }
+
+        notes.sort_by_key(|n| std::cmp::Reverse(n.updated_at));
+        tracing::info!("共 {} 条 Gist 笔记", notes.len());
+        notes
+    }
+
+    fn is_markdown_file(file: &crate::github::models::GistFile) -> bool {
+        file.language.as_deref() == Some("Markdown") || file.filename.to_lowercase().ends_with(".md")
+    }
+
+    /// 拆分 Gist 描述中的文字和 #标签，如 "Rust 异步笔记 #rust #til"
+    fn split_gist_description(description: Option<&str>) -> (String, Vec<String>) {
+        let mut words = Vec::new();
+        let mut tags = Vec::new();
+        for word in description.unwrap_or("").split_whitespace() {
+            match word.strip_prefix('#') {
+                Some(tag) if !tag.is_empty() => tags.push(tag.to_string()),
+                _ => words.push(word),
+            }
+        }
+        (words.join(" "), tags)
+    }
+
+    /// 下载 Gist 中的 Markdown 文件并生成笔记
+    async fn create_note_from_gist(&self, gist: Gist) -> Result<StoredNote, Box<dyn std::error::Error>> {
+        let mut files = Vec::new();
+        let mut contents = Vec::new();
+        // BTreeMap 按文件名排序，多个文件按顺序拼接
+        for file in gist.files.values().filter(|f| Self::is_markdown_file(f)) {
+            contents.push(self.client.get_file_content(&file.raw_url).await?);
+            files.push(file.filename.clone());
+        }
+        let content = contents.join("\n\n");
+
+        let (description, tags) = Self::split_gist_description(gist.description.as_deref());
+        let fallback_title = if description.is_empty() { files[0].clone() } else { description.clone() };
+        let title = content
+            .lines()
+            .take(10)
+            .find_map(|line| line.trim().strip_prefix("# ").map(|t| t.trim().to_string()))
+            .unwrap_or(fallback_title);
+
+        Ok(StoredNote {
+            id: gist.id,
+            title,
+            excerpt: self.generate_excerpt(&content),
+            reading_time: (content.chars().count() / 250).max(1) as u32,
+            description,
+            content,
+            files,
+            html_url: gist.html_url,
+            tags,
+            created_at: gist.created_at,
+            updated_at: gist.updated_at,
+        })
+    }
+
+    /// 更新所有GitHub数据，并把刷新报告写入历史
+    pub async fn update_data(&self, trigger: RefreshTrigger) -> Result<GitHubData, Box<dyn std::error::Error>> {
+        let _guard = REFRESH_LOCK.lock().await;
+        self.update_locked(trigger).await
+    }
+
+    /// 已持有 REFRESH_LOCK 时执行全量刷新
+    #[tracing::instrument(name = "refresh", skip(self))]
+    async fn update_locked(&self, trigger: RefreshTrigger) -> Result<GitHubData, Box<dyn std::error::Error>> {
+        if lifecycle::is_shutting_down() {
+            return Err("服务正在关闭，不再开始新的刷新".into());
+        }
+        self.begin_report(trigger, None);
+        let result = tokio::select! {
+            result = self.run_full_update() => result,
+            _ = lifecycle::shutdown_token().cancelled() => Err("服务正在关闭，刷新已取消".into()),
+        };
+        self.finish_report(result.as_ref().map_err(|e| e.to_string()));
+        match &result {
+            Ok(_) => Self::reset_backoff(),
+            // 关闭时被取消的刷新不计入失败退避
+            Err(_) if lifecycle::is_shutting_down() => {}
+            Err(_) => {
+                let retry_at = Self::schedule_retry();
+                tracing::warn!("全量刷新失败，{} 之前不再自动重试", retry_at);
+            }
+        }
+        result
+    }
+
+    /// 连续失败 failures 次之后的重试间隔（秒）：从 RETRY_BASE_SECS 开始翻倍，不超过 RETRY_MAX_SECS
+    fn retry_delay(failures: u32) -> i64 {
+        RETRY_BASE_SECS
+            .saturating_mul(1 << failures.min(16))
+            .min(RETRY_MAX_SECS)
+    }
+
+    /// 记录一次刷新失败，返回下一次允许自动刷新的时间
+    fn schedule_retry() -> DateTime<Utc> {
+        let mut backoff = RETRY_BACKOFF.lock().unwrap();
+        let delay = Self::retry_delay(backoff.failures);
+        backoff.failures += 1;
+        let retry_at = Utc::now() + chrono::Duration::seconds(delay);
+        backoff.next_attempt = Some(retry_at);
+        retry_at
+    }
+
+    fn reset_backoff() {
+        let mut backoff = RETRY_BACKOFF.lock().unwrap();
+        backoff.failures = 0;
+        backoff.next_attempt = None;
+    }
+
+    /// 仍处于退避期时返回下一次允许重试的时间
+    fn pending_retry() -> Option<DateTime<Utc>> {
+        RETRY_BACKOFF.lock().unwrap().next_attempt.filter(|t| Utc::now() < *t)
+    }
+
+    /// 加载本地缓存并标记为降级数据
+    fn load_degraded(&self, retry_at: Option<DateTime<Utc>>) -> Result<GitHubData, Box<dyn std::error::Error>> {
+        let mut data = self.load_data()?;
+        data.degraded = true;
+        data.retry_at = retry_at;
+        Ok(data)
+    }
+
+    async fn run_full_update(&self) -> Result<GitHubData, Box<dyn std::error::Error>> {
+        tracing::info!("开始更新GitHub数据...");
         
         // 获取项目信息
-        println!("获取项目信息...");
-        let projects = self.fetch_projects().await?;
-        println!("找到 {} 个活跃项目", projects.len());
+        tracing::debug!("获取项目信息...");
+        let mut projects = self.fetch_projects().await?;
+        tracing::info!("找到 {} 个活跃项目", projects.len());
+        for project in &projects {
+            self.record_repo(&project.name, |_| {});
+        }
 
         // 获取用户统计
-        println!("获取用户统计...");
+        tracing::debug!("获取用户统计...");
         let user_stats = self.fetch_user_stats().await.ok();
 
-        // 获取文章
-        println!("获取Markdown文档...");
-        let articles = self.fetch_articles(&projects).await?;
-        println!("找到 {} 篇文档", articles.len());
+        // 获取贡献日历和最近动态，失败时沿用上次的数据
+        tracing::debug!("获取贡献日历和最近动态...");
+        let previous = self.load_data().unwrap_or_default();
+        let activity = self.fetch_activity(&previous.activity).await;
+
+        // 获取项目详情
+        tracing::debug!("获取项目详情...");
+        let project_details = self.fetch_project_details(&projects).await;
+        for project in projects.iter_mut() {
+            project.latest_version = project_details
+                .get(&project.name)
+                .and_then(|d| d.latest_release.as_ref())
+                .map(|r| r.tag_name.clone());
+        }
+
+        // 获取文章（复用上次刷新中未变化的文件）
+        tracing::debug!("获取Markdown文档...");
+        let previous_articles = previous.articles;
+        let mut articles = self.fetch_articles(&projects, &previous_articles).await?;
+        relations::link_articles(&mut articles);
+        let article_changes = ArticleChanges::between(&previous_articles, &articles);
+        tracing::info!("找到 {} 篇文档（{}）", articles.len(), article_changes.summary());
+        if let Some(report) = self.report.lock().unwrap().as_mut() {
+            report.article_changes = Some(article_changes.clone());
+        }
+
+        // 获取 Gist 笔记，列表获取失败时沿用上次的笔记
+        tracing::debug!("获取 Gist 笔记...");
+        let notes = self.fetch_notes(&previous.notes).await;
 
         let now = Utc::now();
         let data = GitHubData {
@@ -322,30 +1012,220 @@ impl GitHubDataManager {
             articles,
             user_stats,
             language_stats: None, // 后续可以实现
+            project_details,
+            article_changes: Some(article_changes),
+            activity,
+            notes,
             last_updated: now,
             next_update: now + chrono::Duration::days(1),
+            degraded: false,
+            retry_at: None,
         };
 
         // 保存数据
         self.save_data(&data)?;
-        println!("GitHub数据更新完成！下次更新时间: {}", data.next_update);
+        tracing::info!("GitHub数据更新完成！下次更新时间: {}", data.next_update);
+
+        Ok(data)
+    }
+
+    /// 增量刷新单个仓库对应的项目、详情和文章，不触发全量更新
+    /// 仓库已删除或不再符合展示规则时，从数据中移除
+    #[tracing::instrument(name = "repo_refresh", skip(self))]
+    pub async fn refresh_repo(&self, repo_name: &str, scope: RepoRefresh) -> Result<GitHubData, Box<dyn std::error::Error>> {
+        let _guard = REFRESH_LOCK.lock().await;
+        if lifecycle::is_shutting_down() {
+            return Err("服务正在关闭，不再开始新的刷新".into());
+        }
+        self.begin_report(RefreshTrigger::Webhook, Some(repo_name));
+        let result = tokio::select! {
+            result = self.run_repo_refresh(repo_name, scope) => result,
+            _ = lifecycle::shutdown_token().cancelled() => Err("服务正在关闭，刷新已取消".into()),
+        };
+        if let Err(e) = &result {
+            self.record_failure(repo_name, (RepoStatus::Error, e.to_string()));
+        }
+        self.finish_report(result.as_ref().map_err(|e| e.to_string()));
+        result
+    }
+
+    async fn run_repo_refresh(&self, repo_name: &str, scope: RepoRefresh) -> Result<GitHubData, Box<dyn std::error::Error>> {
+        let mut data = self.load_data()?;
+        let rules = self.load_rules().await;
+
+        let repo = self.client.get_repo(repo_name).await?
+            .filter(|repo| rules.matches(repo, Utc::now()));
+
+        let Some(repo) = repo else {
+            tracing::info!("仓库 {} 不存在或不符合展示规则，从数据中移除", repo_name);
+            self.record_failure(repo_name, (RepoStatus::Skipped, "仓库不存在或不符合展示规则，已移除".to_string()));
+            Self::remove_repo_entries(&mut data, repo_name);
+            self.record_article_changes(&data);
+            self.save_data(&data)?;
+            return Ok(data);
+        };
+
+        let mut project = Self::to_stored_project(repo, &rules);
+        self.record_repo(&project.name, |_| {});
+
+        if scope == RepoRefresh::Metadata {
+            // 保留上次刷新得到的版本信息
+            project.latest_version = data.projects.iter()
+                .find(|p| p.name == project.name)
+                .and_then(|p| p.latest_version.clone());
+        } else {
+            tracing::debug!("刷新仓库 {} 的详情...", project.name);
+            let details = self.fetch_repo_details(&project).await;
+            project.latest_version = details.latest_release.as_ref().map(|r| r.tag_name.clone());
+            data.project_details.insert(project.name.clone(), details);
+        }
+
+        if scope == RepoRefresh::Full {
+            tracing::debug!("刷新仓库 {} 的文章...", project.name);
+            let (previous, others): (Vec<StoredArticle>, Vec<StoredArticle>) = std::mem::take(&mut data.articles)
+                .into_iter()
+                .partition(|a| a.repo_name == project.name);
+            let articles = self.fetch_articles_from_repo(&project, &previous).await?;
+            let changes = ArticleChanges::between(&previous, &articles);
+            tracing::info!("仓库 {} 文章变化: {}", project.name, changes.summary());
+            self.record_repo(&project.name, |repo| repo.articles = articles.len());
+            data.article_changes = Some(changes);
+            self.record_article_changes(&data);
+            data.articles = others;
+            data.articles.extend(articles);
+            data.articles.sort_by_key(|a| std::cmp::Reverse(a.updated_at));
+            relations::link_articles(&mut data.articles);
+        }
+
+        match data.projects.iter_mut().find(|p| p.name == project.name) {
+            Some(existing) => *existing = project,
+            None => data.projects.push(project),
+        }
+        rules.sort(&mut data.projects);
+
+        // 只更新 last_updated，不推迟全量更新计划
+        data.last_updated = Utc::now();
+        self.save_data(&data)?;
+        tracing::info!("仓库 {} 增量刷新完成 ({:?})", repo_name, scope);
 
         Ok(data)
     }
 
+    /// 从数据中移除仓库对应的项目、详情和文章
+    #[tracing::instrument(name = "repo_remove", skip(self))]
+    pub async fn remove_repo(&self, repo_name: &str) -> Result<GitHubData, Box<dyn std::error::Error>> {
+        let _guard = REFRESH_LOCK.lock().await;
+        if lifecycle::is_shutting_down() {
+            return Err("服务正在关闭，不再修改数据".into());
+        }
+        self.begin_report(RefreshTrigger::Webhook, Some(repo_name));
+        let result = self.load_data().and_then(|mut data| {
+            Self::remove_repo_entries(&mut data, repo_name);
+            self.save_data(&data)?;
+            Ok(data)
+        });
+        if let Ok(data) = &result {
+            self.record_failure(repo_name, (RepoStatus::Skipped, "仓库已删除、设为私有或改名，已移除".to_string()));
+            self.record_article_changes(data);
+        }
+        self.finish_report(result.as_ref().map_err(|e| e.to_string()));
+        result
+    }
+
+    fn record_article_changes(&self, data: &GitHubData) {
+        if let Some(report) = self.report.lock().unwrap().as_mut() {
+            report.article_changes = data.article_changes.clone();
+        }
+    }
+
+    fn remove_repo_entries(data: &mut GitHubData, repo_name: &str) {
+        data.projects.retain(|p| p.name != repo_name);
+        data.project_details.remove(repo_name);
+
+        let previous = data.articles.clone();
+        data.articles.retain(|a| a.repo_name != repo_name);
+        data.article_changes = Some(ArticleChanges::between(&previous, &data.articles));
+        // 被删除文章可能出现在其他文章的相关推荐中
+        relations::link_articles(&mut data.articles);
+    }
+
     /// 获取数据（如果需要则自动更新）
+    /// 离线、退避期内或刷新失败时返回本地缓存，并标记 degraded
     pub async fn get_data(&self) -> Result<GitHubData, Box<dyn std::error::Error>> {
-        if self.needs_update() {
-            println!("数据需要更新，正在从GitHub获取最新数据...");
-            self.update_data().await
-        } else {
-            println!("使用本地缓存数据");
-            self.load_data()
+        if !self.needs_update() {
+            tracing::debug!("使用本地缓存数据");
+            return self.load_data();
+        }
+
+        if GitHubConfig::is_offline() {
+            tracing::debug!("离线模式，使用本地缓存数据");
+            return self.load_degraded(None);
+        }
+
+        if let Some(retry_at) = Self::pending_retry() {
+            tracing::debug!("上次刷新失败，{} 之前使用本地缓存数据", retry_at);
+            return self.load_degraded(Some(retry_at));
+        }
+
+        let result = {
+            let _guard = REFRESH_LOCK.lock().await;
+            // 等锁期间前一个持有者可能已经刷新完成或刷新失败进入退避，直接使用它的结果，
+            // 同时到达的请求只触发一次刷新
+            if !self.needs_update() {
+                tracing::debug!("其他请求已完成刷新，使用本地缓存数据");
+                return self.load_data();
+            }
+            if let Some(retry_at) = Self::pending_retry() {
+                tracing::debug!("其他请求的刷新失败，{} 之前使用本地缓存数据", retry_at);
+                return self.load_degraded(Some(retry_at));
+            }
+            tracing::info!("数据需要更新，正在从GitHub获取最新数据...");
+            self.update_locked(RefreshTrigger::Scheduled).await
+        };
+        match result {
+            Ok(data) => Ok(data),
+            // 没有缓存文件时无可降级，直接返回错误
+            Err(e) if !Path::new(&self.data_file).exists() => Err(e),
+            Err(e) => {
+                tracing::warn!("刷新失败，使用本地缓存数据: {}", e);
+                self.load_degraded(Self::pending_retry())
+            }
         }
     }
 
     /// 强制更新数据
     pub async fn force_update(&self) -> Result<GitHubData, Box<dyn std::error::Error>> {
-        self.update_data().await
+        self.update_data(RefreshTrigger::Manual).await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_up_to_an_hour() {
+        let delays: Vec<i64> = (0..9).map(GitHubDataManager::retry_delay).collect();
+        assert_eq!(delays, [60, 120, 240, 480, 960, 1920, 3600, 3600, 3600]);
+        assert_eq!(GitHubDataManager::retry_delay(u32::MAX), 3600);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn failures_schedule_growing_retries_until_reset() {
+        GitHubDataManager::reset_backoff();
+        let mut previous = Utc::now();
+        for expected in [60, 120, 240] {
+            let retry_at = GitHubDataManager::schedule_retry();
+            let delay = (retry_at - Utc::now()).num_seconds();
+            assert!((expected - 2..=expected).contains(&delay), "期望约 {} 秒，实际 {} 秒", expected, delay);
+            assert!(retry_at > previous);
+            previous = retry_at;
+        }
+        assert_eq!(GitHubDataManager::pending_retry(), Some(previous));
+
+        GitHubDataManager::reset_backoff();
+        assert_eq!(GitHubDataManager::pending_retry(), None);
+        let delay = (GitHubDataManager::schedule_retry() - Utc::now()).num_seconds();
+        assert!((58..=60).contains(&delay));
+        GitHubDataManager::reset_backoff();
+    }
+}
@@ -1,11 +1,19 @@
 pub mod models;
 pub mod client;
+pub mod backend;
+pub mod graphql;
 pub mod storage;
 pub mod manager;
 pub mod config;
+pub mod rules;
+pub mod report;
+pub mod relations;
+pub mod webhook;
 
 pub use models::*;
 pub use client::GitHubClient;
 pub use storage::*;
 pub use manager::GitHubDataManager;
-pub use config::GitHubConfig;
\ No newline at end of file
+pub use config::GitHubConfig;
+pub use report::RefreshReport;
+pub use webhook::handle_github_webhook;
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Repository {
@@ -83,33 +84,74 @@ pub struct Language {
     pub percentage: f32,
 }
 
-// API响应结构
-#[derive(Debug, Serialize)]
-pub struct ApiResponse<T> {
-    pub success: bool,
-    pub data: Option<T>,
-    pub error: Option<String>,
-    pub timestamp: DateTime<Utc>,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub id: i64,
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub html_url: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub body: Option<String>,
+    pub prerelease: bool,
+    pub draft: bool,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub size: u64,
+    pub download_count: u64,
+    pub browser_download_url: String,
+}
+
+// 贡献日历中的一天（来自 GraphQL contributionCalendar）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributionDay {
+    pub date: NaiveDate,
+    pub count: u32,
+    pub level: u8, // 0-4，对应 GitHub 热力图的颜色深浅
 }
 
-impl<T> ApiResponse<T> {
-    pub fn success(data: T) -> Self {
-        Self {
-            success: true,
-            data: Some(data),
-            error: None,
-            timestamp: Utc::now(),
-        }
-    }
-
-    pub fn error(error: String) -> Self {
-        Self {
-            success: false,
-            data: None,
-            error: Some(error),
-            timestamp: Utc::now(),
-        }
-    }
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContributionCalendar {
+    pub total: u32,
+    pub weeks: Vec<Vec<ContributionDay>>, // 每周从周日开始，首尾两周可能不满 7 天
+}
+
+// 用户公开事件（REST /users/{user}/events/public）
+#[derive(Debug, Clone, Deserialize)]
+pub struct Event {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub repo: EventRepo,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventRepo {
+    pub name: String, // owner/repo
+}
+
+// 用户公开 Gist（REST /users/{user}/gists），列表中不含文件内容
+#[derive(Debug, Clone, Deserialize)]
+pub struct Gist {
+    pub id: String,
+    pub html_url: String,
+    pub description: Option<String>,
+    pub files: BTreeMap<String, GistFile>, // 文件名 -> 文件
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GistFile {
+    pub filename: String,
+    pub language: Option<String>,
+    pub raw_url: String,
 }
 
 // GitHub仓库内容
@@ -120,6 +162,8 @@ pub struct RepoContent {
     #[serde(rename = "type")]
     pub item_type: String, // "file" or "dir"
     pub size: u32,
+    #[serde(default)]
+    pub sha: String,  // blob SHA，用于判断文件是否变化
     pub download_url: Option<String>,
     pub html_url: String,
 }
\ No newline at end of file
@@ -0,0 +1,430 @@
+// 文章之间的关联：系列（上一篇/下一篇）和相关文章
+//
+// 系列优先读取 front matter 中的 series 和 series_order（或 order / part），
+// 没有时按文件名编号识别，如 docs/fedora/01-install.md 或 setup-part2.md，同一系列至少两篇。
+// 相关文章按标签重合度、分类和正文 TF-IDF 余弦相似度加权打分，刷新时计算后随文章一起保存。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::storage::{ArticleLink, RelatedArticle, SeriesInfo, StoredArticle};
+
+/// 每篇文章最多保留的相关文章数
+const MAX_RELATED: usize = 5;
+/// 低于该得分的不算相关
+const MIN_SCORE: f32 = 0.2;
+const CONTENT_WEIGHT: f32 = 0.6;
+const TAG_WEIGHT: f32 = 0.25;
+const CATEGORY_WEIGHT: f32 = 0.15;
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "was", "our", "use", "this",
+    "that", "with", "from", "have", "will", "your", "into", "when", "then", "than", "there", "their",
+    "which", "what", "about", "also", "more", "some", "only", "just", "like", "each", "other", "these",
+    "those", "here", "been", "being", "does", "how", "its", "it", "is", "in", "on", "of", "to", "as",
+    "at", "be", "by", "if", "or", "an", "we", "do", "so", "no", "up", "http", "https", "www", "com",
+    "github", "md",
+];
+
+/// 解析开头 `---` 之间的 front matter（只支持单行的 key: value），返回键值和去掉 front matter 后的正文
+pub fn split_front_matter(content: &str) -> (HashMap<String, String>, &str) {
+    let mut fields = HashMap::new();
+    let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+        return (fields, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim();
+        if line == "---" {
+            return (fields, &rest[offset..]);
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            fields.insert(key.trim().to_lowercase(), value.to_string());
+        }
+    }
+
+    // 没有结束标记，不当作 front matter
+    (HashMap::new(), content)
+}
+
+/// 重新计算所有文章的系列和相关文章，文章增删后调用
+pub fn link_articles(articles: &mut [StoredArticle]) {
+    link_series(articles);
+    link_related(articles);
+}
+
+/// 文章在系列中的位置：(仓库, 系列名) 和排序用的编号
+fn series_key(article: &StoredArticle) -> Option<((String, String), u32)> {
+    let (fields, _) = split_front_matter(&article.content);
+    if let Some(name) = fields.get("series").filter(|name| !name.is_empty()) {
+        let order = ["series_order", "order", "part"]
+            .iter()
+            .find_map(|key| fields.get(*key)?.parse().ok())
+            .unwrap_or(u32::MAX);
+        return Some(((article.repo_name.clone(), name.clone()), order));
+    }
+
+    let (dir, filename) = article.file_path.rsplit_once('/').unwrap_or(("", &article.file_path));
+    let stem = filename.strip_suffix(".md").unwrap_or(filename);
+
+    // 开头编号：01-install.md，同一目录下的编号文件为一个系列，系列名取目录名
+    let digits = stem.chars().take_while(|c| c.is_ascii_digit()).count();
+    if (1..=3).contains(&digits) && stem[digits..].starts_with(['-', '_', '.', ' ']) && stem.len() > digits + 1 {
+        let name = dir.rsplit('/').next().filter(|d| !d.is_empty()).unwrap_or(&article.repo_name);
+        return Some(((article.repo_name.clone(), humanize(name)), stem[..digits].parse().ok()?));
+    }
+
+    // 末尾编号：setup-part2.md、setup-2.md、部署第2篇.md，去掉编号后相同的文件为一个系列
+    let trimmed = ["篇", "部", "章", "节"]
+        .iter()
+        .find_map(|suffix| stem.strip_suffix(suffix))
+        .and_then(|s| s.ends_with(|c: char| c.is_ascii_digit()).then_some(s))
+        .unwrap_or(stem);
+    let digits = trimmed.chars().rev().take_while(|c| c.is_ascii_digit()).count();
+    if !(1..=3).contains(&digits) {
+        return None;
+    }
+    let number = trimmed[trimmed.len() - digits..].parse().ok()?;
+    let base = &trimmed[..trimmed.len() - digits];
+    let base = if let Some(base) = base.strip_suffix('第') {
+        base
+    } else {
+        // 编号前需要有分隔符，避免把 utf8、ipv6 这类名称当成系列
+        let base = base.strip_suffix("part").or_else(|| base.strip_suffix("Part")).unwrap_or(base);
+        base.strip_suffix(['-', '_', ' '])?
+    };
+    let base = base.trim_end_matches(['-', '_', ' ']);
+    if base.is_empty() {
+        return None;
+    }
+    Some(((article.repo_name.clone(), humanize(base)), number))
+}
+
+fn humanize(name: &str) -> String {
+    name.replace(['-', '_'], " ").trim().to_string()
+}
+
+fn link_series(articles: &mut [StoredArticle]) {
+    let mut groups: BTreeMap<(String, String), Vec<(u32, usize)>> = BTreeMap::new();
+    for (i, article) in articles.iter_mut().enumerate() {
+        article.series = None;
+        if let Some((key, order)) = series_key(article) {
+            groups.entry(key).or_default().push((order, i));
+        }
+    }
+
+    for ((_, name), mut members) in groups {
+        if members.len() < 2 {
+            continue;
+        }
+        members.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| articles[a.1].file_path.cmp(&articles[b.1].file_path)));
+        let link = |i: usize| ArticleLink {
+            id: articles[i].id.clone(),
+            title: articles[i].title.clone(),
+        };
+        let links: Vec<ArticleLink> = members.iter().map(|&(_, i)| link(i)).collect();
+        for (position, &(_, i)) in members.iter().enumerate() {
+            articles[i].series = Some(SeriesInfo {
+                name: name.clone(),
+                index: position + 1,
+                total: members.len(),
+                prev: position.checked_sub(1).map(|p| links[p].clone()),
+                next: links.get(position + 1).cloned(),
+            });
+        }
+    }
+}
+
+/// 分词：英文按单词（小写，至少两个字符，去掉停用词和纯数字），中文按相邻两字；跳过代码块
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut previous_cjk: Option<char> = None;
+        for c in line.chars().chain([' ']) {
+            if c.is_ascii_alphanumeric() {
+                word.push(c.to_ascii_lowercase());
+                previous_cjk = None;
+                continue;
+            }
+            if word.len() >= 2 && !word.chars().all(|c| c.is_ascii_digit()) && !STOPWORDS.contains(&word.as_str()) {
+                tokens.push(std::mem::take(&mut word));
+            }
+            word.clear();
+
+            if is_cjk(c) {
+                if let Some(p) = previous_cjk {
+                    tokens.push(format!("{}{}", p, c));
+                }
+                previous_cjk = Some(c);
+            } else {
+                previous_cjk = None;
+            }
+        }
+    }
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F)
+}
+
+/// 归一化的 TF-IDF 向量
+fn tfidf_vectors(articles: &[StoredArticle]) -> Vec<HashMap<String, f32>> {
+    let counts: Vec<HashMap<String, f32>> = articles
+        .iter()
+        .map(|article| {
+            let (_, body) = split_front_matter(&article.content);
+            let mut counts = HashMap::new();
+            // 标题中的词权重更高
+            for token in tokenize(&article.title).into_iter().chain(tokenize(&article.title)).chain(tokenize(body)) {
+                *counts.entry(token).or_insert(0.0) += 1.0;
+            }
+            counts
+        })
+        .collect();
+
+    let mut document_frequency: HashMap<&str, f32> = HashMap::new();
+    for terms in &counts {
+        for term in terms.keys() {
+            *document_frequency.entry(term.as_str()).or_insert(0.0) += 1.0;
+        }
+    }
+
+    let n = articles.len() as f32;
+    counts
+        .iter()
+        .map(|terms| {
+            let total: f32 = terms.values().sum();
+            let mut vector: HashMap<String, f32> = terms
+                .iter()
+                .map(|(term, count)| {
+                    let idf = ((n + 1.0) / (document_frequency[term.as_str()] + 1.0)).ln() + 1.0;
+                    (term.clone(), count / total * idf)
+                })
+                .collect();
+            let norm = vector.values().map(|w| w * w).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                vector.values_mut().for_each(|w| *w /= norm);
+            }
+            vector
+        })
+        .collect()
+}
+
+fn cosine(a: &HashMap<String, f32>, b: &HashMap<String, f32>) -> f32 {
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    small.iter().filter_map(|(term, w)| large.get(term).map(|v| w * v)).sum()
+}
+
+fn jaccard(a: &[String], b: &[String]) -> f32 {
+    let a: HashSet<&String> = a.iter().collect();
+    let b: HashSet<&String> = b.iter().collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+fn link_related(articles: &mut [StoredArticle]) {
+    let vectors = tfidf_vectors(articles);
+    let related: Vec<Vec<RelatedArticle>> = (0..articles.len())
+        .map(|i| {
+            let article = &articles[i];
+            let series = article.series.as_ref().map(|s| &s.name);
+            let mut candidates: Vec<RelatedArticle> = articles
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| {
+                    // 同一系列的文章已有上一篇/下一篇导航，不再重复推荐
+                    j != i && !(series.is_some() && other.repo_name == article.repo_name && other.series.as_ref().map(|s| &s.name) == series)
+                })
+                .map(|(j, other)| {
+                    let category = if other.category == article.category { 1.0 } else { 0.0 };
+                    let score = CONTENT_WEIGHT * cosine(&vectors[i], &vectors[j])
+                        + TAG_WEIGHT * jaccard(&article.tags, &other.tags)
+                        + CATEGORY_WEIGHT * category;
+                    RelatedArticle {
+                        id: other.id.clone(),
+                        title: other.title.clone(),
+                        score: (score * 1000.0).round() / 1000.0,
+                    }
+                })
+                .filter(|candidate| candidate.score >= MIN_SCORE)
+                .collect();
+            candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+            candidates.truncate(MAX_RELATED);
+            candidates
+        })
+        .collect();
+
+    for (article, related) in articles.iter_mut().zip(related) {
+        article.related = related;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn article(path: &str, title: &str, content: &str) -> StoredArticle {
+        StoredArticle {
+            id: path.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            excerpt: String::new(),
+            file_path: path.to_string(),
+            file_url: String::new(),
+            repo_name: "demo-notes".to_string(),
+            repo_url: String::new(),
+            updated_at: Utc::now(),
+            file_size: content.len() as u32,
+            reading_time: 1,
+            category: "技术".to_string(),
+            tags: Vec::new(),
+            featured: false,
+            sha: String::new(),
+            series: None,
+            related: Vec::new(),
+        }
+    }
+
+    fn key(path: &str, content: &str) -> Option<(String, u32)> {
+        series_key(&article(path, "标题", content)).map(|((_, name), order)| (name, order))
+    }
+
+    #[test]
+    fn front_matter_fields_and_body() {
+        let (fields, body) = split_front_matter("---\ntitle: \"Fedora 服务器\"\nSeries: 搭建\npart: '2'\n---\n# 正文\n");
+        assert_eq!(fields["title"], "Fedora 服务器");
+        assert_eq!(fields["series"], "搭建");
+        assert_eq!(fields["part"], "2");
+        assert_eq!(body, "# 正文\n");
+
+        let (fields, body) = split_front_matter("---\r\ntitle: 标题\r\n---\r\n正文");
+        assert_eq!(fields["title"], "标题");
+        assert_eq!(body, "正文");
+    }
+
+    #[test]
+    fn front_matter_requires_both_markers() {
+        let content = "---\ntitle: 没有结束标记\n正文";
+        let (fields, body) = split_front_matter(content);
+        assert!(fields.is_empty());
+        assert_eq!(body, content);
+
+        let content = "# 标题\n---\ntitle: x\n---\n";
+        let (fields, body) = split_front_matter(content);
+        assert!(fields.is_empty());
+        assert_eq!(body, content);
+    }
+
+    #[test]
+    fn series_from_trailing_part_number() {
+        assert_eq!(key("fedora-server-part1.md", ""), Some(("fedora server".to_string(), 1)));
+        assert_eq!(key("fedora-server-part2.md", ""), Some(("fedora server".to_string(), 2)));
+        assert_eq!(key("docs/setup-Part3.md", ""), Some(("setup".to_string(), 3)));
+        assert_eq!(key("setup_2.md", ""), Some(("setup".to_string(), 2)));
+    }
+
+    #[test]
+    fn series_from_chinese_numbering() {
+        assert_eq!(key("部署第1篇.md", ""), Some(("部署".to_string(), 1)));
+        assert_eq!(key("部署第12篇.md", ""), Some(("部署".to_string(), 12)));
+        assert_eq!(key("Rust 入门 第3章.md", ""), Some(("Rust 入门".to_string(), 3)));
+    }
+
+    #[test]
+    fn series_from_leading_number_uses_directory() {
+        assert_eq!(key("docs/fedora-server/01-install.md", ""), Some(("fedora server".to_string(), 1)));
+        assert_eq!(key("02_deploy.md", ""), Some(("demo notes".to_string(), 2)));
+    }
+
+    #[test]
+    fn front_matter_series_takes_precedence() {
+        let content = "---\nseries: Fedora 服务器搭建\nseries_order: 2\n---\n正文";
+        assert_eq!(key("install-part5.md", content), Some(("Fedora 服务器搭建".to_string(), 2)));
+        let content = "---\nseries: 无编号\n---\n正文";
+        assert_eq!(key("notes.md", content), Some(("无编号".to_string(), u32::MAX)));
+    }
+
+    #[test]
+    fn names_ending_in_digits_are_not_series() {
+        for path in ["utf8.md", "ipv6.md", "rust2024.md", "part1.md", "2024.md", "第1篇.md"] {
+            assert_eq!(key(path, ""), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn series_links_neighbours_in_order() {
+        let mut articles = vec![
+            article("setup-part2.md", "安装（二）", ""),
+            article("rust-async.md", "Rust 异步", ""),
+            article("setup-part1.md", "安装（一）", ""),
+            article("setup-part3.md", "安装（三）", ""),
+        ];
+        link_articles(&mut articles);
+
+        assert!(articles[1].series.is_none());
+        let first = articles[2].series.as_ref().unwrap();
+        assert_eq!((first.index, first.total), (1, 3));
+        assert!(first.prev.is_none());
+        assert_eq!(first.next.as_ref().unwrap().id, "setup-part2.md");
+        let middle = articles[0].series.as_ref().unwrap();
+        assert_eq!(middle.index, 2);
+        assert_eq!(middle.prev.as_ref().unwrap().id, "setup-part1.md");
+        assert_eq!(middle.next.as_ref().unwrap().id, "setup-part3.md");
+        assert!(articles[3].series.as_ref().unwrap().next.is_none());
+    }
+
+    #[test]
+    fn related_articles_ranked_by_similarity() {
+        let async_text = "tokio async await runtime future spawn select 异步运行时 任务调度";
+        let mut articles = vec![
+            article("rust-async.md", "Rust 异步入门", async_text),
+            article("tokio-select.md", "tokio select 用法", "tokio select future async await 异步运行时 取消"),
+            article("tokio-runtime.md", "tokio 运行时", "tokio runtime spawn 任务调度 线程池"),
+            article("firewalld.md", "防火墙配置", "firewalld zone port service 防火墙规则"),
+        ];
+        articles[3].category = "运维".to_string();
+        link_articles(&mut articles);
+
+        for article in &articles {
+            assert!(article.related.iter().all(|r| r.id != article.id), "{} 推荐了自己", article.id);
+            assert!(article.related.windows(2).all(|w| w[0].score >= w[1].score));
+            assert!(article.related.iter().all(|r| r.score >= MIN_SCORE));
+        }
+        let related: Vec<&str> = articles[0].related.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(related[..2], ["tokio-select.md", "tokio-runtime.md"]);
+        assert!(!related.contains(&"firewalld.md"));
+    }
+
+    #[test]
+    fn series_members_are_not_related() {
+        let text = "fedora server dnf firewalld nginx 部署 服务器";
+        let mut articles = vec![
+            article("fedora-part1.md", "Fedora 服务器（一）", text),
+            article("fedora-part2.md", "Fedora 服务器（二）", text),
+            article("fedora-tips.md", "Fedora 服务器技巧", text),
+        ];
+        link_articles(&mut articles);
+
+        let related: Vec<&str> = articles[0].related.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(related, ["fedora-tips.md"]);
+        let related: Vec<&str> = articles[2].related.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(related, ["fedora-part1.md", "fedora-part2.md"]);
+    }
+}
@@ -0,0 +1,234 @@
+// 刷新报告：记录每次全量或增量刷新的耗时、API 用量、各仓库结果和文章变化
+//
+// 最近的报告保存在数据目录下的历史文件中（条数见 GitHubConfig::get_refresh_history_limit），
+// 通过 /api/update/history 查看，不必翻服务器日志就能看到"跳过仓库"之类的问题。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::storage::{write_atomic, ArticleChanges};
+
+/// 刷新的触发方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefreshTrigger {
+    /// 数据过期后访问时自动刷新
+    Scheduled,
+    /// 通过 /api/update 手动触发
+    Manual,
+    /// GitHub Webhook 触发的单仓库增量刷新
+    Webhook,
+}
+
+impl RefreshTrigger {
+    /// 与序列化结果相同的名称，用作指标标签
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RefreshTrigger::Scheduled => "scheduled",
+            RefreshTrigger::Manual => "manual",
+            RefreshTrigger::Webhook => "webhook",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoStatus {
+    Success,
+    /// 因 API 预算用完等原因未处理，保留上次的数据
+    Skipped,
+    Error,
+}
+
+/// 单个仓库在本次刷新中的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoReport {
+    pub name: String,
+    pub status: RepoStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+    pub articles: usize,
+}
+
+impl RepoReport {
+    /// 记录一条失败原因，状态只会从成功降级为跳过或失败
+    pub fn fail(&mut self, status: RepoStatus, reason: String) {
+        if self.status == RepoStatus::Success || status == RepoStatus::Error {
+            self.status = status;
+        }
+        self.reasons.push(reason);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshReport {
+    pub trigger: RefreshTrigger,
+    /// 使用的数据后端（rest / graphql）
+    #[serde(default)]
+    pub backend: String,
+    /// 增量刷新的目标仓库，全量刷新时为空
+    pub target: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: i64,
+    pub success: bool,
+    pub error: Option<String>,
+    pub api_calls: u32,
+    /// 本次刷新结束时 GitHub 返回的剩余速率限制
+    pub rate_limit_remaining: Option<u32>,
+    pub projects: usize,
+    pub articles: usize,
+    #[serde(default)]
+    pub notes: usize,
+    pub repos: Vec<RepoReport>,
+    pub article_changes: Option<ArticleChanges>,
+}
+
+impl RefreshReport {
+    pub fn new(trigger: RefreshTrigger, target: Option<&str>, backend: &str) -> Self {
+        Self {
+            trigger,
+            backend: backend.to_string(),
+            target: target.map(str::to_string),
+            started_at: Utc::now(),
+            finished_at: None,
+            duration_ms: 0,
+            success: false,
+            error: None,
+            api_calls: 0,
+            rate_limit_remaining: None,
+            projects: 0,
+            articles: 0,
+            notes: 0,
+            repos: Vec::new(),
+            article_changes: None,
+        }
+    }
+
+    /// 获取仓库对应的结果，不存在时以成功状态创建
+    pub fn repo_mut(&mut self, name: &str) -> &mut RepoReport {
+        let index = match self.repos.iter().position(|r| r.name == name) {
+            Some(index) => index,
+            None => {
+                self.repos.push(RepoReport {
+                    name: name.to_string(),
+                    status: RepoStatus::Success,
+                    reasons: Vec::new(),
+                    articles: 0,
+                });
+                self.repos.len() - 1
+            }
+        };
+        &mut self.repos[index]
+    }
+
+    fn count(&self, status: RepoStatus) -> usize {
+        self.repos.iter().filter(|r| r.status == status).count()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "耗时 {:.1} 秒，API 调用 {} 次；仓库成功 {} 个，跳过 {} 个，失败 {} 个",
+            self.duration_ms as f64 / 1000.0,
+            self.api_calls,
+            self.count(RepoStatus::Success),
+            self.count(RepoStatus::Skipped),
+            self.count(RepoStatus::Error),
+        );
+        if let Some(changes) = &self.article_changes {
+            summary.push_str(&format!("；文章{}", changes.summary()));
+        }
+        summary
+    }
+}
+
+/// 读取刷新历史（最新在前），文件不存在或损坏时返回空列表
+pub fn load_history(path: &str) -> Vec<RefreshReport> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 追加一条报告并只保留最近 limit 条
+pub fn append_history(path: &str, report: &RefreshReport, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let mut history = load_history(path);
+    history.insert(0, report.clone());
+    history.truncate(limit);
+
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(Path::new(path), serde_json::to_string_pretty(&history)?.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn history_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("xjz-history-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("nested").join("refresh_history.json")
+    }
+
+    fn report(api_calls: u32) -> RefreshReport {
+        let mut report = RefreshReport::new(RefreshTrigger::Manual, Some("demo-notes"), "rest");
+        report.api_calls = api_calls;
+        report.success = true;
+        report.repo_mut("demo-notes").fail(RepoStatus::Skipped, "API 预算已用完".to_string());
+        report
+    }
+
+    #[test]
+    fn history_round_trips_newest_first() {
+        let path = history_file("round-trip");
+        let path_str = path.to_str().unwrap();
+        assert!(load_history(path_str).is_empty());
+
+        append_history(path_str, &report(1), 20).unwrap();
+        append_history(path_str, &report(2), 20).unwrap();
+        let history = load_history(path_str);
+        assert_eq!(history.iter().map(|r| r.api_calls).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(history[0].trigger, RefreshTrigger::Manual);
+        assert_eq!(history[0].target.as_deref(), Some("demo-notes"));
+        assert_eq!(history[0].repos[0].status, RepoStatus::Skipped);
+        assert_eq!(history[0].repos[0].reasons, ["API 预算已用完"]);
+        let _ = fs::remove_dir_all(path.parent().unwrap().parent().unwrap());
+    }
+
+    #[test]
+    fn history_is_trimmed_to_limit() {
+        let path = history_file("trim");
+        let path_str = path.to_str().unwrap();
+        for api_calls in 1..=5 {
+            append_history(path_str, &report(api_calls), 3).unwrap();
+        }
+        let history = load_history(path_str);
+        assert_eq!(history.iter().map(|r| r.api_calls).collect::<Vec<_>>(), [5, 4, 3]);
+
+        // 损坏的历史文件按空列表处理，追加后恢复正常
+        fs::write(&path, "not json").unwrap();
+        assert!(load_history(path_str).is_empty());
+        append_history(path_str, &report(6), 3).unwrap();
+        assert_eq!(load_history(path_str).len(), 1);
+        let _ = fs::remove_dir_all(path.parent().unwrap().parent().unwrap());
+    }
+
+    #[test]
+    fn repo_status_only_degrades() {
+        let mut report = RefreshReport::new(RefreshTrigger::Scheduled, None, "rest");
+        let repo = report.repo_mut("a");
+        repo.fail(RepoStatus::Error, "x".to_string());
+        repo.fail(RepoStatus::Skipped, "y".to_string());
+        assert_eq!(repo.status, RepoStatus::Error);
+        assert_eq!(repo.reasons.len(), 2);
+        report.repo_mut("b");
+        assert_eq!(report.repos.len(), 2);
+        assert!(report.summary().contains("仓库成功 1 个，跳过 0 个，失败 1 个"));
+    }
+}
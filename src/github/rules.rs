@@ -0,0 +1,321 @@
+// 项目展示规则：过滤、置顶、排序与单项目覆盖
+//
+// 规则从 JSON 配置文件加载（见 GitHubConfig::load_project_rules），
+// 文件不存在时使用默认规则：排除 fork 和 archived 仓库。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use super::models::Repository;
+use super::storage::StoredProject;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectOverride {
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub cover_image: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectRules {
+    /// 仓库名白名单（支持 * 和 ? 通配），为空表示不限制
+    pub include_names: Vec<String>,
+    /// 仓库名黑名单（支持 * 和 ? 通配）
+    pub exclude_names: Vec<String>,
+    /// 至少包含其中一个 topic，为空表示不限制
+    pub include_topics: Vec<String>,
+    /// 包含任一 topic 即排除
+    pub exclude_topics: Vec<String>,
+    /// 主语言白名单，为空表示不限制
+    pub include_languages: Vec<String>,
+    /// 主语言黑名单
+    pub exclude_languages: Vec<String>,
+    /// 最少 star 数
+    pub min_stars: u32,
+    /// 最近一次 push 距今的最大天数
+    pub max_push_age_days: Option<i64>,
+    pub include_forks: bool,
+    pub include_archived: bool,
+    /// 手动置顶的仓库，按列表顺序排在最前，且不受过滤条件影响
+    pub pinned: Vec<String>,
+    /// 仓库名 -> 展示覆盖
+    pub overrides: HashMap<String, ProjectOverride>,
+}
+
+impl ProjectRules {
+    /// 判断仓库是否为置顶项目
+    pub fn is_pinned(&self, name: &str) -> bool {
+        self.pin_index(name).is_some()
+    }
+
+    fn pin_index(&self, name: &str) -> Option<usize> {
+        self.pinned.iter().position(|p| p.eq_ignore_ascii_case(name))
+    }
+
+    /// 仓库的展示覆盖，与置顶一样按仓库名不区分大小写查找
+    fn override_for(&self, name: &str) -> Option<&ProjectOverride> {
+        self.overrides.get(name).or_else(|| {
+            self.overrides
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, o)| o)
+        })
+    }
+
+    /// 判断仓库是否应该展示
+    pub fn matches(&self, repo: &Repository, now: DateTime<Utc>) -> bool {
+        if self.is_pinned(&repo.name) {
+            return true;
+        }
+        if repo.fork && !self.include_forks {
+            return false;
+        }
+        if repo.archived && !self.include_archived {
+            return false;
+        }
+        if !self.include_names.is_empty()
+            && !self.include_names.iter().any(|p| glob_match(p, &repo.name))
+        {
+            return false;
+        }
+        if self.exclude_names.iter().any(|p| glob_match(p, &repo.name)) {
+            return false;
+        }
+
+        let has_topic = |wanted: &String| repo.topics.iter().any(|t| t.eq_ignore_ascii_case(wanted));
+        if !self.include_topics.is_empty() && !self.include_topics.iter().any(has_topic) {
+            return false;
+        }
+        if self.exclude_topics.iter().any(has_topic) {
+            return false;
+        }
+
+        let language = repo.language.as_deref().unwrap_or("");
+        if !self.include_languages.is_empty()
+            && !self.include_languages.iter().any(|l| l.eq_ignore_ascii_case(language))
+        {
+            return false;
+        }
+        if self.exclude_languages.iter().any(|l| l.eq_ignore_ascii_case(language)) {
+            return false;
+        }
+
+        if (repo.stargazers_count.max(0) as u32) < self.min_stars {
+            return false;
+        }
+        if let Some(max_days) = self.max_push_age_days {
+            let pushed_at = repo.pushed_at.unwrap_or(repo.updated_at);
+            if now - pushed_at > chrono::Duration::days(max_days) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// 应用置顶标记和展示覆盖
+    pub fn apply_overrides(&self, project: &mut StoredProject) {
+        project.pinned = self.is_pinned(&project.name);
+        if let Some(o) = self.override_for(&project.name) {
+            if o.display_name.is_some() {
+                project.display_name = o.display_name.clone();
+            }
+            if o.description.is_some() {
+                project.description = o.description.clone();
+            }
+            if o.cover_image.is_some() {
+                project.cover_image = o.cover_image.clone();
+            }
+        }
+    }
+
+    /// 排序：置顶项目按配置顺序在前，其余按更新时间倒序
+    pub fn sort(&self, projects: &mut [StoredProject]) {
+        projects.sort_by(|a, b| {
+            match (self.pin_index(&a.name), self.pin_index(&b.name)) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => b.updated_at.cmp(&a.updated_at),
+            }
+        });
+    }
+}
+
+/// 简单的通配符匹配（不区分大小写），支持 `*` 和 `?`
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    while pi < p.len() && p[pi] == '*' {
+        pi += 1;
+    }
+    pi == p.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn repo(name: &str, extra: serde_json::Value) -> Repository {
+        let mut value = json!({
+            "id": 1,
+            "name": name,
+            "full_name": format!("xjz6626/{}", name),
+            "description": null,
+            "html_url": format!("https://github.com/xjz6626/{}", name),
+            "clone_url": format!("https://github.com/xjz6626/{}.git", name),
+            "language": "Rust",
+            "stargazers_count": 3,
+            "watchers_count": 3,
+            "forks_count": 0,
+            "open_issues_count": 0,
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2026-10-01T00:00:00Z",
+            "pushed_at": "2026-10-01T00:00:00Z",
+            "homepage": null,
+            "topics": ["rust", "axum"],
+            "visibility": "public",
+            "default_branch": "main",
+            "license": null,
+            "size": 100,
+            "archived": false,
+            "disabled": false,
+            "fork": false,
+        });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2026-10-18T00:00:00Z".parse().unwrap()
+    }
+
+    fn parse_rules(value: serde_json::Value) -> ProjectRules {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn glob_star_and_question_mark() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("demo-*", "demo-notes"));
+        assert!(glob_match("*-notes", "demo-notes"));
+        assert!(glob_match("d*o*s", "demo-notes"));
+        assert!(glob_match("demo-note?", "demo-notes"));
+        assert!(!glob_match("demo-note?", "demo-note"));
+        assert!(glob_match("DEMO-*", "demo-notes"));
+    }
+
+    #[test]
+    fn glob_is_anchored() {
+        assert!(!glob_match("demo", "demo-notes"));
+        assert!(!glob_match("notes", "demo-notes"));
+        assert!(!glob_match("demo-*", "my-demo-notes"));
+        assert!(!glob_match("*-demo", "demo-notes"));
+        assert!(glob_match("demo-notes", "demo-notes"));
+    }
+
+    #[test]
+    fn default_rules_exclude_forks_and_archived() {
+        let rules = ProjectRules::default();
+        assert!(rules.matches(&repo("site", json!({})), now()));
+        assert!(!rules.matches(&repo("fork", json!({ "fork": true })), now()));
+        assert!(!rules.matches(&repo("old", json!({ "archived": true })), now()));
+
+        let rules = parse_rules(json!({ "include_forks": true, "include_archived": true }));
+        assert!(rules.matches(&repo("fork", json!({ "fork": true })), now()));
+        assert!(rules.matches(&repo("old", json!({ "archived": true })), now()));
+    }
+
+    #[test]
+    fn name_topic_and_language_filters() {
+        let rules = parse_rules(json!({
+            "include_names": ["xjz-*", "demo-?otes"],
+            "exclude_names": ["*-test"],
+            "exclude_topics": ["ARCHIVE"],
+            "include_languages": ["rust", "TypeScript"],
+        }));
+        assert!(rules.matches(&repo("xjz-website", json!({})), now()));
+        assert!(rules.matches(&repo("demo-notes", json!({})), now()));
+        assert!(!rules.matches(&repo("other", json!({})), now()));
+        assert!(!rules.matches(&repo("xjz-test", json!({})), now()));
+        assert!(!rules.matches(&repo("xjz-old", json!({ "topics": ["archive"] })), now()));
+        assert!(!rules.matches(&repo("xjz-py", json!({ "language": "Python" })), now()));
+        assert!(!rules.matches(&repo("xjz-none", json!({ "language": null })), now()));
+
+        let rules = parse_rules(json!({ "include_topics": ["axum", "web"] }));
+        assert!(rules.matches(&repo("site", json!({})), now()));
+        assert!(!rules.matches(&repo("cli", json!({ "topics": ["cli"] })), now()));
+    }
+
+    #[test]
+    fn stars_and_push_age() {
+        let rules = parse_rules(json!({ "min_stars": 5, "max_push_age_days": 30 }));
+        assert!(!rules.matches(&repo("site", json!({})), now()));
+        assert!(rules.matches(&repo("site", json!({ "stargazers_count": 5 })), now()));
+        let stale = json!({ "stargazers_count": 10, "pushed_at": "2026-08-01T00:00:00Z" });
+        assert!(!rules.matches(&repo("site", stale), now()));
+        // 没有 push 时间时按更新时间计算
+        let never_pushed = json!({ "stargazers_count": 10, "pushed_at": null, "updated_at": "2026-10-10T00:00:00Z" });
+        assert!(rules.matches(&repo("site", never_pushed), now()));
+    }
+
+    #[test]
+    fn pinned_repos_bypass_filters() {
+        let rules = parse_rules(json!({ "pinned": ["Old-Fork"], "min_stars": 100 }));
+        assert!(rules.matches(&repo("old-fork", json!({ "fork": true, "archived": true })), now()));
+        assert!(!rules.matches(&repo("site", json!({})), now()));
+    }
+
+    #[test]
+    fn overrides_ignore_case_like_pins() {
+        let rules = parse_rules(json!({
+            "pinned": ["XJZ-Website"],
+            "overrides": { "XJZ-Website": { "display_name": "XJZ 个人网站" } },
+        }));
+        let mut project: StoredProject = serde_json::from_value(json!({
+            "id": 1,
+            "name": "xjz-website",
+            "full_name": "xjz6626/xjz-website",
+            "description": "原描述",
+            "html_url": "https://github.com/xjz6626/xjz-website",
+            "language": "Rust",
+            "stargazers_count": 3,
+            "forks_count": 0,
+            "updated_at": "2026-10-01T00:00:00Z",
+            "created_at": "2025-01-01T00:00:00Z",
+            "topics": [],
+            "open_issues_count": 0,
+            "size": 100,
+            "default_branch": "main",
+            "archived": false,
+            "fork": false,
+        }))
+        .unwrap();
+        rules.apply_overrides(&mut project);
+        assert!(project.pinned);
+        assert_eq!(project.display_name.as_deref(), Some("XJZ 个人网站"));
+        assert_eq!(project.description.as_deref(), Some("原描述"));
+    }
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
 use chrono::{DateTime, Utc};
+use super::models::{ContributionCalendar, Language, License};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredProject {
@@ -20,6 +24,83 @@ pub struct StoredProject {
     pub default_branch: String,
     pub archived: bool,
     pub fork: bool,
+    #[serde(default)]
+    pub pushed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub pinned: bool,          // 是否在配置中置顶
+    #[serde(default)]
+    pub display_name: Option<String>, // 配置覆盖的展示名称
+    #[serde(default)]
+    pub cover_image: Option<String>,  // 配置覆盖的封面图
+    #[serde(default)]
+    pub license: Option<License>,
+    #[serde(default)]
+    pub latest_version: Option<String>, // 最新正式 Release 的 tag
+}
+
+/// 项目详情页使用的附加数据，在刷新时一并获取
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectDetails {
+    pub readme: Option<String>,        // README 原始 Markdown
+    pub languages: Vec<Language>,      // 按字节数倒序
+    pub recent_commits: Vec<StoredCommit>,
+    pub latest_release: Option<StoredRelease>,
+    #[serde(default)]
+    pub releases: Vec<StoredRelease>,  // 按发布时间倒序，不含草稿
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredCommit {
+    pub sha: String,
+    pub message: String,  // 仅保留提交信息首行
+    pub author: String,
+    pub date: DateTime<Utc>,
+    pub html_url: String,
+}
+
+impl StoredCommit {
+    pub fn short_sha(&self) -> &str {
+        self.sha.get(..7).unwrap_or(&self.sha)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredRelease {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub html_url: String,
+    pub published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub body: Option<String>,           // 更新日志 Markdown
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub assets: Vec<StoredReleaseAsset>,
+}
+
+impl StoredRelease {
+    /// 所有附件的下载次数之和
+    pub fn total_downloads(&self) -> u64 {
+        self.assets.iter().map(|a| a.download_count).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredReleaseAsset {
+    pub name: String,
+    pub size: u64,
+    pub download_count: u64,
+    pub download_url: String,
+}
+
+/// 跨项目的最近发布动态
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseFeedItem {
+    pub repo_name: String,
+    pub repo_url: String,
+    #[serde(flatten)]
+    pub release: StoredRelease,
+    pub total_downloads: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +119,108 @@ pub struct StoredArticle {
     pub category: String,
     pub tags: Vec<String>,
     pub featured: bool,    // 是否为精选文章
+    #[serde(default)]
+    pub sha: String,       // 源文件 blob SHA，未变化时跳过重新下载
+    #[serde(default)]
+    pub series: Option<SeriesInfo>,   // 所属系列及上一篇/下一篇，刷新时计算
+    #[serde(default)]
+    pub related: Vec<RelatedArticle>, // 相关文章，刷新时计算
+}
+
+/// 文章所属的系列，由 front matter 或文件名编号识别
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesInfo {
+    pub name: String,
+    pub index: usize, // 从 1 开始
+    pub total: usize,
+    pub prev: Option<ArticleLink>,
+    pub next: Option<ArticleLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArticleLink {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedArticle {
+    pub id: String,
+    pub title: String,
+    pub score: f32, // 标签、分类和正文相似度的加权得分
+}
+
+/// 由 Markdown Gist 转换的笔记
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredNote {
+    pub id: String,          // Gist ID
+    pub title: String,
+    pub description: String, // Gist 描述中去掉 #标签 后的文字
+    pub content: String,     // 所有 Markdown 文件按文件名顺序拼接
+    pub excerpt: String,
+    pub files: Vec<String>,
+    pub html_url: String,
+    pub tags: Vec<String>,   // Gist 描述中的 #标签
+    pub reading_time: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>, // 未变化时跳过重新下载
+}
+
+/// 一次刷新中文章的变化情况（文章 ID 列表）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArticleChanges {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl ArticleChanges {
+    /// 对比刷新前后的文章列表
+    pub fn between(previous: &[StoredArticle], current: &[StoredArticle]) -> Self {
+        let before: HashMap<&str, &StoredArticle> = previous.iter().map(|a| (a.id.as_str(), a)).collect();
+        let mut changes = Self::default();
+
+        for article in current {
+            match before.get(article.id.as_str()) {
+                None => changes.added.push(article.id.clone()),
+                Some(old) if !old.sha.is_empty() && old.sha == article.sha => changes.unchanged += 1,
+                Some(_) => changes.changed.push(article.id.clone()),
+            }
+        }
+
+        let after: std::collections::HashSet<&str> = current.iter().map(|a| a.id.as_str()).collect();
+        changes.removed = previous
+            .iter()
+            .filter(|a| !after.contains(a.id.as_str()))
+            .map(|a| a.id.clone())
+            .collect();
+
+        changes
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "新增 {} 篇，更新 {} 篇，删除 {} 篇，未变化 {} 篇",
+            self.added.len(), self.changed.len(), self.removed.len(), self.unchanged
+        )
+    }
+}
+
+/// 首页动态：贡献日历和最近的公开活动
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Activity {
+    pub contributions: Option<ContributionCalendar>,
+    pub events: Vec<ActivityItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityItem {
+    pub kind: String,      // push / pull_request / issue / release
+    pub repo_name: String, // owner/repo
+    pub title: String,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,8 +253,43 @@ pub struct GitHubData {
     pub articles: Vec<StoredArticle>,
     pub user_stats: Option<UserStats>,
     pub language_stats: Option<LanguageStats>,
+    #[serde(default)]
+    pub project_details: HashMap<String, ProjectDetails>, // 仓库名 -> 详情
+    #[serde(default)]
+    pub article_changes: Option<ArticleChanges>, // 最近一次刷新的文章变化
+    #[serde(default)]
+    pub activity: Activity,
+    #[serde(default)]
+    pub notes: Vec<StoredNote>,
     pub last_updated: DateTime<Utc>,
     pub next_update: DateTime<Utc>,
+    #[serde(skip)]
+    pub degraded: bool, // 刷新失败或离线时返回的过期缓存，不写入文件
+    #[serde(skip)]
+    pub retry_at: Option<DateTime<Utc>>, // 降级状态下下一次允许重试刷新的时间
+}
+
+impl GitHubData {
+    /// 汇总所有项目的 Release，按发布时间倒序取前 limit 条
+    pub fn recent_releases(&self, limit: usize) -> Vec<ReleaseFeedItem> {
+        let mut feed: Vec<ReleaseFeedItem> = self
+            .projects
+            .iter()
+            .filter_map(|p| self.project_details.get(&p.name).map(|d| (p, d)))
+            .flat_map(|(project, details)| {
+                details.releases.iter().map(move |release| ReleaseFeedItem {
+                    repo_name: project.name.clone(),
+                    repo_url: project.html_url.clone(),
+                    release: release.clone(),
+                    total_downloads: release.total_downloads(),
+                })
+            })
+            .collect();
+
+        feed.sort_by_key(|item| std::cmp::Reverse(item.release.published_at));
+        feed.truncate(limit);
+        feed
+    }
 }
 
 impl Default for GitHubData {
@@ -82,8 +300,89 @@ impl Default for GitHubData {
             articles: Vec::new(),
             user_stats: None,
             language_stats: None,
+            project_details: HashMap::new(),
+            article_changes: None,
+            activity: Activity::default(),
+            notes: Vec::new(),
             last_updated: now,
             next_update: now + chrono::Duration::days(3),
+            degraded: false,
+            retry_at: None,
+        }
+    }
+}
+
+/// 先写入同目录下的临时文件再改名，进程中途退出时原文件保持完整
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn project(name: &str) -> StoredProject {
+        serde_json::from_value(json!({
+            "id": 1, "name": name, "full_name": format!("xjz6626/{}", name), "description": null,
+            "html_url": format!("https://github.com/xjz6626/{}", name), "language": "Rust",
+            "stargazers_count": 0, "forks_count": 0, "updated_at": "2026-01-01T00:00:00Z",
+            "created_at": "2025-01-01T00:00:00Z", "topics": [], "open_issues_count": 0, "size": 0,
+            "default_branch": "main", "archived": false, "fork": false,
+        }))
+        .unwrap()
+    }
+
+    fn release(tag: &str, day: u32, downloads: &[u64]) -> StoredRelease {
+        StoredRelease {
+            tag_name: tag.to_string(),
+            name: None,
+            html_url: format!("https://github.com/releases/{}", tag),
+            published_at: Some(format!("2026-03-{:02}T00:00:00Z", day).parse().unwrap()),
+            body: None,
+            prerelease: false,
+            assets: downloads
+                .iter()
+                .enumerate()
+                .map(|(i, &download_count)| StoredReleaseAsset {
+                    name: format!("asset-{}", i),
+                    size: 1,
+                    download_count,
+                    download_url: String::new(),
+                })
+                .collect(),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn recent_releases_merge_projects_newest_first() {
+        let mut data = GitHubData {
+            projects: vec![project("a"), project("b")],
+            ..GitHubData::default()
+        };
+        let details = |releases| ProjectDetails {
+            releases,
+            ..ProjectDetails::default()
+        };
+        data.project_details.insert("a".into(), details(vec![release("a-2", 20, &[3, 4]), release("a-1", 1, &[])]));
+        data.project_details.insert("b".into(), details(vec![release("b-1", 10, &[7])]));
+        // 不在项目列表中的仓库（如已被规则排除）不出现在动态中
+        data.project_details.insert("gone".into(), details(vec![release("gone-1", 30, &[])]));
+
+        let feed = data.recent_releases(10);
+        let tags: Vec<&str> = feed.iter().map(|item| item.release.tag_name.as_str()).collect();
+        assert_eq!(tags, ["a-2", "b-1", "a-1"]);
+        assert_eq!(feed[0].total_downloads, 7);
+        assert_eq!(feed[1].repo_name, "b");
+        assert_eq!(feed[1].repo_url, "https://github.com/xjz6626/b");
+
+        assert_eq!(data.recent_releases(2).len(), 2);
+        // 展开后与 release 字段同级
+        let item = serde_json::to_value(&feed[0]).unwrap();
+        assert_eq!(item["tag_name"], "a-2");
+        assert_eq!(item["total_downloads"], 7);
+    }
+}
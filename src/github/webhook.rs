@@ -0,0 +1,225 @@
+// GitHub Webhook 接收端
+//
+// 校验 X-Hub-Signature-256 后按事件类型对受影响的仓库做增量刷新，
+// 刷新在后台任务中执行，请求立即返回 202。
+
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use tracing::Instrument;
+
+use crate::api::{ApiError, ApiResponse, ErrorCode};
+use crate::lifecycle;
+use super::config::GitHubConfig;
+use super::manager::{GitHubDataManager, RepoRefresh};
+
+/// GitHub 单次投递的最大负载（25 MB），路由上的请求体上限需要放宽到这个值
+pub const MAX_PAYLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+struct WebhookPayload {
+    action: Option<String>,
+    repository: Option<WebhookRepository>,
+    changes: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookRepository {
+    name: String,
+    owner: WebhookOwner,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookOwner {
+    login: String,
+}
+
+/// Webhook 事件对应的数据操作
+#[derive(Debug, PartialEq, Eq)]
+enum WebhookAction {
+    Refresh(RepoRefresh),
+    Remove,
+}
+
+/// 校验 `sha256=<hex>` 格式的签名（常量时间比较）
+pub(crate) fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(hex_sig) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(hex_sig) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// 事件 + action -> 需要执行的操作，None 表示忽略
+fn action_for_event(event: &str, action: Option<&str>) -> Option<WebhookAction> {
+    match (event, action) {
+        ("push", _) => Some(WebhookAction::Refresh(RepoRefresh::Full)),
+        ("release", _) => Some(WebhookAction::Refresh(RepoRefresh::Details)),
+        ("star", _) => Some(WebhookAction::Refresh(RepoRefresh::Metadata)),
+        ("repository", Some("deleted" | "privatized")) => Some(WebhookAction::Remove),
+        ("repository", _) => Some(WebhookAction::Refresh(RepoRefresh::Full)),
+        _ => None,
+    }
+}
+
+pub async fn handle_github_webhook(headers: HeaderMap, body: Bytes) -> Response {
+    let Some(secret) = GitHubConfig::get_webhook_secret() else {
+        tracing::warn!("收到 GitHub Webhook，但未配置 GITHUB_WEBHOOK_SECRET，已拒绝");
+        return ApiError::new(ErrorCode::NotConfigured, "Webhook 未启用").into_response();
+    };
+
+    let signature = headers
+        .get("X-Hub-Signature-256")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !verify_signature(&secret, &body, signature) {
+        tracing::warn!("GitHub Webhook 签名校验失败");
+        return ApiError::new(ErrorCode::Unauthorized, "签名无效").into_response();
+    }
+
+    let event = headers
+        .get("X-GitHub-Event")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    let delivery = headers
+        .get("X-GitHub-Delivery")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-")
+        .to_string();
+
+    if event == "ping" {
+        return ApiResponse::message("pong").into_response();
+    }
+
+    if GitHubConfig::is_offline() {
+        return ApiError::new(ErrorCode::Offline, "离线模式下不处理 Webhook").into_response();
+    }
+
+    let payload: WebhookPayload = match serde_json::from_slice(&body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::warn!("GitHub Webhook 负载解析失败: {}", e);
+            return ApiError::bad_request("无效的 JSON 负载").into_response();
+        }
+    };
+
+    let Some(repo) = payload.repository else {
+        return ApiResponse::message("事件不涉及仓库，已忽略").into_response();
+    };
+    if !repo.owner.login.eq_ignore_ascii_case(&GitHubConfig::get_username()) {
+        return ApiResponse::message("非本用户仓库，已忽略").into_response();
+    }
+
+    let Some(action) = action_for_event(&event, payload.action.as_deref()) else {
+        return ApiResponse::message(format!("事件 {} 已忽略", event)).into_response();
+    };
+
+    // 仓库改名时先移除旧名称对应的数据
+    let renamed_from = payload
+        .changes
+        .as_ref()
+        .and_then(|c| c.pointer("/repository/name/from"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+
+    tracing::info!(event = %event, delivery = %delivery, repo = %repo.name, "收到 GitHub Webhook，开始增量刷新");
+
+    let repo_name = repo.name.clone();
+    // 后台刷新的日志挂在这次 Webhook 请求下
+    let span = tracing::info_span!("webhook_job", delivery = %delivery, repo = %repo_name);
+    lifecycle::spawn_background(async move {
+        let manager = match GitHubDataManager::new(GitHubConfig::get_username()) {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::error!("GitHubDataManager 初始化失败: {}", e);
+                return;
+            }
+        };
+
+        if let Some(old_name) = renamed_from
+            && let Err(e) = manager.remove_repo(&old_name).await
+        {
+            tracing::error!("移除改名前的仓库 {} 失败: {}", old_name, e);
+        }
+
+        let result = match action {
+            WebhookAction::Refresh(scope) => manager.refresh_repo(&repo_name, scope).await.map(|_| ()),
+            WebhookAction::Remove => manager.remove_repo(&repo_name).await.map(|_| ()),
+        };
+        match result {
+            Ok(()) => tracing::info!(delivery = %delivery, repo = %repo_name, "Webhook 增量刷新完成"),
+            Err(e) => tracing::error!(delivery = %delivery, repo = %repo_name, "Webhook 增量刷新失败: {}", e),
+        }
+    }
+    .instrument(span));
+
+    (
+        StatusCode::ACCEPTED,
+        ApiResponse::message(format!("已接受 {} 事件，正在刷新 {}", event, repo.name)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+    // GitHub 文档中的示例签名
+    const SIGNATURE: &str = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    #[test]
+    fn accepts_valid_signature() {
+        assert!(verify_signature(SECRET, BODY, SIGNATURE));
+        // 十六进制部分不区分大小写
+        let upper = format!("sha256={}", SIGNATURE.trim_start_matches("sha256=").to_uppercase());
+        assert!(verify_signature(SECRET, BODY, &upper));
+    }
+
+    #[test]
+    fn rejects_bad_signature() {
+        assert!(!verify_signature("wrong secret", BODY, SIGNATURE));
+        assert!(!verify_signature(SECRET, b"Hello, World?", SIGNATURE));
+        let truncated = &SIGNATURE[..SIGNATURE.len() - 2];
+        assert!(!verify_signature(SECRET, BODY, truncated));
+        assert!(!verify_signature(SECRET, BODY, "sha256=not-hex"));
+    }
+
+    #[test]
+    fn rejects_missing_or_wrong_prefix() {
+        let hex_only = SIGNATURE.trim_start_matches("sha256=");
+        assert!(!verify_signature(SECRET, BODY, ""));
+        assert!(!verify_signature(SECRET, BODY, "sha256="));
+        assert!(!verify_signature(SECRET, BODY, hex_only));
+        assert!(!verify_signature(SECRET, BODY, &format!("sha1={}", hex_only)));
+        assert!(!verify_signature(SECRET, BODY, &format!("SHA256={}", hex_only)));
+    }
+
+    #[test]
+    fn maps_events_to_actions() {
+        use WebhookAction::*;
+        assert_eq!(action_for_event("push", None), Some(Refresh(RepoRefresh::Full)));
+        assert_eq!(action_for_event("release", Some("published")), Some(Refresh(RepoRefresh::Details)));
+        assert_eq!(action_for_event("star", Some("created")), Some(Refresh(RepoRefresh::Metadata)));
+        assert_eq!(action_for_event("repository", Some("deleted")), Some(Remove));
+        assert_eq!(action_for_event("repository", Some("privatized")), Some(Remove));
+        assert_eq!(action_for_event("repository", Some("renamed")), Some(Refresh(RepoRefresh::Full)));
+        assert_eq!(action_for_event("repository", Some("publicized")), Some(Refresh(RepoRefresh::Full)));
+        // ping 在处理函数中直接回复 pong，不对应数据操作
+        assert_eq!(action_for_event("ping", None), None);
+        assert_eq!(action_for_event("issues", Some("opened")), None);
+    }
+}
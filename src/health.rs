@@ -0,0 +1,245 @@
+// 健康检查：GET /healthz（存活）和 GET /readyz（就绪）
+//
+// /healthz 只说明进程能处理请求，供 systemd watchdog 或负载均衡判断是否需要重启。
+// /readyz 逐项检查依赖：模板能否渲染、数据目录是否可写、GitHub 数据缓存是否有效以及数据年龄、remove.bg 密钥是否配置。
+// 每项的结果为 ok、warn 或 fail：有 fail 时返回 503，只有 warn 时仍返回 200（站点可用，但部分功能受影响）。
+
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::github::config::GitHubConfig;
+use crate::github::GitHubDataManager;
+use crate::tools::config::ToolsConfig;
+
+/// 进程启动时间
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// 记录启动时间，在 main 开始时调用
+pub fn init() {
+    STARTED.get_or_init(Instant::now);
+}
+
+/// 单项检查的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    /// 附加信息，如数据年龄
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn failed(name: &'static str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, message)
+    }
+
+    fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+/// 模板渲染：pages 为成功渲染的页面数
+pub fn check_templates(result: Result<usize, askama::Error>) -> Check {
+    match result {
+        Ok(pages) => Check::new("templates", CheckStatus::Ok, format!("{} 个页面模板渲染正常", pages)),
+        Err(e) => {
+            tracing::error!("就绪检查: 模板渲染失败: {}", e);
+            Check::new("templates", CheckStatus::Fail, format!("模板渲染失败: {}", e))
+        }
+    }
+}
+
+/// 数据目录可写：刷新结果和刷新报告都要写入数据目录
+pub fn check_data_dir(manager: &GitHubDataManager) -> Check {
+    let dir = manager.data_dir();
+    let probe = dir.join(format!(".readyz-{}", hex::encode(rand::random::<[u8; 8]>())));
+    let result = fs::create_dir_all(dir)
+        .and_then(|_| fs::write(&probe, b"ok"))
+        .and_then(|_| fs::remove_file(&probe));
+    match result {
+        Ok(()) => Check::new("data_dir", CheckStatus::Ok, format!("{} 可写", dir.display())),
+        Err(e) => {
+            tracing::error!("就绪检查: 数据目录 {} 不可写: {}", dir.display(), e);
+            Check::new("data_dir", CheckStatus::Fail, format!("{} 不可写: {}", dir.display(), e))
+        }
+    }
+}
+
+/// GitHub 数据缓存：没有缓存或无法解析时页面和 API 都没有数据可用；超过下次更新时间只是过期，仍可降级使用
+pub fn check_github_data(manager: &GitHubDataManager) -> Check {
+    let path = manager.data_file();
+    if !Path::new(path).exists() {
+        return Check::new("github_data", CheckStatus::Fail, "没有 GitHub 数据缓存，首次刷新尚未完成");
+    }
+    let data = match manager.load_data() {
+        Ok(data) => data,
+        Err(e) => {
+            let message = format!("GitHub 数据缓存无法解析: {}", e);
+            tracing::error!("就绪检查: {}", message);
+            return Check::new("github_data", CheckStatus::Fail, message);
+        }
+    };
+
+    let now = Utc::now();
+    let info = json!({
+        "last_updated": data.last_updated,
+        "next_update": data.next_update,
+        "age_seconds": (now - data.last_updated).num_seconds(),
+        "projects": data.projects.len(),
+        "articles": data.articles.len(),
+        "offline": GitHubConfig::is_offline(),
+    });
+    let (status, message) = if now >= data.next_update {
+        (CheckStatus::Warn, format!("数据已过期，最后更新于 {}", data.last_updated.format("%Y-%m-%d %H:%M:%S UTC")))
+    } else {
+        (CheckStatus::Ok, format!("{} 个项目，{} 篇文章", data.projects.len(), data.articles.len()))
+    };
+    Check::new("github_data", status, message).with_data(info)
+}
+
+/// remove.bg 密钥：未配置时只影响背景更换工具
+pub fn check_removebg() -> Check {
+    match ToolsConfig::get_removebg_api_key() {
+        Some(_) => Check::new("removebg", CheckStatus::Ok, "remove.bg API Key 已配置"),
+        None => Check::new("removebg", CheckStatus::Warn, "remove.bg API Key 未配置，背景更换工具不可用"),
+    }
+}
+
+/// 健康检查的响应不能被 CDN 或浏览器缓存
+fn no_store(status: StatusCode, body: Value) -> Response {
+    let mut response = (status, Json(body)).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+/// GET /healthz
+pub async fn healthz() -> Response {
+    let uptime = STARTED.get().map(|started| started.elapsed().as_secs()).unwrap_or(0);
+    no_store(
+        StatusCode::OK,
+        json!({
+            "status": "ok",
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime_seconds": uptime,
+        }),
+    )
+}
+
+/// 汇总就绪检查的结果
+pub fn readiness(checks: Vec<Check>) -> Response {
+    let worst = checks.iter().map(|check| check.status).max().unwrap_or(CheckStatus::Ok);
+    let (status, summary) = match worst {
+        CheckStatus::Ok => (StatusCode::OK, "ready"),
+        CheckStatus::Warn => (StatusCode::OK, "degraded"),
+        CheckStatus::Fail => (StatusCode::SERVICE_UNAVAILABLE, "not_ready"),
+    };
+    if worst == CheckStatus::Fail {
+        let failed: Vec<&str> = checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .map(|check| check.name)
+            .collect();
+        tracing::warn!("就绪检查未通过: {}", failed.join(", "));
+    }
+    no_store(status, json!({ "status": summary, "checks": checks }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::client::GitHubClient;
+    use crate::github::GitHubData;
+    use axum::body::to_bytes;
+
+    fn manager(name: &str) -> (std::path::PathBuf, GitHubDataManager) {
+        let dir = std::env::temp_dir().join(format!("xjz-health-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let manager = GitHubDataManager::with_backend(Box::new(GitHubClient::new().unwrap()), &dir, "tester");
+        (dir, manager)
+    }
+
+    fn write_data(manager: &GitHubDataManager, next_update: chrono::DateTime<Utc>) {
+        let data = GitHubData {
+            next_update,
+            ..GitHubData::default()
+        };
+        fs::write(manager.data_file(), serde_json::to_vec(&data).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn github_data_check_follows_cache_state() {
+        let (dir, manager) = manager("github-data");
+        assert_eq!(check_github_data(&manager).status, CheckStatus::Fail);
+
+        // 数据目录可写，顺便创建目录
+        assert_eq!(check_data_dir(&manager).status, CheckStatus::Ok);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0, "探测文件应已删除");
+
+        write_data(&manager, Utc::now() + chrono::Duration::hours(1));
+        let check = check_github_data(&manager);
+        assert_eq!(check.status, CheckStatus::Ok);
+        assert_eq!(check.data.as_ref().unwrap()["projects"], 0);
+
+        write_data(&manager, Utc::now() - chrono::Duration::hours(1));
+        assert_eq!(check_github_data(&manager).status, CheckStatus::Warn);
+
+        fs::write(manager.data_file(), "{").unwrap();
+        assert_eq!(check_github_data(&manager).status, CheckStatus::Fail);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    async fn summarize(statuses: &[CheckStatus]) -> (StatusCode, Value) {
+        let checks = statuses.iter().map(|status| Check::new("test", *status, "")).collect();
+        let response = readiness(checks);
+        let status = response.status();
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        let body = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn readiness_uses_worst_check() {
+        let (status, body) = summarize(&[CheckStatus::Ok, CheckStatus::Ok]).await;
+        assert_eq!((status, body["status"].as_str()), (StatusCode::OK, Some("ready")));
+        assert_eq!(body["checks"].as_array().unwrap().len(), 2);
+
+        let (status, body) = summarize(&[CheckStatus::Ok, CheckStatus::Warn]).await;
+        assert_eq!((status, body["status"].as_str()), (StatusCode::OK, Some("degraded")));
+
+        let (status, body) = summarize(&[CheckStatus::Fail, CheckStatus::Warn]).await;
+        assert_eq!((status, body["status"].as_str()), (StatusCode::SERVICE_UNAVAILABLE, Some("not_ready")));
+        assert_eq!(body["checks"][0]["status"], "fail");
+    }
+}
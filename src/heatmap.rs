@@ -0,0 +1,147 @@
+// 贡献热力图：把贡献日历渲染成 SVG，样式与 GitHub 个人主页一致
+//
+// 每列是一周（周日在上），按贡献等级 0-4 着色。
+// 前端也可以直接用 /api/activity 返回的 JSON 自行绘制。
+
+use chrono::Datelike;
+
+use crate::github::models::ContributionCalendar;
+
+const CELL: u32 = 10;
+const STEP: u32 = 13;
+const LEFT: u32 = 28;
+const TOP: u32 = 20;
+const COLORS: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+
+pub fn render(calendar: &ContributionCalendar) -> String {
+    if calendar.weeks.is_empty() {
+        return concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="300" height="40" viewBox="0 0 300 40">"#,
+            r##"<text x="150" y="24" text-anchor="middle" font-size="12" fill="#586069">暂无贡献数据</text>"##,
+            "</svg>"
+        )
+        .to_string();
+    }
+
+    let width = LEFT + calendar.weeks.len() as u32 * STEP + 10;
+    let height = TOP + 7 * STEP + 24;
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="-apple-system, sans-serif" font-size="9" fill="#586069">"##,
+        w = width,
+        h = height
+    );
+
+    // 月份标签：放在每月第一次出现的那一列上方，距离下一个标签不足 3 列时省略
+    let mut months: Vec<(usize, u32)> = Vec::new();
+    for (x, week) in calendar.weeks.iter().enumerate() {
+        let Some(first) = week.first() else { continue };
+        if months.last().map(|&(_, month)| month) != Some(first.date.month()) {
+            months.push((x, first.date.month()));
+        }
+    }
+    for (i, &(x, month)) in months.iter().enumerate() {
+        if months.get(i + 1).is_some_and(|&(next, _)| next - x < 3) {
+            continue;
+        }
+        svg.push_str(&format!(
+            r#"<text x="{}" y="{}">{}月</text>"#,
+            LEFT + x as u32 * STEP,
+            TOP - 8,
+            month
+        ));
+    }
+
+    // 星期标签：只标一、三、五，避免拥挤
+    for (row, label) in [(1, "一"), (3, "三"), (5, "五")] {
+        svg.push_str(&format!(
+            r#"<text x="0" y="{}">{}</text>"#,
+            TOP + row * STEP + CELL - 1,
+            label
+        ));
+    }
+
+    for (x, week) in calendar.weeks.iter().enumerate() {
+        for day in week {
+            let y = day.date.weekday().num_days_from_sunday();
+            svg.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{c}" height="{c}" rx="2" fill="{}"><title>{}：{} 次贡献</title></rect>"#,
+                LEFT + x as u32 * STEP,
+                TOP + y * STEP,
+                COLORS[(day.level as usize).min(4)],
+                day.date.format("%Y-%m-%d"),
+                day.count,
+                c = CELL
+            ));
+        }
+    }
+
+    svg.push_str(&format!(
+        r#"<text x="{}" y="{}">过去一年共 {} 次贡献</text>"#,
+        LEFT,
+        height - 6,
+        calendar.total
+    ));
+    svg.push_str("</svg>");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::models::ContributionDay;
+    use chrono::{Duration, NaiveDate};
+
+    /// 从 start（周日）开始 days 天的日历，第 i 天的等级为 i % 6（包括超出范围的 5）
+    fn calendar(start: NaiveDate, days: usize) -> ContributionCalendar {
+        let days: Vec<ContributionDay> = (0..days)
+            .map(|i| ContributionDay {
+                date: start + Duration::days(i as i64),
+                count: i as u32,
+                level: (i % 6) as u8,
+            })
+            .collect();
+        ContributionCalendar {
+            total: days.iter().map(|d| d.count).sum(),
+            weeks: days.chunks(7).map(<[ContributionDay]>::to_vec).collect(),
+        }
+    }
+
+    fn rects(svg: &str) -> Vec<&str> {
+        svg.split("<rect ").skip(1).collect()
+    }
+
+    #[test]
+    fn days_are_laid_out_by_week_and_weekday() {
+        // 2026-01-25 是周日
+        let start = NaiveDate::from_ymd_opt(2026, 1, 25).unwrap();
+        let svg = render(&calendar(start, 10));
+        let rects = rects(&svg);
+        assert_eq!(rects.len(), 10);
+        // 第一周周日在左上角，周一在它下面，第二周的周日在右边一列
+        assert!(rects[0].starts_with(&format!(r#"x="{}" y="{}""#, LEFT, TOP)));
+        assert!(rects[1].starts_with(&format!(r#"x="{}" y="{}""#, LEFT, TOP + STEP)));
+        assert!(rects[7].starts_with(&format!(r#"x="{}" y="{}""#, LEFT + STEP, TOP)));
+        assert!(rects[7].contains("<title>2026-02-01：7 次贡献</title>"));
+        // 跨月时两列距离不足 3 列，只标后一个月
+        assert!(svg.contains(">2月</text>") && !svg.contains(">1月</text>"));
+        assert!(svg.contains("过去一年共 45 次贡献"));
+    }
+
+    #[test]
+    fn levels_pick_colors_and_clamp() {
+        let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let svg = render(&calendar(start, 6));
+        let fills: Vec<&str> = rects(&svg)
+            .iter()
+            .map(|rect| rect.split("fill=\"").nth(1).unwrap().split('"').next().unwrap())
+            .collect();
+        assert_eq!(fills, [COLORS[0], COLORS[1], COLORS[2], COLORS[3], COLORS[4], COLORS[4]]);
+    }
+
+    #[test]
+    fn empty_calendar_renders_placeholder() {
+        let svg = render(&ContributionCalendar::default());
+        assert!(svg.contains("暂无贡献数据"));
+        assert!(rects(&svg).is_empty());
+    }
+}
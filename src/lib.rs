@@ -0,0 +1,21 @@
+// 站点的各个模块，main.rs 中的服务器和 tests/ 下的集成测试都从这里引用
+
+pub mod api;
+pub mod assets;
+pub mod badge;
+pub mod cache;
+pub mod card;
+pub mod client_ip;
+pub mod github;
+pub mod health;
+pub mod heatmap;
+pub mod lifecycle;
+pub mod markdown;
+pub mod metrics;
+pub mod ratelimit;
+pub mod security;
+pub mod server;
+pub mod social;
+pub mod telemetry;
+pub mod tools;
+pub mod update;
@@ -0,0 +1,186 @@
+// 进程生命周期：信号处理、优雅关闭和 systemd 集成
+//
+// 收到 SIGTERM / SIGINT 后停止接受新连接，等待进行中的请求完成（最多 SHUTDOWN_TIMEOUT_SECS 秒），
+// 同时通过 shutdown_token() 取消后台的 GitHub 刷新，并等待 spawn_background() 启动的任务记录结果后再退出；数据文件先写临时文件再改名，中途退出不会留下写了一半的文件。
+// 在 systemd 下运行时（Type=notify）启动完成后发送 READY=1，关闭时发送 STOPPING=1，
+// 配置了 WatchdogSec 时定期发送 WATCHDOG=1；由 .socket 单元传入监听套接字时直接使用，不再自己绑定端口。
+// 不在 systemd 下运行时这些通知都不做任何事。
+
+use std::net::TcpListener as StdTcpListener;
+use std::os::fd::{FromRawFd, IntoRawFd, RawFd};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+use sd_notify::NotifyState;
+use tokio::net::TcpListener;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+pub struct LifecycleConfig;
+
+impl LifecycleConfig {
+    /// 收到退出信号后等待进行中请求的最长时间（SHUTDOWN_TIMEOUT_SECS），默认 30 秒，
+    /// 应小于 systemd 的 TimeoutStopSec
+    pub fn get_shutdown_timeout() -> Duration {
+        let secs = std::env::var("SHUTDOWN_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(30);
+        Duration::from_secs(secs)
+    }
+}
+
+/// 进程开始关闭时取消，后台任务在这里检查是否需要提前结束
+pub fn shutdown_token() -> &'static CancellationToken {
+    static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+    TOKEN.get_or_init(CancellationToken::new)
+}
+
+fn background_tasks() -> &'static TaskTracker {
+    static TRACKER: OnceLock<TaskTracker> = OnceLock::new();
+    TRACKER.get_or_init(TaskTracker::new)
+}
+
+/// 启动后台任务（更新任务、Webhook 刷新），退出前会等待它们结束
+pub fn spawn_background<F>(task: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    background_tasks().spawn(task);
+}
+
+/// 服务停止后等待后台任务结束，最多等待 timeout
+pub async fn wait_for_background(timeout: Duration) {
+    let tasks = background_tasks();
+    tasks.close();
+    if tasks.is_empty() {
+        return;
+    }
+    tracing::info!("等待 {} 个后台任务结束", tasks.len());
+    if tokio::time::timeout(timeout, tasks.wait()).await.is_err() {
+        tracing::warn!("后台任务在 {} 秒内没有结束，强制退出", timeout.as_secs());
+    }
+}
+
+/// 是否已经开始关闭
+pub fn is_shutting_down() -> bool {
+    shutdown_token().is_cancelled()
+}
+
+fn notify(state: &[NotifyState]) {
+    if let Err(e) = sd_notify::notify(false, state) {
+        tracing::warn!("发送 systemd 通知失败: {}", e);
+    }
+}
+
+/// 服务已开始接受请求
+pub fn notify_ready(status: &str) {
+    notify(&[NotifyState::Ready, NotifyState::Status(status)]);
+}
+
+/// 取出 systemd 套接字激活传入的第一个监听套接字，没有时返回 None
+pub fn activated_listener() -> Option<TcpListener> {
+    let fds: Vec<RawFd> = match sd_notify::listen_fds() {
+        Ok(fds) => fds.collect(),
+        Err(e) => {
+            tracing::warn!("读取 systemd 传入的套接字失败，改为自行绑定端口: {}", e);
+            return None;
+        }
+    };
+    let fd = *fds.first()?;
+    if fds.len() > 1 {
+        tracing::warn!("systemd 传入了 {} 个套接字，只使用第一个", fds.len());
+    }
+    // SAFETY: LISTEN_PID 与本进程一致时，从 3 开始的 LISTEN_FDS 个描述符由 systemd 传给本进程，
+    // 且 listen_fds() 已清除环境变量，这里是唯一取得它所有权的地方
+    let listener = unsafe { StdTcpListener::from_raw_fd(fd) };
+    // 描述符不是 TCP 套接字时交还所有权而不关闭它，它可能已被其他代码使用
+    if let Err(e) = listener.local_addr().and_then(|_| listener.set_nonblocking(true)) {
+        tracing::error!("systemd 传入的描述符 {} 不是可用的 TCP 监听套接字，改为自行绑定端口: {}", fd, e);
+        let _ = listener.into_raw_fd();
+        return None;
+    }
+    match TcpListener::from_std(listener) {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            tracing::error!("无法使用 systemd 传入的套接字 {}: {}", fd, e);
+            None
+        }
+    }
+}
+
+/// 配置了 WatchdogSec 时，按一半的间隔发送 WATCHDOG=1；运行时卡死时 systemd 会重启服务
+pub fn spawn_watchdog() {
+    let mut usec = 0;
+    if !sd_notify::watchdog_enabled(false, &mut usec) || usec == 0 {
+        return;
+    }
+    let interval = Duration::from_micros(usec / 2);
+    tracing::info!("已启用 systemd watchdog，每 {:?} 发送一次心跳", interval);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => notify(&[NotifyState::Watchdog]),
+                _ = shutdown_token().cancelled() => break,
+            }
+        }
+    });
+}
+
+/// 等待 SIGTERM 或 SIGINT，然后通知 systemd 并取消后台任务；作为 axum 的优雅关闭信号
+pub async fn shutdown_signal() {
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => Some(terminate),
+        Err(e) => {
+            tracing::error!("无法监听 SIGTERM: {}", e);
+            None
+        }
+    };
+    let name = tokio::select! {
+        _ = tokio::signal::ctrl_c() => "SIGINT",
+        _ = async {
+            match terminate.as_mut() {
+                Some(terminate) => terminate.recv().await,
+                None => std::future::pending().await,
+            }
+        } => "SIGTERM",
+    };
+
+    tracing::info!("收到 {}，停止接受新连接并等待进行中的请求完成", name);
+    notify(&[NotifyState::Stopping, NotifyState::Status("正在关闭")]);
+    shutdown_token().cancel();
+}
+
+/// 开始关闭后再等待 timeout，用于限制优雅关闭的总时长
+pub async fn drain_deadline(timeout: Duration) {
+    shutdown_token().cancelled().await;
+    tokio::time::sleep(timeout).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    // 关闭信号会取消全局的 shutdown_token，影响同一进程中的其他测试，见 tests/lifecycle_shutdown.rs
+    #[tokio::test]
+    async fn waits_for_background_tasks_up_to_timeout() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = finished.clone();
+        spawn_background(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            flag.store(true, Ordering::SeqCst);
+        });
+        wait_for_background(Duration::from_secs(5)).await;
+        assert!(finished.load(Ordering::SeqCst), "应等待后台任务写完结果");
+
+        // 不结束的任务最多等待 timeout
+        spawn_background(std::future::pending());
+        let start = std::time::Instant::now();
+        wait_for_background(Duration::from_millis(100)).await;
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -1,249 +1,601 @@
 use axum::{
-    extract::ConnectInfo, // 需要 ConnectInfo 来获取 IP
-    http::StatusCode,
-    response::{Html, IntoResponse, Json, Response},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    handler::HandlerWithoutStateExt,
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{Html, IntoResponse, Response},
+    middleware,
     routing::{get, post}, // 需要 post 来处理表单提交
     Router,
 };
 use askama::Template;
-use serde::Serialize;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::net::SocketAddr; // 需要 SocketAddr
+use std::sync::Arc;
 use tokio::net::TcpListener;
-use tower_http::services::ServeDir;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt}; // 日志
-
-
-// 引入模块
-mod github;
-mod tools; // 声明 tools 模块
+use tower::ServiceBuilder;
+use tracing::Instrument;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::set_header::SetResponseHeaderLayer;
+use tower_http::trace::TraceLayer;
+
+
+// 引入模块（定义在 lib.rs 中）
+use xjz_website::{
+    api, assets, badge, cache, card, client_ip, github, health, heatmap, lifecycle, markdown, metrics, ratelimit,
+    security, server, social, telemetry, tools, update,
+};
 
 // 使用模块中的内容
-use github::{GitHubDataManager, StoredArticle, StoredProject};
-use crate::tools::{handle_change_background, handle_get_ip, handle_resize_image, handle_get_fake_identity}; // <-- 添加 handle_get_fake_identity
+use api::{ApiError, ApiResponse, ErrorCode};
+use client_ip::ClientIp;
+use github::config::GitHubConfig;
+use github::{handle_github_webhook, Activity, GitHubDataManager, ProjectDetails, RefreshReport, ReleaseFeedItem, StoredArticle, StoredNote, StoredProject, StoredRelease};
+use social::PageMeta;
+use update::{StartOutcome, UpdateConfig, UpdateJob, UpdateJobs};
+use tools::{handle_change_background, handle_get_ip, handle_resize_image, handle_get_fake_identity}; // <-- 添加 handle_get_fake_identity
 
 // === 模板定义 ===
 #[derive(Template)]
 #[template(path = "index.html")]
-struct IndexTemplate;
+struct IndexTemplate {
+    meta: PageMeta,
+}
 
 #[derive(Template)]
 #[template(path = "about.html")]
-struct AboutTemplate;
+struct AboutTemplate {
+    meta: PageMeta,
+}
 
 #[derive(Template)]
 #[template(path = "projects.html")]
-struct ProjectsTemplate;
+struct ProjectsTemplate {
+    meta: PageMeta,
+}
+
+#[derive(Template)]
+#[template(path = "project_detail.html")]
+struct ProjectDetailTemplate {
+    meta: PageMeta,
+    title: String,
+    project: StoredProject,
+    details: ProjectDetails,
+    readme_html: String,
+    release_notes_html: String,
+}
 
 #[derive(Template)]
 #[template(path = "contact.html")]
-struct ContactTemplate;
+struct ContactTemplate {
+    meta: PageMeta,
+}
 
 #[derive(Template)]
 #[template(path = "resume.html")]
-struct ResumeTemplate;
+struct ResumeTemplate {
+    meta: PageMeta,
+}
 
 #[derive(Template)]
 #[template(path = "blog.html")]
-struct BlogTemplate;
+struct BlogTemplate {
+    meta: PageMeta,
+}
+
+#[derive(Template)]
+#[template(path = "notes.html")]
+struct NotesTemplate {
+    meta: PageMeta,
+    notes: Vec<NoteView>,
+    tags: Vec<String>,
+    active_tag: Option<String>,
+}
+
+struct NoteView {
+    note: StoredNote,
+    html: String,
+}
 
 #[derive(Template)] // 添加 Tools 页面的模板
 #[template(path = "tools.html")]
-struct ToolsTemplate;
+struct ToolsTemplate {
+    meta: PageMeta,
+}
+
+#[derive(Template)]
+#[template(path = "error.html")]
+struct ErrorTemplate {
+    meta: PageMeta,
+    status: u16,
+    heading: &'static str,
+    message: String,
+}
+
+// === 共享状态 ===
+#[derive(Clone, Default)]
+struct AppState {
+    update_jobs: Arc<UpdateJobs>,
+}
 
 // === API 处理函数 ===
 const GITHUB_USERNAME: &str = "xjz6626";
 
-#[derive(Serialize)]
-struct ApiResponse<T> {
-    success: bool,
-    data: Option<T>,
-    message: String,
-    last_updated: Option<String>,
+async fn api_projects() -> Result<ApiResponse<Vec<StoredProject>>, ApiError> {
+    let github_data = load_github_data().await?;
+    Ok(ApiResponse::ok(github_data.projects, "项目数据获取成功")
+        .with_data_info(github_data.last_updated, github_data.degraded))
 }
 
-async fn api_projects() -> impl IntoResponse {
-    let manager = match GitHubDataManager::new(GITHUB_USERNAME.to_string()) {
-        Ok(m) => m,
-        Err(e) => {
-            tracing::error!("GitHubDataManager 初始化失败: {}", e); // 添加日志
-            return Json(ApiResponse::<Vec<StoredProject>> {
-                success: false,
-                data: None,
-                message: format!("初始化失败: {}", e),
-                last_updated: None,
-            });
-        }
-    };
+async fn api_articles() -> Result<ApiResponse<Vec<StoredArticle>>, ApiError> {
+    let github_data = load_github_data().await?;
+    Ok(ApiResponse::ok(github_data.articles, "文章数据获取成功")
+        .with_data_info(github_data.last_updated, github_data.degraded))
+}
 
-    match manager.get_data().await {
-        Ok(github_data) => Json(ApiResponse {
-            success: true,
-            data: Some(github_data.projects),
-            message: "项目数据获取成功".to_string(),
-            last_updated: Some(github_data.last_updated.to_rfc3339()),
-        }),
-        Err(e) => {
-            tracing::error!("获取项目数据失败: {}", e); // 添加日志
-            Json(ApiResponse::<Vec<StoredProject>> {
-                success: false,
-                data: None,
-                message: format!("获取项目数据失败: {}", e),
-                last_updated: None,
-            })
-        }
+async fn api_project_releases(Path(name): Path<String>) -> Result<ApiResponse<Vec<StoredRelease>>, ApiError> {
+    let mut github_data = load_github_data().await?;
+    if !github_data.projects.iter().any(|p| p.name == name) {
+        return Err(ApiError::new(ErrorCode::ProjectNotFound, format!("项目 {} 不存在", name)));
     }
+    let releases = github_data
+        .project_details
+        .remove(&name)
+        .map(|d| d.releases)
+        .unwrap_or_default();
+    Ok(ApiResponse::ok(releases, "发布数据获取成功")
+        .with_data_info(github_data.last_updated, github_data.degraded))
 }
 
-async fn api_articles() -> impl IntoResponse {
-    let manager = match GitHubDataManager::new(GITHUB_USERNAME.to_string()) {
-        Ok(m) => m,
-        Err(e) => {
-            tracing::error!("GitHubDataManager 初始化失败: {}", e); // 添加日志
-            return Json(ApiResponse::<Vec<StoredArticle>> {
-                success: false,
-                data: None,
-                message: format!("初始化失败: {}", e),
-                last_updated: None,
-            });
+async fn api_recent_releases() -> Result<ApiResponse<Vec<ReleaseFeedItem>>, ApiError> {
+    let github_data = load_github_data().await?;
+    Ok(ApiResponse::ok(github_data.recent_releases(20), "最近发布获取成功")
+        .with_data_info(github_data.last_updated, github_data.degraded))
+}
+
+async fn api_stats() -> Result<ApiResponse<HashMap<&'static str, u32>>, ApiError> {
+    let github_data = load_github_data().await?;
+    let total_stars: u32 = github_data.projects.iter().map(|p| p.stargazers_count).sum();
+    let total_forks: u32 = github_data.projects.iter().map(|p| p.forks_count).sum();
+
+    let mut stats = HashMap::new();
+    stats.insert("total_projects", github_data.projects.len() as u32);
+    stats.insert("total_articles", github_data.articles.len() as u32);
+    stats.insert("total_stars", total_stars);
+    stats.insert("total_forks", total_forks);
+
+    if let Some(user_stats) = &github_data.user_stats {
+        stats.insert("followers", user_stats.followers);
+        stats.insert("public_repos", user_stats.total_repos);
+    }
+
+    Ok(ApiResponse::ok(stats, "统计数据获取成功").with_data_info(github_data.last_updated, github_data.degraded))
+}
+
+async fn api_force_update(
+    State(state): State<AppState>,
+    ClientIp(client): ClientIp,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let Some(token) = UpdateConfig::get_token() else {
+        return Err(ApiError::new(ErrorCode::NotConfigured, "更新接口未启用，请配置 UPDATE_API_TOKEN"));
+    };
+
+    if !update::authorize(&headers, &body, &token) {
+        tracing::warn!("拒绝未授权的更新请求，来自 {}", client);
+        return Err(ApiError::new(ErrorCode::Unauthorized, "未授权"));
+    }
+
+    if GitHubConfig::is_offline() {
+        return Err(ApiError::new(ErrorCode::Offline, "离线模式下无法从 GitHub 更新数据"));
+    }
+
+    let job = match state.update_jobs.try_start(&client.to_string(), UpdateConfig::get_cooldown()) {
+        StartOutcome::Started(job) => job,
+        StartOutcome::AlreadyRunning(job) => {
+            return Ok((StatusCode::ACCEPTED, ApiResponse::ok(job, "已有更新任务在运行")).into_response());
+        }
+        StartOutcome::CoolingDown(remaining) => {
+            let retry_after = remaining.as_secs().max(1);
+            return Err(
+                ApiError::new(ErrorCode::RateLimited, format!("请求过于频繁，请在 {} 秒后重试", retry_after))
+                    .with_retry_after(retry_after),
+            );
         }
     };
 
-    match manager.get_data().await {
-        Ok(github_data) => Json(ApiResponse {
-            success: true,
-            data: Some(github_data.articles),
-            message: "文章数据获取成功".to_string(),
-            last_updated: Some(github_data.last_updated.to_rfc3339()),
-        }),
-        Err(e) => {
-            tracing::error!("获取文章数据失败: {}", e); // 添加日志
-            Json(ApiResponse::<Vec<StoredArticle>> {
-                success: false,
-                data: None,
-                message: format!("获取文章数据失败: {}", e),
-                last_updated: None,
-            })
+    tracing::info!(job_id = %job.id, client = %client, "开始后台更新 GitHub 数据");
+    let jobs = state.update_jobs.clone();
+    let job_id = job.id.clone();
+    // 后台任务的日志挂在触发它的请求下，可以按请求 ID 找到
+    let span = tracing::info_span!("update_job", job_id = %job_id);
+    lifecycle::spawn_background(async move {
+        let manager = GitHubDataManager::new(GITHUB_USERNAME.to_string())
+            .map_err(|e| format!("初始化失败: {}", e));
+        let (result, report) = match manager {
+            Ok(manager) => {
+                let result = manager
+                    .force_update()
+                    .await
+                    .map_err(|e| format!("强制更新失败: {}", e));
+                (result, manager.last_report())
+            }
+            Err(e) => (Err(e), None),
+        };
+        if let Err(e) = &result {
+            tracing::error!(job_id = %job_id, "{}", e);
         }
+        jobs.finish(&job_id, result.as_ref().map_err(Clone::clone), report);
+    }
+    .instrument(span));
+
+    Ok((StatusCode::ACCEPTED, ApiResponse::ok(job, "更新任务已创建")).into_response())
+}
+
+async fn api_update_status(State(state): State<AppState>, Path(id): Path<String>) -> Result<ApiResponse<UpdateJob>, ApiError> {
+    match state.update_jobs.get(&id) {
+        Some(job) => Ok(ApiResponse::ok(job, "任务状态获取成功")),
+        None => Err(ApiError::new(ErrorCode::JobNotFound, format!("任务 {} 不存在", id))),
     }
 }
 
-async fn api_stats() -> impl IntoResponse {
-    let manager = match GitHubDataManager::new(GITHUB_USERNAME.to_string()) {
-        Ok(m) => m,
-        Err(e) => {
-            tracing::error!("GitHubDataManager 初始化失败: {}", e); // 添加日志
-            let empty_stats: HashMap<&str, u32> = HashMap::new();
-            return Json(ApiResponse {
-                success: false,
-                data: Some(empty_stats),
-                message: format!("初始化失败: {}", e),
-                last_updated: None,
-            });
-        }
+async fn api_update_history() -> Result<ApiResponse<Vec<RefreshReport>>, ApiError> {
+    let manager = GitHubDataManager::new(GITHUB_USERNAME.to_string()).map_err(|e| {
+        tracing::error!("GitHubDataManager 初始化失败: {}", e);
+        ApiError::internal("数据管理器初始化失败")
+    })?;
+
+    let history = manager.load_history();
+    let last_updated = history.first().map(|r| r.started_at.to_rfc3339());
+    let message = format!("共 {} 条刷新记录", history.len());
+    let mut response = ApiResponse::ok(history, message);
+    response.last_updated = last_updated;
+    Ok(response)
+}
+
+async fn api_notes() -> Result<ApiResponse<Vec<StoredNote>>, ApiError> {
+    let github_data = load_github_data().await?;
+    Ok(ApiResponse::ok(github_data.notes, "笔记数据获取成功")
+        .with_data_info(github_data.last_updated, github_data.degraded))
+}
+
+async fn api_activity() -> Result<ApiResponse<Activity>, ApiError> {
+    let github_data = load_github_data().await?;
+    Ok(ApiResponse::ok(github_data.activity, "最近动态获取成功")
+        .with_data_info(github_data.last_updated, github_data.degraded))
+}
+
+// 服务端渲染的贡献热力图，可直接用于 <img>
+async fn api_contributions_svg() -> Response {
+    // 数据不可用时输出“暂无贡献数据”的占位图，避免页面上出现破图
+    let calendar = match load_github_data().await {
+        Ok(github_data) => github_data.activity.contributions.unwrap_or_default(),
+        Err(_) => Default::default(),
     };
 
-    match manager.get_data().await {
-        Ok(github_data) => {
-            let total_stars: u32 = github_data.projects.iter().map(|p| p.stargazers_count).sum();
-            let total_forks: u32 = github_data.projects.iter().map(|p| p.forks_count).sum();
+    (
+        [
+            (header::CONTENT_TYPE, "image/svg+xml; charset=utf-8"),
+            (header::CACHE_CONTROL, "public, max-age=3600"),
+        ],
+        heatmap::render(&calendar),
+    )
+        .into_response()
+}
 
-            let mut stats = HashMap::new();
-            stats.insert("total_projects", github_data.projects.len() as u32);
-            stats.insert("total_articles", github_data.articles.len() as u32);
-            stats.insert("total_stars", total_stars);
-            stats.insert("total_forks", total_forks);
+// 项目徽章，缓存时间跟随 GitHub 数据的刷新周期
+async fn project_badge(
+    Path((repo, metric)): Path<(String, String)>,
+    Query(query): Query<badge::BadgeQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let style = badge::BadgeStyle::parse(query.style.as_deref());
+    let label_color = query.label_color.as_deref().and_then(badge::parse_color);
+    let error_badge = |status: StatusCode, message: &str| {
+        let badge = badge::Badge {
+            label: query.label.clone().unwrap_or_else(|| "badge".to_string()),
+            message: message.to_string(),
+            color: "#9f9f9f".to_string(),
+        };
+        (
+            status,
+            [
+                (header::CONTENT_TYPE, "image/svg+xml; charset=utf-8"),
+                (header::CACHE_CONTROL, "no-cache"),
+            ],
+            badge::render(&badge, label_color.as_deref(), style),
+        )
+            .into_response()
+    };
 
-            if let Some(user_stats) = &github_data.user_stats {
-                stats.insert("followers", user_stats.followers);
-                stats.insert("public_repos", user_stats.total_repos);
-            }
+    let Some(metric) = metric.strip_suffix(".svg") else {
+        return error_badge(StatusCode::NOT_FOUND, "未知指标");
+    };
 
-            Json(ApiResponse {
-                success: true,
-                data: Some(stats),
-                message: "统计数据获取成功".to_string(),
-                last_updated: Some(github_data.last_updated.to_rfc3339()),
-            })
-        }
-        Err(e) => {
-            tracing::error!("获取统计数据失败: {}", e); // 添加日志
-            let empty_stats: HashMap<&str, u32> = HashMap::new();
-            Json(ApiResponse {
-                success: false,
-                data: Some(empty_stats),
-                message: format!("获取统计数据失败: {}", e),
-                last_updated: None,
-            })
-        }
+    let github_data = match load_github_data().await {
+        Ok(data) => data,
+        Err(e) => return error_badge(e.status(), "数据不可用"),
+    };
+
+    let Some(project) = github_data.projects.iter().find(|p| p.name == repo) else {
+        return error_badge(StatusCode::NOT_FOUND, "项目不存在");
+    };
+    let Some(mut badge) = badge::metric_badge(&github_data, project, metric) else {
+        return error_badge(StatusCode::NOT_FOUND, "未知指标");
+    };
+    if let Some(label) = query.label {
+        badge.label = label;
+    }
+    if let Some(color) = query.color.as_deref().and_then(badge::parse_color) {
+        badge.color = color;
+    }
+
+    let svg = badge::render(&badge, label_color.as_deref(), style);
+    let etag = format!("\"{}\"", &hex::encode(Sha256::digest(svg.as_bytes()))[..16]);
+    let last_modified = github_data.last_updated.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+    // 缓存到下一次计划刷新，降级数据只缓存一分钟
+    let max_age = if github_data.degraded {
+        60
+    } else {
+        (github_data.next_update - chrono::Utc::now()).num_seconds().clamp(60, 86400)
+    };
+    let cache_control = format!("public, max-age={}", max_age);
+
+    let not_modified = match headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        Some(tags) => tags.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"),
+        None => headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+            .is_some_and(|since| since.timestamp() >= github_data.last_updated.timestamp()),
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, "image/svg+xml; charset=utf-8".to_string()),
+        (header::CACHE_CONTROL, cache_control),
+        (header::ETAG, etag),
+        (header::LAST_MODIFIED, last_modified),
+    ];
+    if not_modified {
+        (StatusCode::NOT_MODIFIED, headers).into_response()
+    } else {
+        (headers, svg).into_response()
     }
 }
 
-async fn api_force_update() -> impl IntoResponse {
-    let manager = match GitHubDataManager::new(GITHUB_USERNAME.to_string()) {
-        Ok(m) => m,
-        Err(e) => {
-            tracing::error!("GitHubDataManager 初始化失败: {}", e); // 添加日志
-            return Json(ApiResponse::<&str> {
-                success: false,
-                data: None,
-                message: format!("初始化失败: {}", e),
-                last_updated: None,
-            });
+// 分享卡片 PNG，内容不变时命中磁盘缓存
+async fn og_response(slot: String, card: card::Card, github_data: &github::GitHubData, headers: &HeaderMap) -> Result<Response, ApiError> {
+    let avatar_url = github_data.user_stats.as_ref().map(|stats| stats.avatar_url.clone());
+    let (key, png) = social::card_png(&slot, card, avatar_url).await.map_err(|e| {
+        tracing::error!("生成分享卡片失败: {}", e);
+        ApiError::internal("生成分享卡片失败")
+    })?;
+
+    let etag = format!("\"{}\"", key);
+    let response_headers = [
+        (header::CONTENT_TYPE, "image/png".to_string()),
+        (header::CACHE_CONTROL, "public, max-age=86400".to_string()),
+        (header::ETAG, etag.clone()),
+    ];
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"));
+    if not_modified {
+        Ok((StatusCode::NOT_MODIFIED, response_headers).into_response())
+    } else {
+        Ok((response_headers, png).into_response())
+    }
+}
+
+/// 读取 GitHub 数据（需要时自动刷新），失败原因只记日志，不返回给客户端
+async fn load_github_data() -> Result<github::GitHubData, ApiError> {
+    let manager = GitHubDataManager::new(GITHUB_USERNAME.to_string()).map_err(|e| {
+        tracing::error!("GitHubDataManager 初始化失败: {}", e);
+        ApiError::internal("数据管理器初始化失败")
+    })?;
+    manager.get_data().await.map_err(|e| {
+        tracing::error!("获取 GitHub 数据失败: {}", e);
+        ApiError::new(ErrorCode::DataUnavailable, "GitHub 数据暂不可用，请稍后重试")
+    })
+}
+
+async fn og_site(headers: HeaderMap) -> Result<Response, ApiError> {
+    let github_data = load_github_data().await?;
+    og_response(social::card_slot("site", ""), social::site_card(&github_data), &github_data, &headers).await
+}
+
+async fn og_article(Path(file): Path<String>, headers: HeaderMap) -> Result<Response, ApiError> {
+    let not_found = || ApiError::new(ErrorCode::ArticleNotFound, "文章不存在");
+    let id = file.strip_suffix(".png").ok_or_else(not_found)?;
+    let github_data = load_github_data().await?;
+    let article = github_data.articles.iter().find(|a| a.id == id).ok_or_else(not_found)?;
+    let slot = social::card_slot("article", &article.id);
+    og_response(slot, social::article_card(&github_data, article), &github_data, &headers).await
+}
+
+async fn og_project(Path(file): Path<String>, headers: HeaderMap) -> Result<Response, ApiError> {
+    let not_found = || ApiError::new(ErrorCode::ProjectNotFound, "项目不存在");
+    let name = file.strip_suffix(".png").ok_or_else(not_found)?;
+    let github_data = load_github_data().await?;
+    let project = github_data.projects.iter().find(|p| p.name == name).ok_or_else(not_found)?;
+    let slot = social::card_slot("project", &project.name);
+    og_response(slot, social::project_card(&github_data, project), &github_data, &headers).await
+}
+
+// === 页面处理函数 ===
+/// 就绪检查中渲染一遍不依赖 GitHub 数据的页面模板，返回渲染的页面数
+fn render_templates() -> Result<usize, askama::Error> {
+    let pages = [
+        IndexTemplate { meta: PageMeta::page(social::SITE_NAME, "/") }.render()?,
+        AboutTemplate { meta: PageMeta::page("关于我", "/about") }.render()?,
+        ProjectsTemplate { meta: PageMeta::page("我的项目", "/projects") }.render()?,
+        BlogTemplate { meta: PageMeta::page("技术博客", "/blog") }.render()?,
+        ContactTemplate { meta: PageMeta::page("联系我", "/contact") }.render()?,
+        ResumeTemplate { meta: PageMeta::page("简历", "/resume") }.render()?,
+        ToolsTemplate { meta: PageMeta::page("工具箱", "/tools") }.render()?,
+        ErrorTemplate {
+            meta: PageMeta::page("页面不存在", "/"),
+            status: 404,
+            heading: "页面不存在",
+            message: String::new(),
         }
-    };
+        .render()?,
+    ];
+    Ok(pages.len())
+}
 
-    match manager.force_update().await {
-        Ok(github_data) => Json(ApiResponse {
-            success: true,
-            data: Some("数据更新完成"),
-            message: format!(
-                "成功更新了 {} 个项目和 {} 篇文章",
-                github_data.projects.len(),
-                github_data.articles.len()
-            ),
-            last_updated: Some(github_data.last_updated.to_rfc3339()),
-        }),
+/// GET /readyz
+async fn readyz() -> Response {
+    let mut checks = vec![health::check_templates(render_templates())];
+    match GitHubDataManager::new(GITHUB_USERNAME.to_string()) {
+        Ok(manager) => {
+            checks.push(health::check_data_dir(&manager));
+            checks.push(health::check_github_data(&manager));
+        }
         Err(e) => {
-            tracing::error!("强制更新数据失败: {}", e); // 添加日志
-            Json(ApiResponse::<&str> {
-                success: false,
-                data: None,
-                message: format!("强制更新失败: {}", e),
-                last_updated: None,
-            })
+            tracing::error!("GitHubDataManager 初始化失败: {}", e);
+            checks.push(health::Check::failed("github_data", format!("数据管理器初始化失败: {}", e)));
         }
     }
+    checks.push(health::check_removebg());
+    health::readiness(checks)
 }
 
-// === 页面处理函数 ===
 async fn index() -> impl IntoResponse {
-    HtmlTemplate(IndexTemplate {})
+    HtmlTemplate(IndexTemplate {
+        meta: PageMeta::page(social::SITE_NAME, "/"),
+    })
 }
 
 async fn about() -> impl IntoResponse {
-    HtmlTemplate(AboutTemplate {})
+    HtmlTemplate(AboutTemplate {
+        meta: PageMeta::page("关于我", "/about"),
+    })
 }
 
 async fn projects() -> impl IntoResponse {
-    HtmlTemplate(ProjectsTemplate {})
+    HtmlTemplate(ProjectsTemplate {
+        meta: PageMeta::page("我的项目", "/projects"),
+    })
+}
+
+async fn project_detail(Path(name): Path<String>) -> Result<Response, ErrorPage> {
+    let mut github_data = load_github_data().await?;
+
+    let Some(index) = github_data.projects.iter().position(|p| p.name == name) else {
+        return Err(ErrorPage::not_found(format!("项目 {} 不存在或已不再展示", name)));
+    };
+    let project = github_data.projects.swap_remove(index);
+    let details = github_data.project_details.remove(&project.name).unwrap_or_default();
+
+    let base = markdown::RepoLinkBase {
+        full_name: &project.full_name,
+        branch: &project.default_branch,
+    };
+    let readme_html = details
+        .readme
+        .as_deref()
+        .map(|readme| markdown::render(readme, Some(&base)))
+        .unwrap_or_default();
+    let release_notes_html = details
+        .latest_release
+        .as_ref()
+        .and_then(|r| r.body.as_deref())
+        .map(|body| markdown::render(body, Some(&base)))
+        .unwrap_or_default();
+
+    Ok(HtmlTemplate(ProjectDetailTemplate {
+        meta: PageMeta::project(&project),
+        title: project.display_name.clone().unwrap_or_else(|| project.name.clone()),
+        project,
+        details,
+        readme_html,
+        release_notes_html,
+    })
+    .into_response())
 }
 
 async fn contact() -> impl IntoResponse {
-    HtmlTemplate(ContactTemplate {})
+    HtmlTemplate(ContactTemplate {
+        meta: PageMeta::page("联系我", "/contact"),
+    })
 }
 
 async fn resume() -> impl IntoResponse {
-    HtmlTemplate(ResumeTemplate {})
+    HtmlTemplate(ResumeTemplate {
+        meta: PageMeta::page("简历", "/resume"),
+    })
+}
+
+#[derive(Deserialize)]
+struct BlogQuery {
+    article: Option<String>,
+}
+
+// 带 ?article= 时输出该文章的分享元数据，页面加载后定位到对应文章
+async fn blog(Query(query): Query<BlogQuery>) -> impl IntoResponse {
+    let mut meta = PageMeta::page("技术博客", "/blog");
+    if let Some(id) = query.article
+        && let Ok(github_data) = load_github_data().await
+        && let Some(article) = github_data.articles.iter().find(|a| a.id == id)
+    {
+        meta = PageMeta::article(article);
+    }
+    HtmlTemplate(BlogTemplate { meta })
 }
 
-async fn blog() -> impl IntoResponse {
-    HtmlTemplate(BlogTemplate {})
+#[derive(Deserialize)]
+struct NotesQuery {
+    tag: Option<String>,
+}
+
+// 笔记页在服务端渲染 Markdown（Gist 中的原始 HTML 由 markdown::render 清理），可按 ?tag= 过滤
+async fn notes(Query(query): Query<NotesQuery>) -> Result<Response, ErrorPage> {
+    let github_data = load_github_data().await?;
+
+    let mut tags: Vec<String> = github_data.notes.iter().flat_map(|n| n.tags.iter().cloned()).collect();
+    tags.sort();
+    tags.dedup();
+
+    let notes = github_data
+        .notes
+        .into_iter()
+        .filter(|note| query.tag.as_ref().is_none_or(|tag| note.tags.contains(tag)))
+        .map(|note| NoteView {
+            html: markdown::render(&note.content, None),
+            note,
+        })
+        .collect();
+
+    Ok(HtmlTemplate(NotesTemplate {
+        meta: PageMeta::page("笔记", "/notes"),
+        notes,
+        tags,
+        active_tag: query.tag,
+    })
+    .into_response())
 }
 
 // 添加 tools 页面的处理函数
 async fn tools() -> impl IntoResponse {
-    HtmlTemplate(ToolsTemplate {})
+    HtmlTemplate(ToolsTemplate {
+        meta: PageMeta::page("工具箱", "/tools"),
+    })
+}
+
+// 找不到的路径：/api 下返回 JSON 错误，其余返回 404 页面
+async fn not_found(uri: Uri) -> Response {
+    if uri.path().starts_with("/api/") {
+        ApiError::new(ErrorCode::NotFound, format!("接口 {} 不存在", uri.path())).into_response()
+    } else {
+        ErrorPage::not_found("你访问的页面不存在，可能已被移动或删除").into_response()
+    }
 }
 
 // === Axum 响应转换器 ===
@@ -258,11 +610,62 @@ where
             Ok(html) => Html(html).into_response(),
             Err(err) => {
                 tracing::error!("模板渲染失败: {}", err); // 添加日志
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("渲染模板失败: {}", err),
-                )
-                    .into_response()
+                ErrorPage::internal().into_response()
+            }
+        }
+    }
+}
+
+/// 页面错误，渲染为带站点样式的错误页；API 错误可以直接用 ? 转换过来
+struct ErrorPage {
+    status: StatusCode,
+    message: String,
+}
+
+impl ErrorPage {
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.into(),
+        }
+    }
+
+    fn internal() -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: "页面渲染时出现问题，请稍后再试".to_string(),
+        }
+    }
+}
+
+impl From<ApiError> for ErrorPage {
+    fn from(error: ApiError) -> Self {
+        Self {
+            status: error.status(),
+            message: error.message,
+        }
+    }
+}
+
+impl IntoResponse for ErrorPage {
+    fn into_response(self) -> Response {
+        let heading = match self.status {
+            StatusCode::NOT_FOUND => "页面不存在",
+            StatusCode::SERVICE_UNAVAILABLE => "服务暂时不可用",
+            _ => "服务器出错了",
+        };
+        let template = ErrorTemplate {
+            meta: PageMeta::page(heading, "/"),
+            status: self.status.as_u16(),
+            heading,
+            message: self.message,
+        };
+        match template.render() {
+            Ok(html) => (self.status, Html(html)).into_response(),
+            // 错误页本身渲染失败时退回纯文本，不能再递归渲染模板
+            Err(err) => {
+                tracing::error!("错误页渲染失败: {}", err);
+                (self.status, heading).into_response()
             }
         }
     }
@@ -271,13 +674,9 @@ where
 // === 主函数: 程序入口 ===
 #[tokio::main]
 async fn main() {
-    // 初始化 tracing 日志
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()), // 默认为 info 级别
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // 初始化 tracing 日志（LOG_FORMAT=json 时输出 JSON）
+    telemetry::init();
+    health::init();
 
     // 从环境变量读取端口，默认为 8181
     let port = std::env::var("PORT")
@@ -285,8 +684,52 @@ async fn main() {
         .parse::<u16>()
         .unwrap_or(8181);
 
-    // 设置静态文件服务
-    let assets_service = ServeDir::new("public");
+    // --offline：不访问 GitHub，只使用本地缓存数据（本地开发和测试用）
+    if std::env::args().skip(1).any(|arg| arg == "--offline") {
+        GitHubConfig::set_offline(true);
+    }
+    if GitHubConfig::is_offline() {
+        tracing::warn!("离线模式已启用，不会访问 GitHub，所有数据来自本地缓存");
+    }
+
+    // 建立静态文件的哈希清单并生成预压缩文件
+    let summary = assets::prepare().await;
+    // --precompress：只生成预压缩文件后退出（public/ 在服务运行时只读时，部署时执行）
+    if std::env::args().skip(1).any(|arg| arg == "--precompress") {
+        println!(
+            "已扫描 {} 个静态文件，生成 {} 个预压缩文件，{} 个无法生成，{} 个已过期",
+            summary.files, summary.generated, summary.missing, summary.stale
+        );
+        std::process::exit(if summary.missing + summary.stale == 0 { 0 } else { 1 });
+    }
+    if summary.stale > 0 {
+        tracing::warn!("{} 个预压缩文件已过期且无法更新，已停用预压缩文件，请运行 xjz_website --precompress", summary.stale);
+    } else if summary.missing > 0 {
+        tracing::info!("{} 个预压缩文件无法生成，对应的静态文件将在请求时动态压缩", summary.missing);
+    }
+
+    let limits = ratelimit::limits();
+    if limits.enabled {
+        tracing::info!("已启用 {} 条限流规则", limits.rules.len());
+    } else {
+        tracing::warn!("限流已关闭");
+    }
+    tracing::info!("信任 {} 个代理地址段的转发请求头", client_ip::trusted_proxies().len());
+
+    // 设置静态文件服务，找不到文件时返回 404 页面
+    let assets_service = ServiceBuilder::new()
+        .layer(SetResponseHeaderLayer::if_not_present(header::CACHE_CONTROL, assets::static_cache_control))
+        .layer(SetResponseHeaderLayer::if_not_present(header::VARY, assets::vary()))
+        .service(assets::static_dir().not_found_service(not_found.into_service()));
+
+    // 动态压缩：已经压缩过的图片格式不再压缩，SVG 徽章和热力图照常压缩
+    let compress_when = SizeAbove::new(256)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::SSE)
+        .and(NotForContentType::const_new("image/png"))
+        .and(NotForContentType::const_new("image/jpeg"))
+        .and(NotForContentType::const_new("image/webp"))
+        .and(NotForContentType::const_new("image/gif"));
 
     // 创建应用路由
     let app = Router::new()
@@ -294,35 +737,155 @@ async fn main() {
         .route("/", get(index))
         .route("/about", get(about))
         .route("/projects", get(projects))
+        .route("/projects/{name}", get(project_detail))
         .route("/blog", get(blog))
+        .route("/notes", get(notes))
         .route("/contact", get(contact))
         .route("/resume", get(resume))
         .route("/tools", get(tools)) // 添加 tools 页面路由
         // GitHub API 路由
         .route("/api/projects", get(api_projects))
+        .route("/api/projects/{name}/releases", get(api_project_releases))
+        .route("/api/releases", get(api_recent_releases))
         .route("/api/articles", get(api_articles))
+        .route("/api/notes", get(api_notes))
         .route("/api/stats", get(api_stats))
-        .route("/api/update", get(api_force_update))
+        .route("/api/update", post(api_force_update))
+        .route("/api/update/jobs/{id}", get(api_update_status))
+        .route("/api/update/history", get(api_update_history))
+        .route("/api/activity", get(api_activity))
+        .route("/api/activity/contributions.svg", get(api_contributions_svg))
+        .route("/badges/{repo}/{metric}", get(project_badge))
+        .route("/og/site.png", get(og_site))
+        .route("/og/articles/{file}", get(og_article))
+        .route("/og/projects/{file}", get(og_project))
+        // 推送事件可能超过 axum 默认的 2 MB 请求体上限，超出时返回 413，增量刷新不会执行
+        .route(
+            "/webhooks/github",
+            post(handle_github_webhook).layer(DefaultBodyLimit::max(github::webhook::MAX_PAYLOAD_BYTES)),
+        )
+        .route(security::REPORT_PATH, post(security::csp_report))
+        .route("/metrics", get(metrics::handler))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(readyz))
+        .route("/assets/{hash}/{*path}", get(assets::hashed_asset))
         // 工具 API 路由
         .route("/tools/resize-image", post(handle_resize_image)) // 图片大小调整
         .route("/tools/change-background", post(handle_change_background)) // 背景更换
         .route("/api/tools/my-ip", get(handle_get_ip)) // IP 查询
         .route("/api/tools/fake-identity", get(handle_get_fake_identity)) // <-- 新增 虚假身份生成
         // 静态文件服务 (放在最后作为 fallback)
-        .fallback_service(assets_service);
-
-    // 绑定端口并启动服务
-    let addr = SocketAddr::from(([0, 0, 0, 0], port)); // 监听所有接口 0.0.0.0
-    tracing::info!("🚀 服务已启动，监听地址 http://{}", addr); // 使用 tracing info! 宏
+        .fallback_service(assets_service)
+        .with_state(AppState::default())
+        .layer(middleware::from_fn(ratelimit::limit))
+        // 在限流之外，429 也计入请求数
+        .layer(middleware::from_fn(metrics::track))
+        .layer(middleware::from_fn(cache::etag))
+        .layer(CompressionLayer::new().compress_when(compress_when))
+        .layer(middleware::from_fn(security::headers))
+        // 请求 ID 和访问日志，放在最外层以覆盖上面所有中间件的日志
+        .layer(
+            ServiceBuilder::new()
+                .layer(middleware::from_fn(telemetry::sanitize_request_id))
+                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(telemetry::make_span)
+                        .on_request(())
+                        .on_response(telemetry::on_response)
+                        .on_failure(telemetry::on_failure),
+                )
+                .layer(PropagateRequestIdLayer::x_request_id()),
+        );
 
-    let listener = match TcpListener::bind(&addr).await {
-        Ok(listener) => listener,
+    // 配置了证书时主端口使用 HTTPS
+    let tls = match server::tls_acceptor() {
+        Ok(tls) => tls,
         Err(e) => {
-            tracing::error!("❌ 无法绑定端口 {}: {}", port, e); // 使用 tracing error! 宏
+            tracing::error!("❌ 无法启用 HTTPS: {}", e);
             return;
         }
     };
-    if let Err(e) = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await {
-        tracing::error!("服务器运行出错: {}", e); // 添加服务器运行错误日志
+
+    // 优先使用 systemd 套接字激活传入的监听套接字，否则自己绑定端口
+    let listener = match lifecycle::activated_listener() {
+        Some(listener) => listener,
+        None => {
+            let addr = SocketAddr::from(([0, 0, 0, 0], port)); // 监听所有接口 0.0.0.0
+            match TcpListener::bind(&addr).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::error!("❌ 无法绑定端口 {}: {}", port, e); // 使用 tracing error! 宏
+                    return;
+                }
+            }
+        }
+    };
+    let Ok(local_addr) = listener.local_addr() else {
+        tracing::error!("❌ 无法获取监听地址");
+        return;
+    };
+    let mut servers = Vec::new();
+    let mut addresses = Vec::new();
+    match tls {
+        Some(acceptor) => {
+            let listener = match server::TlsListener::new(listener, acceptor) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::error!("❌ 无法启动 HTTPS 监听: {}", e);
+                    return;
+                }
+            };
+            addresses.push(format!("https://{}", local_addr));
+            servers.push(tokio::spawn(server::serve(listener, app.clone(), "HTTPS 服务".to_string())));
+
+            // HTTP→HTTPS 跳转
+            if let Some(redirect_port) = server::ListenConfig::get_redirect_port() {
+                let addr = SocketAddr::from(([0, 0, 0, 0], redirect_port));
+                match TcpListener::bind(&addr).await {
+                    Ok(redirect) => {
+                        addresses.push(format!("http://{}（跳转到 HTTPS）", addr));
+                        let app = server::redirect_app(local_addr.port());
+                        servers.push(tokio::spawn(server::serve(redirect, app, "HTTP 跳转服务".to_string())));
+                    }
+                    Err(e) => tracing::error!("❌ 无法绑定跳转端口 {}: {}", redirect_port, e),
+                }
+            }
+        }
+        None => {
+            addresses.push(format!("http://{}", local_addr));
+            servers.push(tokio::spawn(server::serve(listener, app.clone(), "HTTP 服务".to_string())));
+        }
+    }
+    // 供同机反向代理使用的 Unix 套接字
+    if let Some(path) = server::ListenConfig::get_unix_socket() {
+        match server::UnixSocketListener::bind(&path) {
+            Ok(unix) => {
+                addresses.push(format!("unix:{}", unix.path().display()));
+                servers.push(tokio::spawn(server::serve(unix, app, "Unix 套接字服务".to_string())));
+            }
+            Err(e) => tracing::error!("❌ 无法监听 Unix 套接字 {}: {}", path.display(), e),
+        }
+    }
+
+    let addresses = addresses.join(", ");
+    tracing::info!("🚀 服务已启动，监听地址 {}", addresses); // 使用 tracing info! 宏
+    lifecycle::notify_ready(&format!("监听 {}", addresses));
+    lifecycle::spawn_watchdog();
+
+    // 收到 SIGTERM / SIGINT 后所有监听一起停止，等待进行中的请求完成，超时后直接退出
+    tokio::spawn(lifecycle::shutdown_signal());
+    let timeout = lifecycle::LifecycleConfig::get_shutdown_timeout();
+    tokio::select! {
+        _ = async {
+            for server in servers {
+                let _ = server.await;
+            }
+        } => tracing::info!("所有连接已结束，服务已停止"),
+        _ = lifecycle::drain_deadline(timeout) => {
+            tracing::warn!("等待进行中的请求超过 {} 秒，强制退出", timeout.as_secs());
+        }
     }
+    // 被取消的刷新很快结束，等它们写完刷新报告
+    lifecycle::wait_for_background(timeout).await;
 }
\ No newline at end of file
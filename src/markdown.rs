@@ -0,0 +1,142 @@
+// Markdown 渲染管线
+//
+// README 和文章共用同一套渲染逻辑：启用 GFM 常用扩展，
+// 并把仓库内的相对链接/图片改写为 GitHub 上的绝对地址。
+// README、Release 说明和 Gist 来自第三方，其中的原始 HTML 经 ammonia 清理后才输出，
+// 模板中才能用 |safe 直接插入。
+
+use ammonia::Builder;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// 仓库内相对路径的解析基准
+pub struct RepoLinkBase<'a> {
+    pub full_name: &'a str,
+    pub branch: &'a str,
+}
+
+impl RepoLinkBase<'_> {
+    fn resolve_link(&self, url: &str) -> Option<String> {
+        is_relative(url).then(|| format!(
+            "https://github.com/{}/blob/{}/{}",
+            self.full_name, self.branch, url.trim_start_matches("./").trim_start_matches('/')
+        ))
+    }
+
+    fn resolve_image(&self, url: &str) -> Option<String> {
+        is_relative(url).then(|| format!(
+            "https://raw.githubusercontent.com/{}/{}/{}",
+            self.full_name, self.branch, url.trim_start_matches("./").trim_start_matches('/')
+        ))
+    }
+}
+
+fn is_relative(url: &str) -> bool {
+    !(url.is_empty()
+        || url.starts_with('#')
+        || url.starts_with("//")
+        || url.starts_with("data:")
+        || url.starts_with("mailto:")
+        || url.contains("://"))
+}
+
+/// HTML 白名单：在 ammonia 默认规则（去掉 script、事件属性和 javascript: 链接等）的基础上，
+/// 保留 pulldown-cmark 自身输出需要的任务列表复选框、代码语言和脚注的 class / id
+fn sanitizer() -> &'static Builder<'static> {
+    static SANITIZER: OnceLock<Builder<'static>> = OnceLock::new();
+    SANITIZER.get_or_init(|| {
+        let mut builder = Builder::default();
+        builder
+            .add_tags(["input"])
+            .add_tag_attributes("input", ["type", "checked", "disabled"])
+            .add_tag_attributes("code", ["class"])
+            .add_tag_attributes("sup", ["class"])
+            .add_tag_attributes("div", ["class", "id"])
+            .add_tag_attributes("p", ["align"])
+            .set_tag_attribute_value("input", "disabled", "")
+            .attribute_filter(|element, attribute, value| match (element, attribute) {
+                // 只允许只读的复选框
+                ("input", "type") => (value == "checkbox").then_some(value.into()),
+                _ => Some(value.into()),
+            })
+            .url_schemes(HashSet::from(["http", "https", "mailto"]));
+        builder
+    })
+}
+
+/// 将 Markdown 渲染为 HTML，原始 HTML 经过白名单清理
+pub fn render(markdown: &str, base: Option<&RepoLinkBase>) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    let parser = Parser::new_ext(markdown, options).map(|event| match (event, base) {
+        (Event::Start(Tag::Link { link_type, dest_url, title, id }), Some(base)) => {
+            let dest_url = base.resolve_link(&dest_url).map(CowStr::from).unwrap_or(dest_url);
+            Event::Start(Tag::Link { link_type, dest_url, title, id })
+        }
+        (Event::Start(Tag::Image { link_type, dest_url, title, id }), Some(base)) => {
+            let dest_url = base.resolve_image(&dest_url).map(CowStr::from).unwrap_or(dest_url);
+            Event::Start(Tag::Image { link_type, dest_url, title, id })
+        }
+        (event, _) => event,
+    });
+
+    let mut output = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut output, parser);
+    sanitizer().clean(&output).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_scripts_and_event_handlers() {
+        let markdown = "# 标题\n\n<script>alert(1)</script>\n\n<img src=\"x.png\" onerror=\"alert(2)\">\n\n\
+                        行内 <span onclick=\"alert(3)\">文字</span> <a href=\"javascript:alert(4)\">链接</a>\n";
+        let html = render(markdown, None);
+        assert!(!html.contains("<script"), "{}", html);
+        assert!(!html.contains("alert(1)"), "{}", html);
+        assert!(!html.contains("onerror"), "{}", html);
+        assert!(!html.contains("onclick"), "{}", html);
+        assert!(!html.contains("javascript:"), "{}", html);
+        assert!(html.contains("<h1>标题</h1>"));
+        assert!(html.contains("<img src=\"x.png\">"), "{}", html);
+    }
+
+    #[test]
+    fn sanitizes_gist_notes() {
+        // 笔记页渲染 Gist 时没有仓库基准，同样经过清理
+        let markdown = "<iframe src=\"https://evil.example\"></iframe>\n\n<style>body{display:none}</style>\n\n\
+                        <svg onload=\"alert(1)\"></svg>\n\n<form action=\"/api/update\"><button>提交</button></form>\n\n\
+                        [点我](JavaScript:alert(2)) <a href=\"data:text/html,x\">数据</a>\n";
+        let html = render(markdown, None);
+        for forbidden in ["<iframe", "<style", "<svg", "onload", "<form", "<button", "alert(", "data:text"] {
+            assert!(!html.to_lowercase().contains(&forbidden.to_lowercase()), "{} 出现在 {}", forbidden, html);
+        }
+        assert!(html.contains("点我"));
+    }
+
+    #[test]
+    fn keeps_markdown_output() {
+        let markdown = "- [x] 完成\n- [ ] 未完成\n\n```rust\nfn main() {}\n```\n\n脚注[^1]\n\n[^1]: 说明\n\n\
+                        | a | b |\n|---|---|\n| 1 | 2 |\n";
+        let html = render(markdown, None);
+        assert!(html.contains("<input disabled=\"\" type=\"checkbox\" checked=\"\">"), "{}", html);
+        assert!(html.contains("<code class=\"language-rust\">"), "{}", html);
+        assert!(html.contains("<div class=\"footnote-definition\" id=\"1\">"), "{}", html);
+        assert!(html.contains("<table>"), "{}", html);
+    }
+
+    #[test]
+    fn resolves_relative_links_before_sanitizing() {
+        let base = RepoLinkBase { full_name: "xjz6626/xjz-website", branch: "main" };
+        let html = render("[文档](docs/a.md) ![图](./logo.png) [外链](https://example.com)", Some(&base));
+        assert!(html.contains("href=\"https://github.com/xjz6626/xjz-website/blob/main/docs/a.md\""), "{}", html);
+        assert!(html.contains("src=\"https://raw.githubusercontent.com/xjz6626/xjz-website/main/logo.png\""), "{}", html);
+        assert!(html.contains("href=\"https://example.com\""), "{}", html);
+    }
+}
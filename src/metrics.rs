@@ -0,0 +1,277 @@
+// Prometheus 指标，由 GET /metrics 以文本格式输出
+//
+// 请求数和耗时按路由模板（如 /projects/{name}）统计，静态文件和 404 归入 fallback，避免标签数量无限增长。
+// 工具、remove.bg、GitHub API 和刷新的指标在对应的代码处记录；缓存数据的年龄在抓取时计算。
+// /metrics 默认拒绝所有请求：经本机反向代理或 Unix 套接字转发的请求都来自本机地址，不能据此放行。
+// 配置 METRICS_ALLOW（按 client_ip.rs 解析出的客户端真实 IP 判断）或 METRICS_TOKEN（Bearer Token）后才可访问。
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use ipnet::IpNet;
+use prometheus::{
+    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::api::{ApiError, ErrorCode};
+use crate::client_ip::{self, ClientIp};
+use crate::github::config::GitHubConfig;
+use crate::github::GitHubDataManager;
+use crate::update::constant_time_eq;
+
+const NAMESPACE: &str = "xjz";
+/// 请求耗时的分桶（秒），图片工具可能需要数秒
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+/// 刷新耗时的分桶（秒）
+const REFRESH_BUCKETS: &[f64] = &[1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+pub struct MetricsConfig;
+
+impl MetricsConfig {
+    /// 允许访问 /metrics 的地址（METRICS_ALLOW），格式同 TRUSTED_PROXIES，默认 none（只能用令牌访问）
+    pub fn get_allowed() -> Vec<IpNet> {
+        let value = std::env::var("METRICS_ALLOW").unwrap_or_else(|_| "none".to_string());
+        client_ip::parse_networks("METRICS_ALLOW", &value)
+    }
+
+    /// 访问令牌（METRICS_TOKEN），配置后带 Authorization: Bearer <token> 的请求不受地址限制
+    pub fn get_token() -> Option<String> {
+        std::env::var("METRICS_TOKEN").ok().filter(|token| !token.trim().is_empty())
+    }
+}
+
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_duration: HistogramVec,
+    pub tool_requests: IntCounterVec,
+    pub image_bytes: IntCounterVec,
+    pub removebg_calls: IntCounterVec,
+    pub github_api_calls: IntCounter,
+    pub github_rate_limit_remaining: IntGauge,
+    pub refreshes: IntCounterVec,
+    pub refresh_duration: HistogramVec,
+    pub last_refresh_success: Gauge,
+    pub data_age: Gauge,
+}
+
+impl Metrics {
+    fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new_custom(Some(NAMESPACE.to_string()), None)?;
+        let metrics = Self {
+            http_requests: IntCounterVec::new(
+                Opts::new("http_requests_total", "HTTP 请求数"),
+                &["method", "route", "status"],
+            )?,
+            http_duration: HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "HTTP 请求耗时").buckets(LATENCY_BUCKETS.to_vec()),
+                &["method", "route"],
+            )?,
+            tool_requests: IntCounterVec::new(Opts::new("tool_requests_total", "工具调用次数"), &["tool"])?,
+            image_bytes: IntCounterVec::new(
+                Opts::new("image_bytes_processed_total", "工具处理的图片字节数，in 为上传，out 为返回"),
+                &["tool", "direction"],
+            )?,
+            removebg_calls: IntCounterVec::new(
+                Opts::new("removebg_calls_total", "remove.bg API 调用结果"),
+                &["outcome"],
+            )?,
+            github_api_calls: IntCounter::new("github_api_calls_total", "GitHub API 调用次数（不含 raw 文件下载）")?,
+            github_rate_limit_remaining: IntGauge::new(
+                "github_rate_limit_remaining",
+                "最近一次 GitHub API 响应中的剩余额度，-1 表示尚未调用",
+            )?,
+            refreshes: IntCounterVec::new(
+                Opts::new("github_refreshes_total", "GitHub 数据刷新次数"),
+                &["trigger", "result"],
+            )?,
+            refresh_duration: HistogramVec::new(
+                HistogramOpts::new("github_refresh_duration_seconds", "GitHub 数据刷新耗时")
+                    .buckets(REFRESH_BUCKETS.to_vec()),
+                &["trigger"],
+            )?,
+            last_refresh_success: Gauge::new(
+                "github_last_refresh_success_timestamp_seconds",
+                "最近一次刷新成功的时间（Unix 秒），本次启动后尚未成功时为 0",
+            )?,
+            data_age: Gauge::new("github_data_age_seconds", "本地缓存的 GitHub 数据距上次更新的秒数，没有缓存时为 -1")?,
+            registry,
+        };
+        metrics.github_rate_limit_remaining.set(-1);
+
+        let collectors: Vec<Box<dyn prometheus::core::Collector>> = vec![
+            Box::new(metrics.http_requests.clone()),
+            Box::new(metrics.http_duration.clone()),
+            Box::new(metrics.tool_requests.clone()),
+            Box::new(metrics.image_bytes.clone()),
+            Box::new(metrics.removebg_calls.clone()),
+            Box::new(metrics.github_api_calls.clone()),
+            Box::new(metrics.github_rate_limit_remaining.clone()),
+            Box::new(metrics.refreshes.clone()),
+            Box::new(metrics.refresh_duration.clone()),
+            Box::new(metrics.last_refresh_success.clone()),
+            Box::new(metrics.data_age.clone()),
+        ];
+        for collector in collectors {
+            metrics.registry.register(collector)?;
+        }
+        Ok(metrics)
+    }
+
+    /// 记录一次工具调用和上传的图片大小
+    pub fn tool_used(&self, tool: &str, input_bytes: usize) {
+        self.tool_requests.with_label_values(&[tool]).inc();
+        self.image_bytes.with_label_values(&[tool, "in"]).inc_by(input_bytes as u64);
+    }
+
+    pub fn tool_output(&self, tool: &str, output_bytes: usize) {
+        self.image_bytes.with_label_values(&[tool, "out"]).inc_by(output_bytes as u64);
+    }
+}
+
+/// 全局指标，第一次使用时注册
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("注册 Prometheus 指标失败"))
+}
+
+/// 统计请求数和耗时的中间件
+pub async fn track(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "fallback".to_string());
+
+    let response = next.run(request).await;
+
+    let metrics = metrics();
+    metrics
+        .http_requests
+        .with_label_values(&[method.as_str(), route.as_str(), response.status().as_str()])
+        .inc();
+    metrics
+        .http_duration
+        .with_label_values(&[method.as_str(), route.as_str()])
+        .observe(start.elapsed().as_secs_f64());
+    response
+}
+
+/// 客户端 IP 在 allowed 中，或带有正确的 Bearer Token 时放行
+fn check_access(ip: IpAddr, headers: &HeaderMap, allowed: &[IpNet], token: Option<&str>) -> Result<(), ApiError> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let token_ok = matches!((token, bearer), (Some(token), Some(bearer)) if constant_time_eq(bearer.trim().as_bytes(), token.as_bytes()));
+    let ip_ok = allowed.iter().any(|network| network.contains(&ip));
+    if token_ok || ip_ok {
+        return Ok(());
+    }
+
+    tracing::warn!("拒绝来自 {} 的 /metrics 请求", ip);
+    Err(if token.is_some() {
+        ApiError::new(ErrorCode::Unauthorized, "未授权")
+    } else if allowed.is_empty() {
+        ApiError::new(ErrorCode::Forbidden, "未配置 METRICS_ALLOW 或 METRICS_TOKEN，/metrics 不对外开放")
+    } else {
+        ApiError::new(ErrorCode::Forbidden, "不允许从该地址访问")
+    })
+}
+
+/// GET /metrics
+pub async fn handler(ClientIp(ip): ClientIp, headers: HeaderMap) -> Result<Response, ApiError> {
+    check_access(ip, &headers, &MetricsConfig::get_allowed(), MetricsConfig::get_token().as_deref())?;
+
+    let metrics = metrics();
+    let age = GitHubDataManager::new(GitHubConfig::get_username())
+        .ok()
+        .and_then(|manager| manager.cached_last_updated())
+        .map(|last_updated| (Utc::now() - last_updated).num_milliseconds() as f64 / 1000.0)
+        .unwrap_or(-1.0);
+    metrics.data_age.set(age);
+
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    if let Err(e) = encoder.encode(&metrics.registry.gather(), &mut body) {
+        tracing::error!("生成指标失败: {}", e);
+        return Err(ApiError::internal("生成指标失败"));
+    }
+    Ok(([(header::CONTENT_TYPE, encoder.format_type().to_string())], body).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderValue, StatusCode};
+
+    fn status(result: Result<(), ApiError>) -> StatusCode {
+        match result {
+            Ok(()) => StatusCode::OK,
+            Err(e) => e.into_response().status(),
+        }
+    }
+
+    fn loopback() -> Vec<IpNet> {
+        client_ip::parse_networks("METRICS_ALLOW", "loopback")
+    }
+
+    #[test]
+    fn default_denies_everyone() {
+        let allowed = client_ip::parse_networks("METRICS_ALLOW", "none");
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        assert_eq!(status(check_access(ip, &HeaderMap::new(), &allowed, None)), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn proxied_request_is_judged_by_forwarded_client() {
+        // 经本机的可信代理转发，连接地址是 127.0.0.1，真实客户端在 X-Forwarded-For 中
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7"));
+        let (ip, _) = client_ip::resolve(&headers, "127.0.0.1".parse().unwrap());
+        assert_eq!(ip, "203.0.113.7".parse::<IpAddr>().unwrap());
+        assert_eq!(status(check_access(ip, &headers, &loopback(), None)), StatusCode::FORBIDDEN);
+
+        let (ip, _) = client_ip::resolve(&HeaderMap::new(), "127.0.0.1".parse().unwrap());
+        assert_eq!(status(check_access(ip, &HeaderMap::new(), &loopback(), None)), StatusCode::OK);
+    }
+
+    #[test]
+    fn bearer_token_allows_any_address() {
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer s3cret"));
+        assert_eq!(status(check_access(ip, &headers, &[], Some("s3cret"))), StatusCode::OK);
+        assert_eq!(status(check_access(ip, &headers, &[], Some("other"))), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(check_access(ip, &HeaderMap::new(), &[], Some("s3cret"))), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn tool_calls_increment_counters() {
+        // 单独的注册表，不受其他测试对全局指标的影响
+        let metrics = Metrics::new().unwrap();
+        metrics.tool_used("image_resizer", 1000);
+        metrics.tool_used("image_resizer", 500);
+        metrics.tool_output("image_resizer", 300);
+        metrics.tool_used("background_changer", 10);
+
+        assert_eq!(metrics.tool_requests.with_label_values(&["image_resizer"]).get(), 2);
+        assert_eq!(metrics.tool_requests.with_label_values(&["background_changer"]).get(), 1);
+        assert_eq!(metrics.image_bytes.with_label_values(&["image_resizer", "in"]).get(), 1500);
+        assert_eq!(metrics.image_bytes.with_label_values(&["image_resizer", "out"]).get(), 300);
+
+        let mut body = Vec::new();
+        TextEncoder::new().encode(&metrics.registry.gather(), &mut body).unwrap();
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.contains("xjz_tool_requests_total{tool=\"image_resizer\"} 2"), "{}", body);
+    }
+}
@@ -0,0 +1,347 @@
+// 按客户端 IP 的请求限流（令牌桶）
+//
+// 客户端 IP 见 client_ip.rs。规则从 config/rate_limits.json 读取（路径可用 RATE_LIMITS_FILE 覆盖），文件不存在时使用内置规则。
+// 每条规则按路径匹配，每个客户端在每条规则下有一个令牌桶：桶容量为 burst，按 requests / period_secs 的速度补充。
+// IPv6 客户端按 /64 前缀计数，同一前缀下的地址通常属于同一用户，可以随意更换。
+// 命中规则的响应带有 RateLimit-Limit / RateLimit-Remaining / RateLimit-Reset / RateLimit-Policy，
+// 令牌用完时返回 429 和 Retry-After。
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, Ipv6Addr};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::api::{ApiError, ErrorCode};
+use crate::client_ip;
+
+/// 限流规则文件的默认路径
+const RATE_LIMITS_FILE: &str = "config/rate_limits.json";
+/// 清理已补满的令牌桶的间隔
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 一条限流规则
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitRule {
+    /// 请求路径，以 * 结尾时按前缀匹配，如 /api/*
+    pub path: String,
+    /// 每个周期补充的请求数
+    pub requests: u32,
+    /// 周期（秒）
+    pub period_secs: u64,
+    /// 允许的突发请求数（桶容量），默认等于 requests
+    #[serde(default)]
+    pub burst: Option<u32>,
+}
+
+impl RateLimitRule {
+    fn new(path: &str, requests: u32, period_secs: u64, burst: u32) -> Self {
+        Self {
+            path: path.to_string(),
+            requests,
+            period_secs,
+            burst: Some(burst),
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        match self.path.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == self.path,
+        }
+    }
+
+    fn capacity(&self) -> f64 {
+        f64::from(self.burst.unwrap_or(self.requests).max(1))
+    }
+
+    /// 每秒补充的令牌数
+    fn refill_rate(&self) -> f64 {
+        f64::from(self.requests.max(1)) / self.period_secs.max(1) as f64
+    }
+}
+
+/// 限流配置，规则按顺序匹配，第一条命中的生效
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimits {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub rules: Vec<RateLimitRule>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: vec![
+                // 每次调用都消耗 remove.bg 的付费额度
+                RateLimitRule::new("/tools/change-background", 10, 3600, 3),
+                // 反复压缩 JPEG，CPU 开销大
+                RateLimitRule::new("/tools/resize-image", 20, 60, 5),
+                RateLimitRule::new("/api/tools/*", 60, 60, 20),
+                RateLimitRule::new("/csp-report", 30, 60, 30),
+                RateLimitRule::new("/api/*", 120, 60, 60),
+            ],
+        }
+    }
+}
+
+pub struct RateLimitConfig;
+
+impl RateLimitConfig {
+    /// 读取限流规则，路径可通过 RATE_LIMITS_FILE 环境变量覆盖，文件不存在时使用内置规则
+    pub fn load() -> RateLimits {
+        let path = std::env::var("RATE_LIMITS_FILE").unwrap_or_else(|_| RATE_LIMITS_FILE.to_string());
+
+        if !Path::new(&path).exists() {
+            return RateLimits::default();
+        }
+
+        match fs::read_to_string(&path).map(|content| serde_json::from_str::<RateLimits>(&content)) {
+            Ok(Ok(limits)) => limits,
+            Ok(Err(e)) => {
+                tracing::warn!("限流规则文件 {} 解析失败，使用内置规则: {}", path, e);
+                RateLimits::default()
+            }
+            Err(e) => {
+                tracing::warn!("读取限流规则文件 {} 失败，使用内置规则: {}", path, e);
+                RateLimits::default()
+            }
+        }
+    }
+}
+
+/// 当前生效的限流规则，第一次使用时加载
+pub fn limits() -> &'static RateLimits {
+    static LIMITS: OnceLock<RateLimits> = OnceLock::new();
+    LIMITS.get_or_init(RateLimitConfig::load)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(rule: &RateLimitRule, now: Instant) -> Self {
+        Self {
+            tokens: rule.capacity(),
+            updated: now,
+        }
+    }
+
+    /// 补充从上次更新到 now 的令牌，再尝试取走一个
+    fn take(&mut self, rule: &RateLimitRule, now: Instant) -> Decision {
+        let capacity = rule.capacity();
+        let rate = rule.refill_rate();
+        self.tokens = (self.tokens + now.duration_since(self.updated).as_secs_f64() * rate).min(capacity);
+        self.updated = now;
+
+        let allowed = self.tokens >= 1.0;
+        if allowed {
+            self.tokens -= 1.0;
+        }
+        Decision {
+            allowed,
+            remaining: self.tokens.floor() as u32,
+            reset: ((capacity - self.tokens) / rate).ceil() as u64,
+            retry_after: if allowed { 0 } else { ((1.0 - self.tokens) / rate).ceil() as u64 },
+        }
+    }
+}
+
+/// 令牌桶按客户端地址计数；IPv6 地址只取前 64 位，IPv4 映射地址按 IPv4 处理
+fn bucket_key(client: IpAddr) -> IpAddr {
+    match client.to_canonical() {
+        IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from_bits(ip.to_bits() & !0u128 << 64)),
+        ip => ip,
+    }
+}
+
+#[derive(Default)]
+struct Buckets {
+    /// (规则序号, 客户端 IP 或 IPv6 /64 前缀) -> 令牌桶
+    buckets: HashMap<(usize, IpAddr), Bucket>,
+    last_prune: Option<Instant>,
+}
+
+static BUCKETS: OnceLock<Mutex<Buckets>> = OnceLock::new();
+
+/// 一次取令牌的结果
+struct Decision {
+    allowed: bool,
+    remaining: u32,
+    /// 桶补满还需的秒数
+    reset: u64,
+    /// 被拒绝时，下一个令牌可用还需的秒数
+    retry_after: u64,
+}
+
+fn take(rule_index: usize, rule: &RateLimitRule, client: IpAddr) -> Decision {
+    let now = Instant::now();
+    let mut state = BUCKETS.get_or_init(Default::default).lock().unwrap();
+
+    // 定期清理已经补满的桶，它们和新建的桶没有区别
+    if state.last_prune.is_none_or(|at| now.duration_since(at) >= PRUNE_INTERVAL) {
+        let rules = &limits().rules;
+        state.buckets.retain(|(index, _), bucket| {
+            let rule = &rules[*index];
+            bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rule.refill_rate() < rule.capacity()
+        });
+        state.last_prune = Some(now);
+    }
+
+    state
+        .buckets
+        .entry((rule_index, bucket_key(client)))
+        .or_insert_with(|| Bucket::full(rule, now))
+        .take(rule, now)
+}
+
+fn set_rate_limit_headers(headers: &mut HeaderMap, rule: &RateLimitRule, decision: &Decision) {
+    let values = [
+        ("ratelimit-limit", rule.capacity().to_string()),
+        ("ratelimit-remaining", decision.remaining.to_string()),
+        ("ratelimit-reset", decision.reset.to_string()),
+        (
+            "ratelimit-policy",
+            format!("{};w={};burst={}", rule.requests, rule.period_secs, rule.capacity()),
+        ),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+}
+
+/// 限流中间件，没有命中规则的请求直接放行
+pub async fn limit(request: Request, next: Next) -> Response {
+    let limits = limits();
+    if !limits.enabled {
+        return next.run(request).await;
+    }
+    let path = request.uri().path();
+    let Some((index, rule)) = limits.rules.iter().enumerate().find(|(_, rule)| rule.matches(path)) else {
+        return next.run(request).await;
+    };
+    let Some((client, source)) = client_ip::of(&request) else {
+        return next.run(request).await;
+    };
+
+    let decision = take(index, rule, client);
+    if !decision.allowed {
+        tracing::warn!("请求过于频繁，已限流: {} (来自 {}) {} (规则 {})", client, source.as_str(), path, rule.path);
+        let mut response = ApiError::new(ErrorCode::RateLimited, "请求过于频繁，请稍后再试")
+            .with_retry_after(decision.retry_after)
+            .into_response();
+        set_rate_limit_headers(response.headers_mut(), rule, &decision);
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    set_rate_limit_headers(response.headers_mut(), rule, &decision);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(bucket: &mut Bucket, rule: &RateLimitRule, now: Instant) -> u32 {
+        let mut allowed = 0;
+        while bucket.take(rule, now).allowed {
+            allowed += 1;
+        }
+        allowed
+    }
+
+    #[test]
+    fn burst_is_exhausted_then_rejected() {
+        let rule = RateLimitRule::new("/api/*", 60, 60, 5);
+        let now = Instant::now();
+        let mut bucket = Bucket::full(&rule, now);
+
+        let first = bucket.take(&rule, now);
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 4);
+        assert_eq!(first.reset, 1);
+        assert_eq!(drain(&mut bucket, &rule, now), 4);
+
+        let rejected = bucket.take(&rule, now);
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.remaining, 0);
+        assert_eq!(rejected.reset, 5);
+    }
+
+    #[test]
+    fn tokens_refill_over_time_up_to_burst() {
+        // 每 10 秒补充一个令牌
+        let rule = RateLimitRule::new("/api/*", 6, 60, 3);
+        let start = Instant::now();
+        let mut bucket = Bucket::full(&rule, start);
+        assert_eq!(drain(&mut bucket, &rule, start), 3);
+
+        assert!(!bucket.take(&rule, start + Duration::from_secs(9)).allowed);
+        assert!(bucket.take(&rule, start + Duration::from_secs(10)).allowed);
+        assert!(!bucket.take(&rule, start + Duration::from_secs(10)).allowed);
+
+        // 很久之后也只补满到桶容量
+        let later = start + Duration::from_secs(3600);
+        assert_eq!(drain(&mut bucket, &rule, later), 3);
+    }
+
+    #[test]
+    fn retry_after_is_time_until_next_token() {
+        // remove.bg 的规则：每小时 10 次，每 360 秒补充一个令牌
+        let rule = RateLimitRule::new("/tools/change-background", 10, 3600, 3);
+        let start = Instant::now();
+        let mut bucket = Bucket::full(&rule, start);
+        drain(&mut bucket, &rule, start);
+
+        let rejected = bucket.take(&rule, start);
+        assert_eq!(rejected.retry_after, 360);
+        let rejected = bucket.take(&rule, start + Duration::from_secs(300));
+        assert_eq!(rejected.retry_after, 60);
+        let rejected = bucket.take(&rule, start + Duration::from_millis(359_500));
+        assert_eq!(rejected.retry_after, 1);
+        assert_eq!(bucket.take(&rule, start + Duration::from_secs(360)).retry_after, 0);
+    }
+
+    #[test]
+    fn ipv6_clients_share_a_bucket_per_64() {
+        let key = |ip: &str| bucket_key(ip.parse().unwrap());
+        assert_eq!(key("2001:db8:1:2:aaaa::1"), key("2001:db8:1:2:ffff:1:2:3"));
+        assert_eq!(key("2001:db8:1:2:aaaa::1"), "2001:db8:1:2::".parse::<IpAddr>().unwrap());
+        assert_ne!(key("2001:db8:1:2::1"), key("2001:db8:1:3::1"));
+
+        assert_eq!(key("203.0.113.7"), "203.0.113.7".parse::<IpAddr>().unwrap());
+        assert_ne!(key("203.0.113.7"), key("203.0.113.8"));
+        assert_eq!(key("::ffff:203.0.113.7"), key("203.0.113.7"));
+    }
+
+    #[test]
+    fn rule_paths_match_exactly_or_by_prefix() {
+        let rules = RateLimits::default().rules;
+        let find = |path: &str| rules.iter().find(|rule| rule.matches(path)).map(|rule| rule.path.as_str());
+        assert_eq!(find("/tools/change-background"), Some("/tools/change-background"));
+        assert_eq!(find("/tools/change-background/x"), None);
+        assert_eq!(find("/api/tools/info"), Some("/api/tools/*"));
+        assert_eq!(find("/api/projects"), Some("/api/*"));
+        assert_eq!(find("/articles"), None);
+    }
+}
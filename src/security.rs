@@ -0,0 +1,257 @@
+// 安全响应头：Content-Security-Policy、HSTS 等，以及 CSP 违规报告
+//
+// 每个请求生成一个随机 nonce，模板中的内联脚本通过 `nonce="{{ crate::security::nonce() }}"` 带上它，
+// CSP 只允许带 nonce 的内联脚本执行，内联事件属性（onclick 等）会被拦截，需要改用 addEventListener。
+// 浏览器发现的违规上报到 /csp-report 并写入日志；CSP_MODE=report-only 时只上报不拦截，适合调整策略时使用。
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// CSP 违规报告的接收地址
+pub const REPORT_PATH: &str = "/csp-report";
+/// 默认策略，{nonce} 替换为本次请求的 nonce，{frame_ancestors} 替换为 FRAME_ANCESTORS
+const DEFAULT_POLICY: &str = "default-src 'self'; \
+    script-src 'self' 'nonce-{nonce}'; \
+    style-src 'self' 'unsafe-inline' https://fonts.googleapis.com; \
+    font-src 'self' https://fonts.gstatic.com; \
+    img-src 'self' data: blob: https:; \
+    connect-src 'self'; \
+    object-src 'none'; \
+    base-uri 'self'; \
+    form-action 'self'; \
+    frame-ancestors {frame_ancestors}";
+const REFERRER_POLICY: &str = "strict-origin-when-cross-origin";
+const PERMISSIONS_POLICY: &str = "camera=(), microphone=(), geolocation=(), payment=(), usb=(), interest-cohort=()";
+/// 单个违规报告的大小上限，超过的直接丢弃
+const MAX_REPORT_SIZE: usize = 16 * 1024;
+
+/// CSP 的启用方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CspMode {
+    Enforce,
+    ReportOnly,
+    Off,
+}
+
+pub struct SecurityConfig;
+
+impl SecurityConfig {
+    /// CSP 模式（CSP_MODE）：enforce（默认）、report-only 或 off
+    pub fn get_csp_mode() -> CspMode {
+        match std::env::var("CSP_MODE").map(|v| v.trim().to_lowercase()).as_deref() {
+            Ok("report-only") | Ok("report_only") => CspMode::ReportOnly,
+            Ok("off") | Ok("false") | Ok("0") => CspMode::Off,
+            _ => CspMode::Enforce,
+        }
+    }
+
+    /// 自定义策略（CSP_POLICY），可以使用 {nonce} 和 {frame_ancestors} 占位符；未配置时使用默认策略
+    pub fn get_csp_policy() -> String {
+        std::env::var("CSP_POLICY")
+            .ok()
+            .filter(|policy| !policy.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_POLICY.to_string())
+    }
+
+    /// 允许嵌入本站的页面（FRAME_ANCESTORS），默认 'none'，即不允许被任何页面用 iframe 嵌入
+    pub fn get_frame_ancestors() -> String {
+        std::env::var("FRAME_ANCESTORS")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| "'none'".to_string())
+    }
+
+    /// HSTS 的有效期（HSTS_MAX_AGE，秒），默认一年，设为 0 时不发送。
+    /// 浏览器只认 HTTPS 响应中的 HSTS，经 Cloudflare 访问时即可生效
+    pub fn get_hsts_max_age() -> u64 {
+        std::env::var("HSTS_MAX_AGE")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(31_536_000)
+    }
+}
+
+/// 本次请求的 nonce，并记录模板是否用到了它
+struct RequestNonce {
+    value: String,
+    used: AtomicBool,
+}
+
+tokio::task_local! {
+    static NONCE: RequestNonce;
+}
+
+/// 模板中内联脚本使用的 nonce；不在请求处理过程中调用时返回空字符串
+pub fn nonce() -> String {
+    NONCE
+        .try_with(|nonce| {
+            nonce.used.store(true, Ordering::Relaxed);
+            nonce.value.clone()
+        })
+        .unwrap_or_default()
+}
+
+/// 本次响应是否包含 nonce。带 nonce 的页面每次都不同，不能用 ETag 做条件请求
+pub fn nonce_used() -> bool {
+    NONCE.try_with(|nonce| nonce.used.load(Ordering::Relaxed)).unwrap_or(false)
+}
+
+/// 为所有响应加上安全相关的响应头
+pub async fn headers(request: Request, next: Next) -> Response {
+    let nonce = RequestNonce {
+        value: hex::encode(rand::random::<[u8; 16]>()),
+        used: AtomicBool::new(false),
+    };
+    let policy = SecurityConfig::get_csp_policy()
+        .replace("{nonce}", &nonce.value)
+        .replace("{frame_ancestors}", &SecurityConfig::get_frame_ancestors());
+    let mut response = NONCE.scope(nonce, next.run(request)).await;
+
+    let headers = response.headers_mut();
+    let csp_header = match SecurityConfig::get_csp_mode() {
+        CspMode::Enforce => Some(header::CONTENT_SECURITY_POLICY),
+        CspMode::ReportOnly => Some(header::CONTENT_SECURITY_POLICY_REPORT_ONLY),
+        CspMode::Off => None,
+    };
+    if let Some(name) = csp_header {
+        let policy = format!("{}; report-uri {}", policy.trim().trim_end_matches(';'), REPORT_PATH);
+        match HeaderValue::from_str(&policy) {
+            Ok(value) => {
+                headers.entry(name).or_insert(value);
+            }
+            Err(e) => tracing::error!("CSP_POLICY 不是合法的响应头: {}", e),
+        }
+    }
+
+    let max_age = SecurityConfig::get_hsts_max_age();
+    if max_age > 0 && let Ok(value) = HeaderValue::from_str(&format!("max-age={}", max_age)) {
+        headers.entry(header::STRICT_TRANSPORT_SECURITY).or_insert(value);
+    }
+    // 不支持 frame-ancestors 的旧浏览器使用 X-Frame-Options
+    let frame_options = match SecurityConfig::get_frame_ancestors().trim() {
+        "'none'" => Some("DENY"),
+        "'self'" => Some("SAMEORIGIN"),
+        _ => None,
+    };
+    if let Some(value) = frame_options {
+        headers.entry(header::X_FRAME_OPTIONS).or_insert(HeaderValue::from_static(value));
+    }
+    headers
+        .entry(header::X_CONTENT_TYPE_OPTIONS)
+        .or_insert(HeaderValue::from_static("nosniff"));
+    headers
+        .entry(header::REFERRER_POLICY)
+        .or_insert(HeaderValue::from_static(REFERRER_POLICY));
+    headers
+        .entry(HeaderName::from_static("permissions-policy"))
+        .or_insert(HeaderValue::from_static(PERMISSIONS_POLICY));
+    response
+}
+
+/// POST /csp-report：接收浏览器的 CSP 违规报告并写入日志。
+/// 兼容 report-uri 的 application/csp-report 格式和 Reporting API 的 application/reports+json 格式
+pub async fn csp_report(body: Body) -> Response {
+    let Ok(bytes) = to_bytes(body, MAX_REPORT_SIZE).await else {
+        return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+    };
+    let Ok(report) = serde_json::from_slice::<Value>(&bytes) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    let reports: Vec<&Value> = match &report {
+        // Reporting API：[{ "type": "csp-violation", "body": { ... } }]
+        Value::Array(items) => items
+            .iter()
+            .filter(|item| item["type"] == "csp-violation")
+            .map(|item| &item["body"])
+            .collect(),
+        // report-uri：{ "csp-report": { ... } }
+        _ => vec![&report["csp-report"]],
+    };
+    for report in reports.into_iter().filter(|r| r.is_object()) {
+        // 两种格式的字段名不同，取第一个存在的
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| match &report[*name] {
+                    Value::String(s) if !s.is_empty() => Some(s.clone()),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| "-".to_string())
+        };
+        tracing::warn!(
+            "CSP 违规: 页面 {} 指令 {} 被拦截 {} 来源 {}:{}",
+            field(&["document-uri", "documentURL"]),
+            field(&["effective-directive", "effectiveDirective", "violated-directive"]),
+            field(&["blocked-uri", "blockedURL"]),
+            field(&["source-file", "sourceFile"]),
+            field(&["line-number", "lineNumber"]),
+        );
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::{get, post}, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            // 模拟带内联脚本的页面：把 nonce 写进响应体
+            .route("/", get(|| async { nonce() }))
+            .route(REPORT_PATH, post(csp_report))
+            .layer(middleware::from_fn(headers))
+    }
+
+    async fn send(request: Request) -> Response {
+        app().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn page_nonce_matches_csp_and_changes_per_request() {
+        let mut nonces = Vec::new();
+        for _ in 0..2 {
+            let response = send(Request::get("/").body(Body::empty()).unwrap()).await;
+            let headers = response.headers().clone();
+            let nonce = String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap();
+            assert_eq!(nonce.len(), 32);
+            let csp = headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+            assert!(csp.contains(&format!("script-src 'self' 'nonce-{}'", nonce)), "{}", csp);
+            assert!(csp.contains("frame-ancestors 'none'") && csp.ends_with("; report-uri /csp-report"), "{}", csp);
+            assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+            assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+            assert_eq!(headers[header::STRICT_TRANSPORT_SECURITY], "max-age=31536000");
+            assert_eq!(headers[header::REFERRER_POLICY], REFERRER_POLICY);
+            assert_eq!(headers["permissions-policy"], PERMISSIONS_POLICY);
+            nonces.push(nonce);
+        }
+        assert_ne!(nonces[0], nonces[1]);
+        // 不在请求处理过程中时没有 nonce
+        assert_eq!(nonce(), "");
+    }
+
+    #[tokio::test]
+    async fn csp_reports_in_both_formats_are_accepted() {
+        let report_uri = r#"{"csp-report":{"document-uri":"https://example.com/","blocked-uri":"inline","line-number":3}}"#;
+        let reporting_api = r#"[{"type":"csp-violation","body":{"documentURL":"https://example.com/","blockedURL":"eval"}}]"#;
+        for body in [report_uri, reporting_api] {
+            let response = send(Request::post(REPORT_PATH).body(Body::from(body)).unwrap()).await;
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        }
+
+        let response = send(Request::post(REPORT_PATH).body(Body::from("not json")).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = send(Request::post(REPORT_PATH).body(Body::from(vec![b' '; MAX_REPORT_SIZE + 1])).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
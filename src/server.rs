@@ -0,0 +1,396 @@
+// 监听方式：HTTP / HTTPS（rustls）、HTTP→HTTPS 跳转和 Unix 套接字
+//
+// 配置 TLS_CERT 和 TLS_KEY 后主端口改为 HTTPS，通过 ALPN 同时支持 HTTP/2 和 HTTP/1.1；
+// 证书文件按 TLS_RELOAD_SECS 的间隔检查修改时间，续期后自动加载，新证书无效时继续使用旧证书。
+// HTTP_REDIRECT_PORT 额外监听一个 HTTP 端口，把所有请求 308 跳转到 HTTPS。
+// UNIX_SOCKET 额外监听一个 Unix 套接字供同机的反向代理使用，连接视为来自 127.0.0.1，
+// 因此默认信任其中的 X-Forwarded-For 等请求头（见 client_ip.rs）。
+// 所有监听都在收到退出信号后一起停止（见 lifecycle.rs）。
+
+use axum::{
+    extract::Request,
+    http::{header, StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
+    serve::{Listener, ListenerExt},
+    Router,
+};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+use crate::lifecycle;
+
+/// TLS 握手的超时时间，超时的连接直接关闭
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// 等待握手完成的连接数上限，超过时暂停接受新连接
+const PENDING_HANDSHAKES: usize = 256;
+
+pub struct ListenConfig;
+
+impl ListenConfig {
+    /// 证书链文件（TLS_CERT，PEM 格式），与 TLS_KEY 同时配置时启用 HTTPS
+    pub fn get_tls_cert() -> Option<String> {
+        std::env::var("TLS_CERT").ok().filter(|v| !v.trim().is_empty())
+    }
+
+    /// 私钥文件（TLS_KEY，PEM 格式，支持 PKCS#8、PKCS#1 和 SEC1）
+    pub fn get_tls_key() -> Option<String> {
+        std::env::var("TLS_KEY").ok().filter(|v| !v.trim().is_empty())
+    }
+
+    /// 检查证书文件是否更新的间隔（TLS_RELOAD_SECS，秒），默认 60，设为 0 时不自动重新加载
+    pub fn get_tls_reload_secs() -> u64 {
+        std::env::var("TLS_RELOAD_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(60)
+    }
+
+    /// HTTP→HTTPS 跳转端口（HTTP_REDIRECT_PORT），只在启用 HTTPS 时生效
+    pub fn get_redirect_port() -> Option<u16> {
+        std::env::var("HTTP_REDIRECT_PORT").ok().and_then(|v| v.trim().parse().ok())
+    }
+
+    /// Unix 套接字路径（UNIX_SOCKET）
+    pub fn get_unix_socket() -> Option<PathBuf> {
+        std::env::var("UNIX_SOCKET")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Unix 套接字文件的权限（UNIX_SOCKET_MODE，八进制），默认 660，反向代理需要在同一个组中
+    pub fn get_unix_socket_mode() -> u32 {
+        std::env::var("UNIX_SOCKET_MODE")
+            .ok()
+            .and_then(|v| u32::from_str_radix(v.trim(), 8).ok())
+            .unwrap_or(0o660)
+    }
+}
+
+/// 读取证书链和私钥，并确认两者匹配
+fn load_certified_key(cert_path: &str, key_path: &str) -> Result<CertifiedKey, Box<dyn std::error::Error>> {
+    let cert_pem = fs::read(cert_path).map_err(|e| format!("读取证书 {} 失败: {}", cert_path, e))?;
+    let key_pem = fs::read(key_path).map_err(|e| format!("读取私钥 {} 失败: {}", key_path, e))?;
+
+    let certs = CertificateDer::pem_slice_iter(&cert_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("解析证书 {} 失败: {}", cert_path, e))?;
+    if certs.is_empty() {
+        return Err(format!("证书文件 {} 中没有证书", cert_path).into());
+    }
+    let key = PrivateKeyDer::from_pem_slice(&key_pem).map_err(|e| format!("解析私钥 {} 失败: {}", key_path, e))?;
+    let signing_key = rustls::crypto::ring::sign::any_supported_type(&key)
+        .map_err(|e| format!("不支持的私钥类型: {}", e))?;
+
+    let certified = CertifiedKey::new(certs, signing_key);
+    certified.keys_match().map_err(|e| format!("证书与私钥不匹配: {}", e))?;
+    Ok(certified)
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// 当前使用的证书，握手时读取，重新加载时整体替换
+#[derive(Debug)]
+struct CertStore {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ResolvesServerCert for CertStore {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+/// 定期检查证书和私钥的修改时间，变化后重新加载
+fn spawn_cert_reload(store: Arc<CertStore>, cert_path: String, key_path: String, interval: Duration) {
+    tokio::spawn(async move {
+        let mut loaded = (modified(&cert_path), modified(&key_path));
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = lifecycle::shutdown_token().cancelled() => break,
+            }
+            let current = (modified(&cert_path), modified(&key_path));
+            if current == loaded {
+                continue;
+            }
+            // 证书和私钥通常先后写入，只有两者都能加载且匹配时才替换
+            match load_certified_key(&cert_path, &key_path) {
+                Ok(certified) => {
+                    *store.current.write().unwrap() = Arc::new(certified);
+                    loaded = current;
+                    tracing::info!("TLS 证书已重新加载: {}", cert_path);
+                }
+                Err(e) => tracing::warn!("TLS 证书已变化但无法加载，继续使用旧证书: {}", e),
+            }
+        }
+    });
+}
+
+/// 配置了 TLS_CERT 和 TLS_KEY 时创建 TLS 接收器，并开始监视证书文件；只配置了其中一个时返回错误
+pub fn tls_acceptor() -> Result<Option<TlsAcceptor>, Box<dyn std::error::Error>> {
+    let (cert_path, key_path) = match (ListenConfig::get_tls_cert(), ListenConfig::get_tls_key()) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) => return Ok(None),
+        _ => return Err("TLS_CERT 和 TLS_KEY 需要同时配置".into()),
+    };
+
+    let store = Arc::new(CertStore {
+        current: RwLock::new(Arc::new(load_certified_key(&cert_path, &key_path)?)),
+    });
+    let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_cert_resolver(store.clone());
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    tracing::info!("已加载 TLS 证书: {}", cert_path);
+
+    let reload_secs = ListenConfig::get_tls_reload_secs();
+    if reload_secs > 0 {
+        spawn_cert_reload(store, cert_path, key_path, Duration::from_secs(reload_secs));
+    }
+    Ok(Some(TlsAcceptor::from(Arc::new(config))))
+}
+
+/// 与 axum 对 TcpListener 的处理一致：连接级错误直接忽略，其他错误（如文件描述符用尽）稍等再试
+async fn handle_accept_error(e: io::Error) {
+    if matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionAborted | io::ErrorKind::ConnectionReset
+    ) {
+        return;
+    }
+    tracing::error!("接受连接失败: {}", e);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+}
+
+/// HTTPS 监听：后台任务接受 TCP 连接并并发完成握手，握手慢的客户端不会阻塞其他连接
+pub struct TlsListener {
+    incoming: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, acceptor: TlsAcceptor) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (sender, incoming) = mpsc::channel(PENDING_HANDSHAKES);
+        tokio::spawn(async move {
+            loop {
+                // 排队的连接达到上限时先等待；服务停止时 incoming 被丢弃，不再接受新连接
+                let Ok(permit) = sender.clone().reserve_owned().await else {
+                    break;
+                };
+                let (stream, addr) = tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            handle_accept_error(e).await;
+                            continue;
+                        }
+                    },
+                    _ = sender.closed() => break,
+                };
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            permit.send((stream, addr));
+                        }
+                        Ok(Err(e)) => tracing::debug!("TLS 握手失败 {}: {}", addr, e),
+                        Err(_) => tracing::debug!("TLS 握手超时 {}", addr),
+                    }
+                });
+            }
+        });
+        Ok(Self { incoming, local_addr })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(accepted) => accepted,
+            // 接受连接的任务只会在 incoming 被丢弃后退出，这里不会走到
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// Unix 套接字监听，停止时删除套接字文件
+pub struct UnixSocketListener {
+    inner: UnixListener,
+    path: PathBuf,
+}
+
+impl UnixSocketListener {
+    /// 绑定套接字；路径上残留的旧套接字文件会被删除，其他类型的文件返回错误
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if let Ok(meta) = fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} 已存在且不是套接字文件", path.display()),
+                ));
+            }
+            fs::remove_file(path)?;
+        }
+        let inner = UnixListener::bind(path)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(ListenConfig::get_unix_socket_mode()))?;
+        Ok(Self {
+            inner,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for UnixSocketListener {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Unix 套接字没有对端 IP，按本机连接处理
+const UNIX_PEER: SocketAddr = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 0);
+
+impl Listener for UnixSocketListener {
+    type Io = UnixStream;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            match self.inner.accept().await {
+                Ok((stream, _)) => return (stream, UNIX_PEER),
+                Err(e) => handle_accept_error(e).await,
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(UNIX_PEER)
+    }
+}
+
+/// 运行一个监听，直到收到退出信号且连接都已结束
+pub async fn serve<L>(listener: L, app: Router, name: String)
+where
+    L: Listener<Addr = SocketAddr>,
+{
+    // 经 tap_io 包装后自定义监听也能提供 ConnectInfo<SocketAddr>
+    let listener = listener.tap_io(|_| {});
+    let shutdown = lifecycle::shutdown_token().clone().cancelled_owned();
+    if let Err(e) = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown)
+        .await
+    {
+        tracing::error!("{} 运行出错: {}", name, e);
+    }
+}
+
+/// HTTP→HTTPS 跳转，https_port 为 443 时省略端口
+pub fn redirect_app(https_port: u16) -> Router {
+    Router::new().fallback(move |request: Request| async move { redirect_to_https(request, https_port) })
+}
+
+fn redirect_to_https(request: Request, https_port: u16) -> Response {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| request.uri().host());
+    let Some(host) = host else {
+        return (StatusCode::BAD_REQUEST, "缺少 Host 请求头").into_response();
+    };
+    // 去掉原来的端口，IPv6 地址形如 [::1]:8080
+    let hostname = match host.rsplit_once(':') {
+        Some((name, port)) if !name.is_empty() && port.chars().all(|c| c.is_ascii_digit()) && (!name.contains(':') || name.ends_with(']')) => name,
+        _ => host,
+    };
+    let authority = if https_port == 443 {
+        hostname.to_string()
+    } else {
+        format!("{}:{}", hostname, https_port)
+    };
+    let path = request.uri().path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+    match Uri::builder().scheme("https").authority(authority).path_and_query(path).build() {
+        Ok(location) => Redirect::permanent(&location.to_string()).into_response(),
+        Err(_) => (StatusCode::BAD_REQUEST, "无效的 Host 请求头").into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn redirect(uri: &str, host: Option<&str>, https_port: u16) -> Response {
+        let mut request = Request::get(uri);
+        if let Some(host) = host {
+            request = request.header(header::HOST, host);
+        }
+        redirect_to_https(request.body(Body::empty()).unwrap(), https_port)
+    }
+
+    fn location(response: &Response) -> &str {
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        response.headers()[header::LOCATION].to_str().unwrap()
+    }
+
+    #[test]
+    fn replaces_port_in_host() {
+        assert_eq!(location(&redirect("/", Some("example.com:8080"), 8443)), "https://example.com:8443/");
+        assert_eq!(location(&redirect("/", Some("example.com:80"), 443)), "https://example.com/");
+        assert_eq!(location(&redirect("/", Some("example.com"), 443)), "https://example.com/");
+    }
+
+    #[test]
+    fn keeps_bracketed_ipv6_host() {
+        assert_eq!(location(&redirect("/", Some("[::1]:8080"), 8443)), "https://[::1]:8443/");
+        assert_eq!(location(&redirect("/", Some("[2001:db8::1]"), 443)), "https://[2001:db8::1]/");
+    }
+
+    #[test]
+    fn keeps_path_and_query() {
+        let response = redirect("/articles/a.md?tag=rust&page=2", Some("example.com"), 443);
+        assert_eq!(location(&response), "https://example.com/articles/a.md?tag=rust&page=2");
+    }
+
+    #[test]
+    fn falls_back_to_absolute_uri_host() {
+        let response = redirect("http://example.com:8080/notes?tag=a", None, 443);
+        assert_eq!(location(&response), "https://example.com/notes?tag=a");
+    }
+
+    #[test]
+    fn missing_or_invalid_host_is_bad_request() {
+        assert_eq!(redirect("/", None, 443).status(), StatusCode::BAD_REQUEST);
+        assert_eq!(redirect("/", Some("bad host"), 443).status(), StatusCode::BAD_REQUEST);
+    }
+}
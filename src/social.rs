@@ -0,0 +1,373 @@
+// 社交分享：页面的 Open Graph 元数据，以及文章/项目分享卡片的生成和磁盘缓存
+//
+// 卡片按“所属页面 + 内容哈希”缓存在 data/og 下，内容不变时直接返回已有的 PNG，
+// 内容变化后写入新卡片的同时删除同一页面的旧卡片；
+// 作者头像下载一次后同样缓存，离线模式或下载失败时改画首字母。
+
+use image::RgbaImage;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::card::{self, Card};
+use crate::github::config::GitHubConfig;
+use crate::github::{GitHubData, StoredArticle, StoredProject};
+
+pub const SITE_NAME: &str = "XJZ 的个人网站";
+const SITE_DESCRIPTION: &str = "用 Rust 和 Axum 构建的现代化网站";
+/// 卡片布局变化时递增，使旧缓存失效
+const CARD_VERSION: u32 = 1;
+/// 头像下载失败后的重试间隔，期间卡片改画首字母且不写入缓存
+const AVATAR_RETRY_INTERVAL: Duration = Duration::from_secs(600);
+
+static AVATAR_FAILED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// 随站点部署的中文字体（Noto Sans SC 的常用字子集），优先于系统字体
+pub const BUNDLED_CJK_FONT: &str = "assets/fonts/NotoSansSC-Subset.otf";
+
+/// 常见发行版中的中文字体位置，按顺序查找
+const FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/google-noto-sans-cjk-vf-fonts/NotoSansCJK-VF.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+];
+
+pub struct SocialConfig;
+
+impl SocialConfig {
+    /// 站点的公开地址（SITE_URL），用于生成 og:url 和 og:image 的绝对地址；未配置时使用相对路径
+    pub fn get_site_url() -> String {
+        std::env::var("SITE_URL")
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .unwrap_or_default()
+    }
+
+    /// 卡片使用的中文字体（OG_FONT），未配置时先用随站点部署的字体，再在常见位置查找
+    pub fn get_font_path() -> Option<String> {
+        std::env::var("OG_FONT")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+            .or_else(|| {
+                std::iter::once(&BUNDLED_CJK_FONT)
+                    .chain(FONT_CANDIDATES)
+                    .find(|path| Path::new(path).is_file())
+                    .map(|path| path.to_string())
+            })
+    }
+
+    /// 卡片缓存目录（OG_CACHE_DIR，默认 data/og）
+    pub fn get_cache_dir() -> PathBuf {
+        std::env::var("OG_CACHE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/og"))
+    }
+}
+
+/// 页面的分享元数据，由 base.html 渲染为 Open Graph 和 Twitter Card 标签
+pub struct PageMeta {
+    pub title: String,
+    pub description: String,
+    /// 页面路径，如 /projects/xjz-website
+    pub path: String,
+    /// 预览图路径
+    pub image: String,
+    /// og:type，文章为 article，其余为 website
+    pub og_type: &'static str,
+}
+
+impl PageMeta {
+    /// 普通页面，使用站点卡片作为预览图
+    pub fn page(title: &str, path: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            description: SITE_DESCRIPTION.to_string(),
+            path: path.to_string(),
+            image: "/og/site.png".to_string(),
+            og_type: "website",
+        }
+    }
+
+    pub fn article(article: &StoredArticle) -> Self {
+        Self {
+            title: article.title.clone(),
+            description: article.excerpt.clone(),
+            path: format!("/blog?article={}", encode_path(&article.id)),
+            image: format!("/og/articles/{}.png", encode_path(&article.id)),
+            og_type: "article",
+        }
+    }
+
+    pub fn project(project: &StoredProject) -> Self {
+        Self {
+            title: project.display_name.clone().unwrap_or_else(|| project.name.clone()),
+            description: project.description.clone().unwrap_or_else(|| SITE_DESCRIPTION.to_string()),
+            path: format!("/projects/{}", encode_path(&project.name)),
+            image: format!("/og/projects/{}.png", encode_path(&project.name)),
+            og_type: "website",
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}{}", SocialConfig::get_site_url(), self.path)
+    }
+
+    pub fn image_url(&self) -> String {
+        format!("{}{}", SocialConfig::get_site_url(), self.image)
+    }
+}
+
+/// 对路径片段做百分号编码（保留 RFC 3986 的非保留字符）
+fn encode_path(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn author(data: &GitHubData) -> String {
+    data.user_stats
+        .as_ref()
+        .map(|stats| stats.username.clone())
+        .unwrap_or_else(GitHubConfig::get_username)
+}
+
+pub fn site_card(data: &GitHubData) -> Card {
+    Card {
+        tag: "个人网站".to_string(),
+        title: SITE_NAME.to_string(),
+        subtitle: Some(SITE_DESCRIPTION.to_string()),
+        meta: format!("{} 个项目 · {} 篇文章", data.projects.len(), data.articles.len()),
+        author: author(data),
+        site_name: SITE_NAME.to_string(),
+    }
+}
+
+pub fn article_card(data: &GitHubData, article: &StoredArticle) -> Card {
+    Card {
+        tag: article.category.clone(),
+        title: article.title.clone(),
+        subtitle: None,
+        meta: format!("阅读约 {} 分钟 · {}", article.reading_time, article.repo_name),
+        author: author(data),
+        site_name: SITE_NAME.to_string(),
+    }
+}
+
+pub fn project_card(data: &GitHubData, project: &StoredProject) -> Card {
+    let mut meta = format!("★ {} · Fork {}", project.stargazers_count, project.forks_count);
+    if let Some(version) = &project.latest_version {
+        meta.push_str(&format!(" · {}", version));
+    }
+    meta.push_str(&format!(" · {}", project.full_name));
+
+    Card {
+        tag: project.language.clone().unwrap_or_else(|| "项目".to_string()),
+        title: project.display_name.clone().unwrap_or_else(|| project.name.clone()),
+        subtitle: project.description.clone().filter(|d| !d.trim().is_empty()),
+        meta,
+        author: author(data),
+        site_name: SITE_NAME.to_string(),
+    }
+}
+
+/// 卡片所属的页面（site、article、project + 标识），作为缓存文件名的前缀，
+/// 标识取哈希，避免文章 ID 中的路径字符出现在文件名中
+pub fn card_slot(kind: &str, id: &str) -> String {
+    if id.is_empty() {
+        return kind.to_string();
+    }
+    format!("{}-{}", kind, &hex::encode(Sha256::digest(id.as_bytes()))[..12])
+}
+
+/// 卡片内容和所用中文字体的哈希，同时用作缓存文件名和 ETag；换用字体后旧卡片随之失效
+pub fn card_key(card: &Card, avatar_url: Option<&str>) -> String {
+    let source = format!(
+        "{}|{}|{}|{}|{}|{}|{}|{}",
+        CARD_VERSION,
+        card::cjk_font().unwrap_or(""),
+        card.tag,
+        card.title,
+        card.subtitle.as_deref().unwrap_or(""),
+        card.meta,
+        card.author,
+        avatar_url.unwrap_or("")
+    );
+    hex::encode(Sha256::digest(source.as_bytes()))[..16].to_string()
+}
+
+/// 返回卡片的 PNG，命中磁盘缓存时不重新绘制
+pub async fn card_png(slot: &str, card: Card, avatar_url: Option<String>) -> Result<(String, Vec<u8>), Box<dyn std::error::Error + Send + Sync>> {
+    let key = card_key(&card, avatar_url.as_deref());
+    let file_name = format!("{}.{}.png", slot, key);
+    let path = SocialConfig::get_cache_dir().join(&file_name);
+    if let Ok(png) = fs::read(&path) {
+        return Ok((key, png));
+    }
+
+    let avatar = match &avatar_url {
+        Some(url) => load_avatar(url).await,
+        None => None,
+    };
+    // 缺少头像的卡片只是临时结果，不缓存，等头像可用后重新生成
+    let cacheable = avatar_url.is_none() || avatar.is_some();
+    // 绘制和 PNG 编码较耗 CPU，放到阻塞线程中执行
+    let png = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, image::ImageError> {
+        let image = card::render(&card, avatar.as_ref());
+        let mut png = Vec::new();
+        image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+        Ok(png)
+    })
+    .await??;
+
+    if cacheable {
+        match fs::create_dir_all(SocialConfig::get_cache_dir()).and_then(|_| fs::write(&path, &png)) {
+            Ok(()) => remove_stale_cards(&SocialConfig::get_cache_dir(), slot, &file_name),
+            Err(e) => tracing::warn!("写入分享卡片缓存失败: {}", e),
+        }
+    }
+    Ok((key, png))
+}
+
+/// 删除同一页面的旧卡片，以及旧版本按内容哈希命名（不带页面前缀）的卡片；头像缓存不受影响
+fn remove_stale_cards(dir: &Path, slot: &str, current: &str) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        let Some(stem) = name.strip_suffix(".png") else {
+            continue;
+        };
+        let same_slot = stem.split_once('.').is_some_and(|(s, _)| s == slot);
+        let legacy = stem.len() == 16 && stem.chars().all(|c| c.is_ascii_hexdigit());
+        if name != current && (same_slot || legacy) {
+            match fs::remove_file(entry.path()) {
+                Ok(()) => tracing::debug!("已删除过期的分享卡片: {}", name),
+                Err(e) => tracing::warn!("删除过期的分享卡片 {} 失败: {}", name, e),
+            }
+        }
+    }
+}
+
+/// 读取缓存的头像，没有缓存时下载（离线模式下不下载）
+async fn load_avatar(url: &str) -> Option<RgbaImage> {
+    let name = format!("avatar-{}.png", &hex::encode(Sha256::digest(url.as_bytes()))[..16]);
+    let path = SocialConfig::get_cache_dir().join(name);
+    if let Ok(image) = image::open(&path) {
+        return Some(image.to_rgba8());
+    }
+    if GitHubConfig::is_offline() {
+        return None;
+    }
+    let recently_failed = AVATAR_FAILED_AT
+        .lock()
+        .unwrap()
+        .is_some_and(|at| at.elapsed() < AVATAR_RETRY_INTERVAL);
+    if recently_failed {
+        return None;
+    }
+
+    let bytes = async {
+        let response = reqwest::Client::new()
+            .get(url)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await?
+            .error_for_status()?;
+        response.bytes().await
+    }
+    .await;
+    let image = match bytes.map(|b| image::load_from_memory(&b)) {
+        Ok(Ok(image)) => image.to_rgba8(),
+        Ok(Err(e)) => {
+            tracing::warn!("无法解析头像 {}: {}", url, e);
+            *AVATAR_FAILED_AT.lock().unwrap() = Some(Instant::now());
+            return None;
+        }
+        Err(e) => {
+            tracing::warn!("下载头像失败 {}: {}", url, e);
+            *AVATAR_FAILED_AT.lock().unwrap() = Some(Instant::now());
+            return None;
+        }
+    };
+
+    let image = image::imageops::resize(&image, 128, 128, image::imageops::FilterType::Lanczos3);
+    if let Err(e) = fs::create_dir_all(SocialConfig::get_cache_dir()).and_then(|_| {
+        image.save(&path).map_err(std::io::Error::other)
+    }) {
+        tracing::warn!("写入头像缓存失败: {}", e);
+    }
+    Some(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("xjz-og-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn card_slot_hides_article_paths() {
+        assert_eq!(card_slot("site", ""), "site");
+        let slot = card_slot("article", "docs/fedora/01-install.md");
+        assert!(slot.starts_with("article-"));
+        assert_eq!(slot.len(), "article-".len() + 12);
+        assert!(!slot.contains('/') && !slot.contains('.'));
+        assert_ne!(slot, card_slot("article", "docs/fedora/02-nginx.md"));
+        assert_ne!(card_slot("article", "x"), card_slot("project", "x"));
+    }
+
+    #[test]
+    fn stale_cards_of_the_same_page_are_removed() {
+        let dir = cache_dir("stale");
+        let article = card_slot("article", "a.md");
+        let other = card_slot("article", "b.md");
+        for name in [
+            format!("{}.0000000000000001.png", article),
+            format!("{}.0000000000000002.png", article),
+            format!("{}.0000000000000001.png", other),
+            "site.0000000000000001.png".to_string(),
+            "0123456789abcdef.png".to_string(),
+            "avatar-0123456789abcdef.png".to_string(),
+        ] {
+            fs::write(dir.join(name), b"png").unwrap();
+        }
+
+        let current = format!("{}.0000000000000002.png", article);
+        remove_stale_cards(&dir, &article, &current);
+        let mut expected = vec![
+            current,
+            format!("{}.0000000000000001.png", other),
+            "avatar-0123456789abcdef.png".to_string(),
+            "site.0000000000000001.png".to_string(),
+        ];
+        expected.sort();
+        assert_eq!(files(&dir), expected);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+// 日志初始化、请求 ID 和访问日志
+//
+// 每个请求都有一个 request span，带有请求 ID（沿用客户端传入的 X-Request-Id，没有或格式不合法时生成 UUID，并在响应中返回）、
+// 方法、路径和客户端 IP；处理过程中的日志（包括由请求触发的 GitHub 刷新）都在这个 span 内，可以按请求 ID 串起来。
+// 请求结束时以 target "access" 记录状态码和耗时，RUST_LOG=info,access=off 可以关闭访问日志。
+// LOG_FORMAT=json 时每行输出一个 JSON 对象，span 中的字段放在 span / spans 中，便于 journald 之外的日志系统采集。
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, Response},
+    middleware::Next,
+};
+use std::time::Duration;
+use tower_http::classify::ServerErrorsFailureClass;
+use tower_http::request_id::RequestId;
+use tracing::Span;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::client_ip;
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+pub struct LogConfig;
+
+impl LogConfig {
+    /// 日志格式（LOG_FORMAT）：text（默认）或 json
+    pub fn get_format() -> LogFormat {
+        match std::env::var("LOG_FORMAT").map(|v| v.trim().to_lowercase()).as_deref() {
+            Ok("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+
+    /// 日志级别过滤（RUST_LOG），默认为 info
+    pub fn get_filter() -> String {
+        std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into())
+    }
+}
+
+/// 初始化全局日志
+pub fn init() {
+    let filter = tracing_subscriber::EnvFilter::new(LogConfig::get_filter());
+    match LogConfig::get_format() {
+        LogFormat::Json => tracing_subscriber::registry()
+            .with(filter)
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true),
+            )
+            .init(),
+        LogFormat::Text => tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer())
+            .init(),
+    }
+}
+
+/// 客户端传入的请求 ID 的最大长度
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// 请求 ID 只接受不超过 64 个字符的 [A-Za-z0-9._-]，避免把任意内容写进日志和响应头
+fn is_valid_request_id(id: &[u8]) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.iter().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+}
+
+/// 去掉不合法的 X-Request-Id，交给内层的 SetRequestIdLayer 重新生成
+pub async fn sanitize_request_id(mut request: Request, next: Next) -> axum::response::Response {
+    let invalid = request
+        .headers()
+        .get_all(&X_REQUEST_ID)
+        .iter()
+        .enumerate()
+        .any(|(i, id)| i > 0 || !is_valid_request_id(id.as_bytes()));
+    if invalid {
+        request.headers_mut().remove(&X_REQUEST_ID);
+    }
+    next.run(request).await
+}
+
+/// 请求的 span，请求 ID 由外层的 SetRequestIdLayer 设置
+pub fn make_span(request: &Request) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or("-");
+    let client = client_ip::of(request)
+        .map(|(ip, _)| ip.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    // 只记录路径，查询参数中可能带有令牌等敏感信息
+    tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+        client = %client,
+    )
+}
+
+/// 访问日志
+pub fn on_response<B>(response: &Response<B>, latency: Duration, _span: &Span) {
+    tracing::info!(
+        target: "access",
+        status = response.status().as_u16(),
+        latency_ms = latency.as_millis() as u64,
+        "请求完成"
+    );
+}
+
+/// 5xx 响应
+pub fn on_failure(failure: ServerErrorsFailureClass, latency: Duration, _span: &Span) {
+    tracing::error!(
+        target: "access",
+        latency_ms = latency.as_millis() as u64,
+        "请求失败: {}",
+        failure
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::{ServiceBuilder, ServiceExt};
+    use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+
+    async fn request_id(incoming: Option<&str>) -> String {
+        let app = Router::new().route("/", get(|| async { "ok" })).layer(
+            ServiceBuilder::new()
+                .layer(middleware::from_fn(sanitize_request_id))
+                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+                .layer(PropagateRequestIdLayer::x_request_id()),
+        );
+        let mut request = Request::get("/");
+        if let Some(id) = incoming {
+            request = request.header("x-request-id", id);
+        }
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        response.headers()["x-request-id"].to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn request_id_charset_and_length() {
+        assert!(is_valid_request_id(b"abc-123_DEF.4"));
+        assert!(is_valid_request_id("a".repeat(64).as_bytes()));
+        assert!(!is_valid_request_id("a".repeat(65).as_bytes()));
+        assert!(!is_valid_request_id(b""));
+        assert!(!is_valid_request_id(b"a b"));
+        assert!(!is_valid_request_id(b"id\"\n"));
+        assert!(!is_valid_request_id("请求".as_bytes()));
+    }
+
+    #[tokio::test]
+    async fn valid_id_is_kept_invalid_is_replaced() {
+        assert_eq!(request_id(Some("edge-42.a_b")).await, "edge-42.a_b");
+
+        for incoming in [None, Some("has space"), Some("<script>"), Some(&*"x".repeat(65))] {
+            let id = request_id(incoming).await;
+            assert_ne!(Some(id.as_str()), incoming);
+            assert!(id.len() == 36 && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-'), "{}", id);
+        }
+    }
+}
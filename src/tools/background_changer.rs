@@ -2,7 +2,6 @@
 
 use axum::{
     extract::{Query, Multipart},
-    http::StatusCode,
     response::{IntoResponse, Response, Json},
 };
 use base64::{engine::general_purpose::STANDARD as BASE64Engine, Engine as _};
@@ -15,6 +14,7 @@ use tracing;
 
 // 导入同级目录下的 ToolsConfig
 use super::config::ToolsConfig;
+use crate::api::{ApiError, ErrorCode};
 
 #[derive(Deserialize)]
 pub struct BgChangeParams {
@@ -24,38 +24,41 @@ pub struct BgChangeParams {
 pub async fn handle_change_background(
     Query(params): Query<BgChangeParams>,
     mut multipart: Multipart,
-) -> Result<Response, (StatusCode, String)> {
+) -> Result<Response, ApiError> {
     tracing::info!(target_color = ?params.target_color, "开始处理背景移除请求");
 
     let api_key = match ToolsConfig::get_removebg_api_key() {
         Some(key) => key,
         None => {
             tracing::error!("未能获取 remove.bg API 密钥。请检查配置。");
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "服务器配置错误：缺少 API 密钥".to_string(),
-            ));
+            return Err(ApiError::new(ErrorCode::NotConfigured, "背景更换服务未配置 API 密钥"));
         }
     };
 
-    while let Some(field) = multipart.next_field().await.unwrap() {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::bad_request(format!("无效的上传数据: {}", e)))?
+    {
         let field_name = field.name().unwrap_or("unknown").to_string();
 
         if field_name == "image" {
             let file_name = field.file_name().unwrap_or("uploaded_image").to_string();
             let data = field.bytes().await.map_err(|e| {
                 tracing::error!("读取图片数据失败: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "无法读取图片数据".to_string())
+                ApiError::bad_request("无法读取图片数据")
             })?;
             let _input_format = image::guess_format(&data).ok();
             
             tracing::info!(file_name = %file_name, size = %data.len(), "已读取上传的图片文件");
+            let metrics = crate::metrics::metrics();
+            metrics.tool_used("change_background", data.len());
 
             let client = reqwest::Client::new();
             let image_part = multipart::Part::bytes(data.to_vec())
                 .file_name(file_name.clone())
                 .mime_str("application/octet-stream")
-                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("创建 image part 失败: {}", e)))?;
+                .map_err(|e| ApiError::internal(format!("创建 image part 失败: {}", e)))?;
 
             let mut form = multipart::Form::new().part("image_file", image_part);
 
@@ -80,7 +83,8 @@ pub async fn handle_change_background(
                 .await
                 .map_err(|e| {
                     tracing::error!("发送到 remove.bg API 失败: {}", e);
-                    (StatusCode::SERVICE_UNAVAILABLE, format!("调用背景移除服务失败: {}", e))
+                    metrics.removebg_calls.with_label_values(&["network_error"]).inc();
+                    ApiError::new(ErrorCode::UpstreamError, "调用背景移除服务失败")
                 })?;
 
             if response.status().is_success() {
@@ -94,10 +98,13 @@ pub async fn handle_change_background(
 
                  let image_bytes = response.bytes().await.map_err(|e| {
                      tracing::error!("读取 remove.bg 响应体失败: {}", e);
-                     (StatusCode::INTERNAL_SERVER_ERROR, "读取API响应失败".to_string())
+                     metrics.removebg_calls.with_label_values(&["network_error"]).inc();
+                     ApiError::new(ErrorCode::UpstreamError, "读取背景移除服务的响应失败")
                  })?;
                  
                  tracing::info!("成功从 remove.bg API 获取到 {} bytes 的图片", image_bytes.len());
+                 metrics.removebg_calls.with_label_values(&["success"]).inc();
+                 metrics.tool_output("change_background", image_bytes.len());
                  
                  let base64_image = BASE64Engine.encode(&image_bytes);
                  let data_url = format!("data:{};base64,{}", mime_type, base64_image);
@@ -108,14 +115,15 @@ pub async fn handle_change_background(
                 let error_text = response.text().await.unwrap_or_else(|_| "无法读取错误详情".to_string());
                 
                 tracing::error!(status = %status, error_details = %error_text, "remove.bg API 请求失败");
+                metrics.removebg_calls.with_label_values(&["http_error"]).inc();
                 
-                return Err((
-                    status, 
-                    format!("背景移除服务失败: {}", error_text),
+                return Err(ApiError::new(
+                    ErrorCode::UpstreamError,
+                    format!("背景移除服务返回错误 {}", status.as_u16()),
                 ));
             }
         }
     }
 
-    Err((StatusCode::BAD_REQUEST, "缺少图片文件".to_string()))
+    Err(ApiError::bad_request("缺少图片文件"))
 }
\ No newline at end of file
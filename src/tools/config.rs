@@ -9,7 +9,7 @@ impl ToolsConfig {
     pub fn get_removebg_api_key() -> Option<String> {
         
         // 直接使用硬编码（偏移加密）值解密
-        tracing::info!("尝试从硬编码值获取 remove.bg API 密钥");
+        tracing::debug!("尝试从硬编码值获取 remove.bg API 密钥");
 
         // !!! --- 重要：确保这里是你自己加密后的 API Key --- !!!
         let encrypted_key = "5ccRiIp7u50q3UqaO0wG5Sb7"; // <--- 确保替换成你加密后的 Key
@@ -30,22 +30,17 @@ impl ToolsConfig {
             );
             None // 返回 None 表示密钥无效
         } else {
-            tracing::info!("成功从硬编码值获取 remove.bg API 密钥");
+            tracing::debug!("成功从硬编码值获取 remove.bg API 密钥");
             Some(decrypted_key)
         }
     }
 
     /// 检查并打印配置状态 (可选)
     pub fn check_config() {
-        println!("=== 工具配置状态 ===");
         match Self::get_removebg_api_key() {
-            Some(_) => println!("remove.bg API Key: 已从代码配置 ✅"), // 修改提示
-            None => {
-                println!("remove.bg API Key: 未配置或无效 ❌"); // 修改提示
-                println!("  请确保已在 src/tools/config.rs 中设置正确的加密密钥！");
-            }
+            Some(_) => tracing::info!("工具配置: remove.bg API Key 已从代码配置 ✅"),
+            None => tracing::warn!("工具配置: remove.bg API Key 未配置或无效 ❌，请确保已在 src/tools/config.rs 中设置正确的加密密钥！"),
         }
-        println!("=====================");
     }
 }
 
@@ -55,6 +55,7 @@ fn create_us_identity() -> VirtualIdentity {
 
 // Axum 路由处理函数 (简化，不再接收 Query 参数)
 pub async fn handle_get_fake_identity() -> impl IntoResponse {
+    crate::metrics::metrics().tool_requests.with_label_values(&["fake_identity"]).inc();
     // 始终调用生成美国身份的函数
     let identity = create_us_identity();
     (StatusCode::OK, Json(identity))
@@ -10,13 +10,14 @@ use image::{
     ImageEncoder, // 需要 ImageEncoder trait 来调用 write_image
     ImageFormat,
     Rgba,
-    GenericImageView, // 需要 GenericImageView 来获取尺寸等
 };
 use imagequant;
 use serde::Deserialize;
 use std::io::Cursor;
 use tracing;
 
+use crate::api::ApiError;
+
 #[derive(Deserialize)]
 pub struct ResizeParams {
     target_size_kb: usize,
@@ -26,7 +27,7 @@ pub struct ResizeParams {
 pub async fn handle_resize_image(
     Query(params): Query<ResizeParams>,
     mut multipart: Multipart,
-) -> Result<Response, (StatusCode, String)> {
+) -> Result<Response, ApiError> {
     let target_bytes = params.target_size_kb * 1024;
     let requested_format_str = params.output_format.unwrap_or_else(|| "auto".to_string());
 
@@ -36,27 +37,33 @@ pub async fn handle_resize_image(
         requested_format_str
     );
 
-    while let Some(field) = multipart.next_field().await.unwrap() {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::bad_request(format!("无效的上传数据: {}", e)))?
+    {
         if field.name().unwrap_or("") == "image" {
             let data = field.bytes().await.map_err(|e| {
                 tracing::error!("读取图片数据失败: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "无法读取图片数据".to_string())
+                ApiError::bad_request("无法读取图片数据")
             })?;
 
+            crate::metrics::metrics().tool_used("resize_image", data.len());
+
             let input_format = image::guess_format(&data).map_err(|e| {
                 tracing::error!("无法识别图片格式: {}", e);
-                (StatusCode::BAD_REQUEST, "无法识别图片格式".to_string())
+                ApiError::bad_request("无法识别图片格式")
             })?;
             tracing::info!("输入图片格式: {:?}", input_format);
 
             if input_format != ImageFormat::Jpeg && input_format != ImageFormat::Png {
-                return Err((StatusCode::BAD_REQUEST, "仅支持JPEG/PNG输入格式".to_string()));
+                return Err(ApiError::bad_request("仅支持JPEG/PNG输入格式"));
             }
 
             // --- 修改点：不再指定格式加载，让 image 库自动推断 ---
             let img = image::load_from_memory(&data).map_err(|e| {
                 tracing::error!("加载图片失败: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "无法加载图片".to_string())
+                ApiError::bad_request("无法加载图片")
             })?;
             // --- 修改结束 ---
 
@@ -67,7 +74,7 @@ pub async fn handle_resize_image(
             };
             tracing::info!("目标输出格式: {:?}", output_format);
 
-            let mut final_output_data: Vec<u8> = Vec::new();
+            let mut final_output_data: Vec<u8>;
 
             // --- JPEG 处理逻辑 ---
             if output_format == ImageFormat::Jpeg {
@@ -109,7 +116,7 @@ pub async fn handle_resize_image(
                     if let Err(e) = encode_result {
                         tracing::error!("JPEG 编码失败 (尝试 {}, 质量 {}): {}", i, quality, e);
                         if best_jpeg_data.is_empty() && last_over_target_data.is_none() {
-                             return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("首次JPEG编码失败: {}", e)));
+                             return Err(ApiError::internal(format!("首次JPEG编码失败: {}", e)));
                         }
                         break;
                     }
@@ -140,7 +147,7 @@ pub async fn handle_resize_image(
                     final_output_data = last_data;
                 } else {
                      tracing::error!("未能生成有效的 JPEG 数据");
-                     return Err((StatusCode::INTERNAL_SERVER_ERROR, "未能生成 JPEG 数据".to_string()));
+                     return Err(ApiError::internal("未能生成 JPEG 数据"));
                 }
 
             // --- PNG 处理逻辑 (不变) ---
@@ -162,7 +169,7 @@ pub async fn handle_resize_image(
                         Ok(_) => {},
                         Err(e) => {
                              tracing::error!("初始 PNG 编码失败: {}", e);
-                             return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("初始 PNG 编码失败: {}", e)));
+                             return Err(ApiError::internal(format!("初始 PNG 编码失败: {}", e)));
                         }
                     }
                      tracing::info!("初始 PNG (最佳无损压缩) 编码大小: {} bytes", initial_png_data.len());
@@ -218,10 +225,7 @@ pub async fn handle_resize_image(
                         "填充后大小不匹配！预期 {}, 实际 {}",
                         target_bytes, final_output_data.len()
                     );
-                    return Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "填充文件大小失败".to_string(),
-                    ));
+                    return Err(ApiError::internal("填充文件大小失败"));
                 }
             } else {
                 tracing::info!(
@@ -233,13 +237,11 @@ pub async fn handle_resize_image(
 
             if final_output_data.is_empty() {
                 tracing::error!("未能成功生成最终图片数据");
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "未能成功处理图片".to_string(),
-                ));
+                return Err(ApiError::internal("未能成功处理图片"));
             }
 
             tracing::info!("最终返回文件大小: {} bytes", final_output_data.len());
+            crate::metrics::metrics().tool_output("resize_image", final_output_data.len());
 
             let mime_type = match output_format {
                 ImageFormat::Jpeg => "image/jpeg",
@@ -255,7 +257,7 @@ pub async fn handle_resize_image(
                 .into_response());
         }
     }
-    Err((StatusCode::BAD_REQUEST, "缺少图片文件".to_string()))
+    Err(ApiError::bad_request("缺少图片文件"))
 }
 
 // 辅助函数：计算 JPEG 质量 (不变)
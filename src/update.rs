@@ -0,0 +1,276 @@
+// 手动数据更新：鉴权、按客户端冷却和后台任务登记
+//
+// POST /api/update 通过鉴权后创建后台任务并立即返回任务 ID，
+// 任务状态可通过 GET /api/update/jobs/{id} 查询。
+
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::github::webhook::verify_signature;
+use crate::github::{ArticleChanges, GitHubData, RefreshReport};
+
+/// 保留的历史任务数量
+const MAX_JOBS: usize = 20;
+/// HMAC 签名请求允许的时间偏差
+const SIGNATURE_MAX_SKEW_SECS: i64 = 300;
+
+pub struct UpdateConfig;
+
+impl UpdateConfig {
+    /// 获取更新接口的访问令牌（UPDATE_API_TOKEN），未配置时接口关闭
+    pub fn get_token() -> Option<String> {
+        std::env::var("UPDATE_API_TOKEN")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+    }
+
+    /// 同一客户端两次触发更新的最小间隔（UPDATE_COOLDOWN_SECS，默认 300 秒）
+    pub fn get_cooldown() -> Duration {
+        let secs = std::env::var("UPDATE_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+        Duration::from_secs(secs)
+    }
+}
+
+/// 校验请求：`Authorization: Bearer <token>`，或
+/// `X-Update-Timestamp` + `X-Update-Signature: sha256=HMAC(token, "{timestamp}.{body}")`
+pub fn authorize(headers: &HeaderMap, body: &[u8], token: &str) -> bool {
+    if let Some(bearer) = headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return constant_time_eq(bearer.trim().as_bytes(), token.as_bytes());
+    }
+
+    let timestamp = headers.get("X-Update-Timestamp").and_then(|v| v.to_str().ok());
+    let signature = headers.get("X-Update-Signature").and_then(|v| v.to_str().ok());
+    let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+        return false;
+    };
+    let Ok(ts) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (Utc::now().timestamp() - ts).abs() > SIGNATURE_MAX_SKEW_SECS {
+        return false;
+    }
+
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    verify_signature(token, &message, signature)
+}
+
+/// 比较两个令牌：先各自计算 SHA-256，再以常量时间比较定长摘要，比较耗时不会泄露令牌长度或相同前缀的长度
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let (a, b) = (Sha256::digest(a), Sha256::digest(b));
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// 任务状态，GET /api/update/jobs/{id} 无需鉴权即可查询，不包含触发者的 IP 等信息
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateJob {
+    pub id: String,
+    pub status: JobStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub message: Option<String>,
+    pub projects: Option<usize>,
+    pub articles: Option<usize>,
+    pub article_changes: Option<ArticleChanges>,
+    pub report: Option<RefreshReport>,
+}
+
+/// 触发更新的结果
+pub enum StartOutcome {
+    Started(UpdateJob),
+    /// 已有任务在运行，返回该任务
+    AlreadyRunning(UpdateJob),
+    /// 客户端仍在冷却中，附带剩余时间
+    CoolingDown(Duration),
+}
+
+#[derive(Default)]
+struct JobsInner {
+    jobs: VecDeque<UpdateJob>,
+    last_trigger: HashMap<String, Instant>,
+}
+
+/// 更新任务登记表
+#[derive(Default)]
+pub struct UpdateJobs {
+    inner: Mutex<JobsInner>,
+}
+
+impl UpdateJobs {
+    /// 检查冷却并登记新任务
+    pub fn try_start(&self, client: &str, cooldown: Duration) -> StartOutcome {
+        let mut inner = self.inner.lock().unwrap();
+
+        if let Some(job) = inner.jobs.iter().find(|j| j.status == JobStatus::Running) {
+            return StartOutcome::AlreadyRunning(job.clone());
+        }
+
+        let now = Instant::now();
+        if let Some(last) = inner.last_trigger.get(client) {
+            let elapsed = now.duration_since(*last);
+            if elapsed < cooldown {
+                return StartOutcome::CoolingDown(cooldown - elapsed);
+            }
+        }
+        inner.last_trigger.retain(|_, t| now.duration_since(*t) < cooldown);
+        inner.last_trigger.insert(client.to_string(), now);
+
+        let job = UpdateJob {
+            id: format!("{:016x}", rand::rng().random::<u64>()),
+            status: JobStatus::Running,
+            started_at: Utc::now(),
+            finished_at: None,
+            message: None,
+            projects: None,
+            articles: None,
+            article_changes: None,
+            report: None,
+        };
+        inner.jobs.push_front(job.clone());
+        inner.jobs.truncate(MAX_JOBS);
+
+        StartOutcome::Started(job)
+    }
+
+    /// 标记任务结束，附带本次刷新的报告
+    pub fn finish(&self, id: &str, result: Result<&GitHubData, String>, report: Option<RefreshReport>) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(job) = inner.jobs.iter_mut().find(|j| j.id == id) {
+            job.finished_at = Some(Utc::now());
+            job.report = report;
+            match result {
+                Ok(data) => {
+                    job.status = JobStatus::Succeeded;
+                    job.projects = Some(data.projects.len());
+                    job.articles = Some(data.articles.len());
+                    job.message = Some(match (&job.report, &data.article_changes) {
+                        (Some(report), _) => format!(
+                            "成功更新了 {} 个项目和 {} 篇文章（{}）",
+                            data.projects.len(), data.articles.len(), report.summary()
+                        ),
+                        (None, Some(changes)) => format!(
+                            "成功更新了 {} 个项目和 {} 篇文章（{}）",
+                            data.projects.len(), data.articles.len(), changes.summary()
+                        ),
+                        (None, None) => format!("成功更新了 {} 个项目和 {} 篇文章", data.projects.len(), data.articles.len()),
+                    });
+                    job.article_changes = data.article_changes.clone();
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.message = Some(e);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<UpdateJob> {
+        let inner = self.inner.lock().unwrap();
+        inner.jobs.iter().find(|j| j.id == id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const TOKEN: &str = "s3cret-update-token";
+
+    fn bearer(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn bearer_token_required() {
+        assert!(!authorize(&HeaderMap::new(), b"", TOKEN));
+        assert!(!authorize(&bearer(TOKEN), b"", TOKEN));
+        assert!(!authorize(&bearer("Bearer "), b"", TOKEN));
+    }
+
+    #[test]
+    fn wrong_bearer_token_rejected() {
+        assert!(!authorize(&bearer("Bearer wrong"), b"", TOKEN));
+        assert!(!authorize(&bearer("Bearer s3cret-update-token-and-more"), b"", TOKEN));
+        assert!(!authorize(&bearer("Bearer s3cret-update-toke"), b"", TOKEN));
+        assert!(!authorize(&bearer("Basic s3cret-update-token"), b"", TOKEN));
+    }
+
+    #[test]
+    fn correct_bearer_token_accepted() {
+        assert!(authorize(&bearer("Bearer s3cret-update-token"), b"", TOKEN));
+        assert!(authorize(&bearer("Bearer s3cret-update-token "), b"{}", TOKEN));
+    }
+
+    #[test]
+    fn constant_time_eq_compares_content() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+        assert!(!constant_time_eq(b"", b"abc"));
+    }
+
+    #[test]
+    fn second_start_while_running_is_rejected() {
+        let jobs = UpdateJobs::default();
+        let StartOutcome::Started(first) = jobs.try_start("203.0.113.1", Duration::ZERO) else {
+            panic!("第一次应创建任务");
+        };
+        // 其他客户端也不能同时启动第二个任务
+        match jobs.try_start("203.0.113.2", Duration::ZERO) {
+            StartOutcome::AlreadyRunning(job) => assert_eq!(job.id, first.id),
+            _ => panic!("已有任务运行时应返回该任务"),
+        }
+
+        jobs.finish(&first.id, Err("失败".to_string()), None);
+        assert_eq!(jobs.get(&first.id).unwrap().status, JobStatus::Failed);
+        assert!(matches!(jobs.try_start("203.0.113.2", Duration::ZERO), StartOutcome::Started(_)));
+    }
+
+    #[test]
+    fn same_client_cools_down() {
+        let jobs = UpdateJobs::default();
+        let StartOutcome::Started(job) = jobs.try_start("203.0.113.1", Duration::from_secs(300)) else {
+            panic!("第一次应创建任务");
+        };
+        jobs.finish(&job.id, Err("失败".to_string()), None);
+        match jobs.try_start("203.0.113.1", Duration::from_secs(300)) {
+            StartOutcome::CoolingDown(remaining) => assert!(remaining > Duration::from_secs(290)),
+            _ => panic!("冷却期内应拒绝"),
+        }
+        assert!(matches!(jobs.try_start("203.0.113.2", Duration::from_secs(300)), StartOutcome::Started(_)));
+    }
+
+    #[test]
+    fn job_view_omits_client() {
+        let jobs = UpdateJobs::default();
+        let StartOutcome::Started(job) = jobs.try_start("203.0.113.1", Duration::ZERO) else {
+            panic!("第一次应创建任务");
+        };
+        let json = serde_json::to_string(&jobs.get(&job.id).unwrap()).unwrap();
+        assert!(!json.contains("203.0.113.1"), "{}", json);
+    }
+}
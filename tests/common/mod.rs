@@ -0,0 +1,149 @@
+// 本地 GitHub fixture 服务器：用 fixtures/github 下的文件模拟 REST、GraphQL 和 raw 文件下载，
+// 集成测试在进程内启动它，examples/github_fixture_server.rs 把它作为独立服务器运行。
+//
+// 路径映射：
+//   GET  /users/xjz6626/repos  -> fixtures/github/rest/users/xjz6626/repos(.json)
+//   POST /graphql              -> fixtures/github/graphql/<operationName>.json
+//   GET  /raw/<path>           -> fixtures/github/raw/<path>
+//   任意 /status/<code>/<path>  -> 直接返回该状态码，用于模拟 GitHub 出错
+// 文件中的 {{base}} 会替换为服务器地址，用于 download_url。
+
+#![allow(dead_code)]
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{Path as UrlPath, State},
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::{any, post},
+};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/github");
+
+/// fixture 服务器收到的请求数
+static REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// 到目前为止 fixture 服务器收到的请求数
+pub fn requests() -> usize {
+    REQUESTS.load(Ordering::SeqCst)
+}
+
+/// 读取 fixture 文件并替换 {{base}}，先按原路径查找，再尝试追加 .json
+fn read_fixture(base: &str, path: &Path) -> Option<String> {
+    let json_path = path.with_file_name(format!("{}.json", path.file_name()?.to_str()?));
+    [path.to_path_buf(), json_path]
+        .iter()
+        .find(|p| p.is_file())
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .map(|content| content.replace("{{base}}", base))
+}
+
+fn respond(base: &str, path: PathBuf) -> Response {
+    // 拒绝跳出 fixture 目录的路径
+    if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match read_fixture(base, &path) {
+        Some(content) => {
+            tracing::info!("200 {}", path.display());
+            content.into_response()
+        }
+        None => {
+            tracing::info!("404 {}", path.display());
+            (StatusCode::NOT_FOUND, Json(serde_json::json!({ "message": "Not Found" }))).into_response()
+        }
+    }
+}
+
+async fn rest(State(base): State<String>, uri: Uri) -> Response {
+    REQUESTS.fetch_add(1, Ordering::SeqCst);
+    // download_url 中的文件名是编码后的（空格、#、中文），按磁盘上的文件名查找
+    let path = percent_encoding::percent_decode_str(uri.path()).decode_utf8_lossy();
+    let path = path.trim_matches('/');
+    let path = match path.strip_prefix("raw/") {
+        Some(raw) => Path::new(FIXTURE_DIR).join("raw").join(raw),
+        None => Path::new(FIXTURE_DIR).join("rest").join(path),
+    };
+    respond(&base, path)
+}
+
+async fn graphql(State(base): State<String>, body: Bytes) -> Response {
+    REQUESTS.fetch_add(1, Ordering::SeqCst);
+    let operation = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v.get("operationName").and_then(|o| o.as_str()).map(str::to_string));
+    match operation {
+        Some(operation) => respond(&base, Path::new(FIXTURE_DIR).join("graphql").join(operation)),
+        None => (StatusCode::BAD_REQUEST, "缺少 operationName").into_response(),
+    }
+}
+
+async fn status(UrlPath((code, path)): UrlPath<(u16, String)>) -> Response {
+    REQUESTS.fetch_add(1, Ordering::SeqCst);
+    let status = StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    tracing::info!("{} {}", status.as_u16(), path);
+    (status, Json(serde_json::json!({ "message": "fixture error" }))).into_response()
+}
+
+/// fixture 服务器的路由，base 为服务器地址（如 http://127.0.0.1:18282）
+pub fn fixture_router(base: String) -> Router {
+    Router::new()
+        .route("/graphql", post(graphql))
+        .route("/status/{code}/{*path}", any(status))
+        .fallback(rest)
+        .with_state(base)
+}
+
+/// 在独立线程中启动 fixture 服务器（各测试的运行时结束后仍可用），返回服务器地址
+pub fn start_fixture_server() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("无法绑定 fixture 端口");
+    listener.set_nonblocking(true).unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let router = fixture_router(base.clone());
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            axum::serve(listener, router).await.unwrap();
+        });
+    });
+    base
+}
+
+/// 把 GitHub 的 API 和 raw 地址指向 api_base，并使用仓库中的项目规则
+///
+/// 只能在测试开始时（各测试共用的 OnceLock 初始化中）调用，见 fixture_server()
+pub fn point_github_at(api_base: &str, raw_base: &str) {
+    // SAFETY: 调用方在 OnceLock 初始化中调用，其他测试线程在 OnceLock 上等待，此时没有线程读取环境变量
+    unsafe {
+        std::env::set_var("GITHUB_API_URL", api_base);
+        std::env::set_var("GITHUB_RAW_URL", format!("{}/raw", raw_base));
+        std::env::remove_var("GITHUB_GRAPHQL_URL");
+        std::env::remove_var("GITHUB_OFFLINE");
+        std::env::set_var(
+            "PROJECT_RULES_FILE",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/config/projects.json"),
+        );
+    }
+}
+
+/// 启动 fixture 服务器并把 GitHub 地址指向它，每个测试都先调用本函数
+pub fn fixture_server() -> &'static str {
+    static BASE: OnceLock<String> = OnceLock::new();
+    BASE.get_or_init(|| {
+        let base = start_fixture_server();
+        point_github_at(&base, &base);
+        base
+    })
+}
+
+/// 每个测试使用单独的临时数据目录
+pub fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("xjz-website-fixture-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
@@ -0,0 +1,125 @@
+// REST 和 GraphQL 两种数据后端对 fixture 服务器的完整刷新：两者得到的项目、文章、Release、置顶和贡献日历应与 fixtures/github 一致
+
+mod common;
+
+use common::{data_dir, fixture_server};
+use xjz_website::github::backend::GitHubBackend;
+use xjz_website::github::client::GitHubClient;
+use xjz_website::github::graphql::GraphQlClient;
+use xjz_website::github::report::RefreshTrigger;
+use xjz_website::github::{GitHubData, GitHubDataManager};
+
+async fn refresh(backend: Box<dyn GitHubBackend>) -> GitHubData {
+    let dir = data_dir(backend.name());
+    let manager = GitHubDataManager::with_backend(backend, &dir, "xjz6626");
+    let data = manager.update_data(RefreshTrigger::Manual).await.expect("刷新失败");
+    let report = manager.last_report().expect("缺少刷新报告");
+    assert!(report.success, "刷新报告: {}", report.summary());
+    let _ = std::fs::remove_dir_all(&dir);
+    data
+}
+
+/// 两种后端都应得到的数据
+fn check_common(data: &GitHubData) {
+    // 项目：fixture 中的两个仓库都符合 config/projects.json 的规则，配置置顶的 xjz-website 排在最前
+    let names: Vec<&str> = data.projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["xjz-website", "demo-notes"]);
+    let website = &data.projects[0];
+    assert!(website.pinned);
+    assert_eq!(website.display_name.as_deref(), Some("XJZ 个人网站"));
+    assert_eq!(website.stargazers_count, 3);
+    assert_eq!(website.topics, ["rust", "axum", "website"]);
+    assert_eq!(website.latest_version.as_deref(), Some("v0.2.0"));
+    assert_eq!(data.projects[1].stargazers_count, 5);
+    assert_eq!(data.projects[1].latest_version, None);
+
+    // 文章：demo-notes 根目录下除 README 以外的 Markdown 文件，part1/part2 归入同一系列
+    let mut articles: Vec<(&str, &str)> = data
+        .articles
+        .iter()
+        .map(|a| (a.file_path.as_str(), a.title.as_str()))
+        .collect();
+    articles.sort();
+    assert_eq!(
+        articles,
+        [
+            ("fedora-server-part1.md", "Fedora 服务器搭建（一）：安装系统"),
+            ("fedora-server-part2.md", "Fedora 服务器搭建（二）：部署网站"),
+            ("rust-async.md", "Rust 异步入门"),
+            ("rust-error-handling.md", "Rust 错误处理"),
+            ("部署 笔记 #1.md", "网站部署清单"),
+        ]
+    );
+    // 文件名中的空格、# 和中文逐段编码，两种后端得到相同的地址，也能正常下载
+    let deploy = data.articles.iter().find(|a| a.file_path == "部署 笔记 #1.md").unwrap();
+    assert_eq!(
+        deploy.file_url,
+        "https://github.com/xjz6626/demo-notes/blob/main/%E9%83%A8%E7%BD%B2%20%E7%AC%94%E8%AE%B0%20%231.md"
+    );
+    assert!(deploy.content.starts_with("# 网站部署清单"));
+    assert!(data.articles.iter().all(|a| a.repo_name == "demo-notes" && !a.content.is_empty()));
+    let part1 = data.articles.iter().find(|a| a.file_path == "fedora-server-part1.md").unwrap();
+    let series = part1.series.as_ref().expect("part1 应属于系列");
+    assert_eq!(series.name, "Fedora 服务器搭建");
+    assert_eq!((series.index, series.total), (1, 2));
+    assert_eq!(series.next.as_ref().map(|n| n.title.as_str()), Some("Fedora 服务器搭建（二）：部署网站"));
+
+    // Release 和 README
+    let releases: Vec<&str> = data.project_details["xjz-website"].releases.iter().map(|r| r.tag_name.as_str()).collect();
+    assert_eq!(releases, ["v0.2.0"]);
+    assert!(data.project_details["demo-notes"].releases.is_empty());
+    assert!(data.project_details["xjz-website"].readme.as_deref().is_some_and(|r| r.starts_with("# xjz-website")));
+
+    // 贡献日历（两种后端都通过 GraphQL 获取）和最近动态
+    let calendar = data.activity.contributions.as_ref().expect("缺少贡献日历");
+    assert_eq!(calendar.total, 264);
+    assert_eq!(calendar.weeks.len(), 12);
+    let day = &calendar.weeks[0][1];
+    assert_eq!((day.date.to_string().as_str(), day.count, day.level), ("2026-07-27", 5, 3));
+    assert_eq!(data.activity.events.len(), 4);
+    assert_eq!(data.notes.len(), 2);
+}
+
+#[tokio::test]
+async fn rest_backend_matches_fixtures() {
+    fixture_server();
+    let data = refresh(Box::new(GitHubClient::new().unwrap())).await;
+    check_common(&data);
+    // REST API 不提供个人主页置顶信息，只有配置中的置顶
+    assert!(!data.projects[1].pinned);
+}
+
+#[tokio::test]
+async fn graphql_backend_matches_fixtures() {
+    fixture_server();
+    let data = refresh(Box::new(GraphQlClient::new(GitHubClient::new().unwrap()))).await;
+    check_common(&data);
+    // GitHub 个人主页置顶的 demo-notes 追加到配置的置顶之后
+    assert!(data.projects[1].pinned);
+}
+
+#[tokio::test]
+async fn concurrent_stale_requests_refresh_once() {
+    fixture_server();
+    let dir = data_dir("single-flight");
+    // 已过期的缓存
+    let stale = GitHubData {
+        next_update: chrono::Utc::now() - chrono::Duration::hours(1),
+        ..GitHubData::default()
+    };
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("github_xjz6626.json"), serde_json::to_vec(&stale).unwrap()).unwrap();
+    let managers: Vec<GitHubDataManager> = (0..5)
+        .map(|_| GitHubDataManager::with_backend(Box::new(GitHubClient::new().unwrap()), &dir, "xjz6626"))
+        .collect();
+
+    // 缓存过期时同时到达的请求：第一个执行刷新，其余等待后直接使用它写入的数据
+    let results = futures_util::future::join_all(managers.iter().map(|m| m.get_data())).await;
+    for data in results {
+        let data = data.expect("获取数据失败");
+        assert_eq!(data.projects.len(), 2);
+        assert!(!data.degraded);
+    }
+    assert_eq!(managers[0].load_history().len(), 1, "应只刷新一次");
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,38 @@
+// API 预算很小（GITHUB_API_BUDGET）的刷新：预算用完后未处理的仓库保留上次的文章，不会被当作删除
+//
+// 修改 GITHUB_API_BUDGET 会影响同一进程中其他测试创建的客户端，因此单独放在一个测试文件中
+
+mod common;
+
+use common::{data_dir, fixture_server};
+use xjz_website::github::client::GitHubClient;
+use xjz_website::github::report::{RefreshTrigger, RepoStatus};
+use xjz_website::github::GitHubDataManager;
+
+#[tokio::test]
+async fn small_budget_keeps_cached_articles() {
+    fixture_server();
+    let dir = data_dir("small-budget");
+    let manager = GitHubDataManager::with_backend(Box::new(GitHubClient::new().unwrap()), &dir, "xjz6626");
+    let previous = manager.update_data(RefreshTrigger::Manual).await.expect("刷新失败");
+    assert_eq!(previous.articles.len(), 5);
+
+    // SAFETY: 本文件只有这一个测试，此时没有其他线程读取环境变量
+    unsafe { std::env::set_var("GITHUB_API_BUDGET", "1") };
+    let manager = GitHubDataManager::with_backend(Box::new(GitHubClient::new().unwrap()), &dir, "xjz6626");
+    let data = manager.update_data(RefreshTrigger::Manual).await.expect("刷新失败");
+    let report = manager.last_report().unwrap();
+    assert!(report.success, "刷新报告: {}", report.summary());
+    assert_eq!(report.api_calls, 1);
+
+    // 仓库列表用掉了唯一的一次预算，文章目录无法获取，两个仓库都记为跳过并保留上次的文章
+    let mut ids: Vec<&str> = data.articles.iter().map(|a| a.id.as_str()).collect();
+    let mut previous_ids: Vec<&str> = previous.articles.iter().map(|a| a.id.as_str()).collect();
+    ids.sort();
+    previous_ids.sort();
+    assert_eq!(ids, previous_ids);
+    let changes = report.article_changes.as_ref().unwrap();
+    assert!(changes.removed.is_empty());
+    assert!(report.repos.iter().all(|r| r.status == RepoStatus::Skipped), "{:?}", report.repos);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,56 @@
+// GitHub 不可用时的降级：fixture 服务器对所有 API 请求返回 500，过期的缓存应作为降级数据返回，
+// 失败后的重试间隔从 60 秒开始翻倍
+//
+// 重试退避状态在进程内共享，因此单独放在一个测试文件中，且只有一个测试
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{data_dir, point_github_at, start_fixture_server};
+use xjz_website::github::client::GitHubClient;
+use xjz_website::github::report::RefreshTrigger;
+use xjz_website::github::{GitHubData, GitHubDataManager};
+
+fn seconds_until(at: Option<chrono::DateTime<Utc>>) -> i64 {
+    (at.expect("缺少 retry_at") - Utc::now()).num_seconds()
+}
+
+#[tokio::test]
+async fn stale_cache_is_served_degraded_with_backoff() {
+    let base = start_fixture_server();
+    point_github_at(&format!("{}/status/500", base), &base);
+
+    let dir = data_dir("degraded");
+    let last_updated = Utc::now() - Duration::days(2);
+    let stale = GitHubData {
+        last_updated,
+        next_update: Utc::now() - Duration::hours(1),
+        ..GitHubData::default()
+    };
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("github_xjz6626.json"), serde_json::to_vec(&stale).unwrap()).unwrap();
+    let manager = GitHubDataManager::with_backend(Box::new(GitHubClient::new().unwrap()), &dir, "xjz6626");
+
+    // 刷新失败，返回带降级标记的旧数据，60 秒内不再重试
+    let requests = common::requests();
+    let data = manager.get_data().await.expect("应返回缓存数据");
+    assert!(data.degraded);
+    assert_eq!(data.last_updated, last_updated);
+    assert!((58..=60).contains(&seconds_until(data.retry_at)));
+    assert!(common::requests() > requests, "应尝试访问 GitHub");
+
+    // 退避期内直接使用缓存，不访问 GitHub
+    let requests = common::requests();
+    let data = manager.get_data().await.unwrap();
+    assert!(data.degraded);
+    assert_eq!(common::requests(), requests);
+
+    // 再次失败后间隔翻倍
+    assert!(manager.update_data(RefreshTrigger::Manual).await.is_err());
+    let data = manager.get_data().await.unwrap();
+    assert!(data.degraded);
+    assert!((118..=120).contains(&seconds_until(data.retry_at)));
+    assert_eq!(manager.load_history().len(), 2);
+    assert!(manager.load_history().iter().all(|report| !report.success));
+    let _ = std::fs::remove_dir_all(&dir);
+}
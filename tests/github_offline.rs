@@ -0,0 +1,48 @@
+// 离线模式（GITHUB_OFFLINE=1 或启动参数 --offline）：不发出任何网络请求，只使用本地缓存
+//
+// 离线标记在进程内共享，因此单独放在一个测试文件中，且只有一个测试
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{data_dir, fixture_server};
+use xjz_website::github::client::GitHubClient;
+use xjz_website::github::config::GitHubConfig;
+use xjz_website::github::{GitHubData, GitHubDataManager};
+
+#[tokio::test]
+async fn offline_mode_serves_cache_without_requests() {
+    fixture_server();
+    let dir = data_dir("offline");
+    let last_updated = Utc::now() - Duration::days(2);
+    let stale = GitHubData {
+        last_updated,
+        next_update: Utc::now() - Duration::hours(1),
+        ..GitHubData::default()
+    };
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("github_xjz6626.json"), serde_json::to_vec(&stale).unwrap()).unwrap();
+
+    // SAFETY: 本文件只有这一个测试，fixture_server() 已设置完其余环境变量，此时没有其他线程读取环境变量
+    unsafe { std::env::set_var("GITHUB_OFFLINE", "1") };
+    let requests = common::requests();
+    let manager = GitHubDataManager::with_backend(Box::new(GitHubClient::new().unwrap()), &dir, "xjz6626");
+    let data = manager.get_data().await.expect("应返回缓存数据");
+    assert!(data.degraded);
+    assert_eq!(data.last_updated, last_updated);
+    assert_eq!(data.retry_at, None);
+    assert!(manager.force_update().await.is_err());
+
+    // --offline 启动参数
+    // SAFETY: 同上
+    unsafe { std::env::remove_var("GITHUB_OFFLINE") };
+    assert!(!GitHubConfig::is_offline());
+    GitHubConfig::set_offline(true);
+    let data = manager.get_data().await.expect("应返回缓存数据");
+    assert!(data.degraded);
+    assert_eq!(data.last_updated, last_updated);
+    assert!(manager.force_update().await.is_err());
+
+    assert_eq!(common::requests(), requests, "离线模式下不应访问 GitHub");
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,218 @@
+// 增量刷新对 fixture 服务器的行为：SHA 未变化的文件直接复用，仓库中删除的文件记为删除，
+// 下载失败或 API 预算用完时保留上次的版本，等下次刷新重试
+
+mod common;
+
+use async_trait::async_trait;
+use common::{data_dir, fixture_server};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use xjz_website::github::backend::GitHubBackend;
+use xjz_website::github::client::{BudgetExhausted, GitHubClient};
+use xjz_website::github::report::{RefreshReport, RefreshTrigger, RepoStatus};
+use xjz_website::github::rules::ProjectRules;
+use xjz_website::github::{
+    Commit, ContributionCalendar, Event, Gist, GitHubData, GitHubDataManager, Release, RepoContent, Repository, User,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// 变化文件的下载结果
+#[derive(Clone, Copy)]
+enum Download {
+    /// 正常下载
+    Ok,
+    /// fixture 服务器返回该状态码
+    Status(u16),
+    /// 本次刷新的 API 预算已用完
+    OverBudget,
+}
+
+/// 包装 REST 客户端：从目录列表中去掉 deleted，把 changed 的 SHA 改为新值，并记录下载了哪些文件
+struct ScriptedRepo {
+    inner: GitHubClient,
+    base: &'static str,
+    deleted: &'static [&'static str],
+    changed: &'static [&'static str],
+    download: Download,
+    /// 下载过的文件名，测试在管理器接管后端后仍可读取
+    downloads: Arc<Mutex<Vec<String>>>,
+}
+
+impl ScriptedRepo {
+    fn new(deleted: &'static [&'static str], changed: &'static [&'static str], download: Download) -> Self {
+        Self {
+            inner: GitHubClient::new().unwrap(),
+            base: fixture_server(),
+            deleted,
+            changed,
+            download,
+            downloads: Arc::default(),
+        }
+    }
+
+    fn downloads(&self) -> Arc<Mutex<Vec<String>>> {
+        self.downloads.clone()
+    }
+}
+
+#[async_trait]
+impl GitHubBackend for ScriptedRepo {
+    fn name(&self) -> &'static str {
+        "scripted"
+    }
+
+    async fn get_featured_repos(&self, rules: &ProjectRules) -> Result<Vec<Repository>> {
+        self.inner.get_featured_repos(rules).await
+    }
+
+    async fn get_pinned_repos(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_repo(&self, repo_name: &str) -> Result<Option<Repository>> {
+        self.inner.get_repo(repo_name).await
+    }
+
+    async fn get_user_info(&self) -> Result<User> {
+        self.inner.get_user_info().await
+    }
+
+    async fn get_contribution_calendar(&self) -> Result<ContributionCalendar> {
+        GitHubBackend::get_contribution_calendar(&self.inner).await
+    }
+
+    async fn get_public_events(&self, per_page: u32) -> Result<Vec<Event>> {
+        self.inner.get_public_events(per_page).await
+    }
+
+    async fn get_user_gists(&self, per_page: u32) -> Result<Vec<Gist>> {
+        self.inner.get_user_gists(per_page).await
+    }
+
+    async fn get_repo_readme(&self, repo_name: &str) -> Result<Option<String>> {
+        self.inner.get_repo_readme(repo_name).await
+    }
+
+    async fn get_repo_languages(&self, repo_name: &str) -> Result<HashMap<String, u64>> {
+        self.inner.get_repo_languages(repo_name).await
+    }
+
+    async fn get_repo_commits(&self, repo_name: &str, per_page: u32) -> Result<Vec<Commit>> {
+        self.inner.get_repo_commits(repo_name, per_page).await
+    }
+
+    async fn get_repo_releases(&self, repo_name: &str, per_page: u32) -> Result<Vec<Release>> {
+        self.inner.get_repo_releases(repo_name, per_page).await
+    }
+
+    async fn get_repo_contents(&self, repo_name: &str, path: &str) -> Result<Vec<RepoContent>> {
+        let mut contents = self.inner.get_repo_contents(repo_name, path).await?;
+        contents.retain(|item| !self.deleted.contains(&item.path.as_str()));
+        for item in contents.iter_mut().filter(|item| self.changed.contains(&item.path.as_str())) {
+            item.sha = "f".repeat(40);
+            if let Download::Status(code) = self.download {
+                item.download_url = Some(format!("{}/status/{}/{}", self.base, code, item.path));
+            }
+        }
+        Ok(contents)
+    }
+
+    async fn get_file_content(&self, download_url: &str) -> Result<String> {
+        let name = download_url.rsplit('/').next().unwrap_or_default().to_string();
+        let over_budget = matches!(self.download, Download::OverBudget) && self.changed.contains(&name.as_str());
+        self.downloads.lock().unwrap().push(name);
+        if over_budget {
+            return Err(Box::new(BudgetExhausted(0)));
+        }
+        self.inner.get_file_content(download_url).await
+    }
+
+    fn api_calls(&self) -> u32 {
+        self.inner.api_calls()
+    }
+
+    fn remaining_budget(&self) -> u32 {
+        self.inner.remaining_budget()
+    }
+
+    fn rate_limit_remaining(&self) -> Option<u32> {
+        self.inner.rate_limit_remaining()
+    }
+}
+
+/// 用 backend 在 dir 中刷新一次，返回数据和刷新报告
+async fn refresh(backend: Box<dyn GitHubBackend>, dir: &Path) -> (GitHubData, RefreshReport) {
+    let manager = GitHubDataManager::with_backend(backend, dir, "xjz6626");
+    let data = manager.update_data(RefreshTrigger::Manual).await.expect("刷新失败");
+    let report = manager.last_report().expect("缺少刷新报告");
+    assert!(report.success, "刷新报告: {}", report.summary());
+    (data, report)
+}
+
+/// 先用真实的 REST 客户端完整刷新一次，作为上次刷新的结果
+async fn seed(name: &str) -> (std::path::PathBuf, GitHubData) {
+    fixture_server();
+    let dir = data_dir(name);
+    let (data, _) = refresh(Box::new(GitHubClient::new().unwrap()), &dir).await;
+    assert_eq!(data.articles.len(), 5);
+    (dir, data)
+}
+
+fn paths(data: &GitHubData) -> Vec<&str> {
+    let mut paths: Vec<&str> = data.articles.iter().map(|a| a.file_path.as_str()).collect();
+    paths.sort();
+    paths
+}
+
+#[tokio::test]
+async fn unchanged_reused_deleted_removed_failed_kept() {
+    let (dir, previous) = seed("incremental").await;
+    let old_async = previous.articles.iter().find(|a| a.file_path == "rust-async.md").unwrap();
+
+    let backend = ScriptedRepo::new(&["rust-error-handling.md"], &["rust-async.md"], Download::Status(500));
+    let downloads = backend.downloads();
+    let (data, report) = refresh(Box::new(backend), &dir).await;
+    assert_eq!(*downloads.lock().unwrap(), ["rust-async.md"]);
+
+    // 仓库中删除的文件记为删除；下载失败的文件保留旧版本和旧 SHA，未变化的文件没有重新下载
+    assert_eq!(paths(&data), ["fedora-server-part1.md", "fedora-server-part2.md", "rust-async.md", "部署 笔记 #1.md"]);
+    let kept = data.articles.iter().find(|a| a.file_path == "rust-async.md").unwrap();
+    assert_eq!(kept.sha, old_async.sha);
+    assert_eq!(kept.content, old_async.content);
+
+    let changes = report.article_changes.as_ref().unwrap();
+    assert_eq!(changes.removed, ["demo-notes-rust-error-handling"]);
+    assert!(changes.added.is_empty() && changes.changed.is_empty());
+    assert_eq!(changes.unchanged, 4);
+
+    let repo = report.repos.iter().find(|r| r.name == "demo-notes").unwrap();
+    assert_eq!(repo.status, RepoStatus::Error);
+    assert!(repo.reasons.iter().any(|r| r.contains("rust-async.md") && r.contains("保留旧版本")), "{:?}", repo.reasons);
+
+    // 保留的是旧 SHA，下次刷新会重新下载
+    let backend = ScriptedRepo::new(&["rust-error-handling.md"], &["rust-async.md"], Download::Ok);
+    let downloads = backend.downloads();
+    let (_, report) = refresh(Box::new(backend), &dir).await;
+    assert_eq!(*downloads.lock().unwrap(), ["rust-async.md"]);
+    assert_eq!(report.article_changes.unwrap().changed, ["demo-notes-rust-async"]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn over_budget_downloads_are_deferred() {
+    let (dir, previous) = seed("over-budget").await;
+
+    let backend = ScriptedRepo::new(&[], &["rust-async.md", "fedora-server-part1.md"], Download::OverBudget);
+    let (data, report) = refresh(Box::new(backend), &dir).await;
+
+    // 预算用完的文件保留旧版本，不算删除也不算失败
+    assert_eq!(paths(&data), paths(&previous));
+    let changes = report.article_changes.as_ref().unwrap();
+    assert!(changes.removed.is_empty() && changes.changed.is_empty());
+    let repo = report.repos.iter().find(|r| r.name == "demo-notes").unwrap();
+    assert_eq!(repo.status, RepoStatus::Success);
+    assert_eq!(repo.reasons, ["2 个文件因 API 预算用完，留到下次刷新"]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
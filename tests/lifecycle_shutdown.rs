@@ -0,0 +1,38 @@
+// 收到 SIGTERM 后的关闭流程：取消 shutdown_token，之后不再修改 GitHub 数据，drain_deadline 开始计时
+//
+// 会取消进程内全局的 shutdown_token，所以放在单独的测试程序中
+
+use std::time::{Duration, Instant};
+use xjz_website::github::client::GitHubClient;
+use xjz_website::github::GitHubDataManager;
+use xjz_website::lifecycle;
+
+#[tokio::test]
+async fn sigterm_cancels_background_work() {
+    let signal = tokio::spawn(lifecycle::shutdown_signal());
+    // 等 shutdown_signal 注册好 SIGTERM 处理，否则默认行为会直接结束测试进程
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!lifecycle::is_shutting_down());
+
+    let status = std::process::Command::new("kill")
+        .args(["-TERM", &std::process::id().to_string()])
+        .status()
+        .expect("无法执行 kill");
+    assert!(status.success());
+    tokio::time::timeout(Duration::from_secs(5), signal)
+        .await
+        .expect("没有收到 SIGTERM")
+        .unwrap();
+    assert!(lifecycle::is_shutting_down());
+
+    // 关闭过程中不再删除仓库数据
+    let dir = std::env::temp_dir().join(format!("xjz-website-shutdown-{}", std::process::id()));
+    let manager = GitHubDataManager::with_backend(Box::new(GitHubClient::new().unwrap()), &dir, "xjz6626");
+    let error = manager.remove_repo("demo-notes").await.unwrap_err();
+    assert!(error.to_string().contains("正在关闭"), "{}", error);
+
+    let start = Instant::now();
+    lifecycle::drain_deadline(Duration::from_millis(100)).await;
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    let _ = std::fs::remove_dir_all(&dir);
+}